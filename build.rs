@@ -1,18 +1,19 @@
 #![allow(dead_code)]
 
-#[cfg(any(feature = "nfc", feature = "nfd", feature = "nfkc", feature = "nfkd"))]
-extern crate unicode_normalization;
-
 extern crate atlatl;
 extern crate bincode;
 extern crate kl_hyphenate_commons;
 extern crate serde;
+extern crate xxhash_rust;
+#[cfg(feature = "compress")]
+extern crate zstd;
 
 use atlatl::fst;
 use bincode as bin;
+use bincode::Options;
 use serde::ser;
-use std::collections::HashMap;
-use std::hash::Hash;
+use xxhash_rust::xxh3;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::error;
 use std::fmt;
@@ -26,29 +27,41 @@ use kl_hyphenate_commons::dictionary::*;
 use kl_hyphenate_commons::dictionary::extended as ext;
 use kl_hyphenate_commons::Language;
 use kl_hyphenate_commons::parse::*;
+use kl_hyphenate_commons::provenance::Provenance;
 
 
-// Configuration of exclusive optional features
+// Configuration of the build
 
 use configuration::*;
 mod configuration {
-    // In service of configurable normalization forms, a type alias and a function
-    // are defined via conditional compilation.
-    //
-    // If no feature is explicitly set, normalization is avoided altogether.
-
-    // Neither Cargo nor rustc allows us to set exclusive features; we must indulge
-    // them with this clumsy branle of cfg declarations.
-    #[cfg(not(any(feature = "nfc", feature = "nfd", feature = "nfkc", feature = "nfkd")))]
-    pub fn normalize(s : &str) -> String { s.to_owned() }
-
-    #[cfg(any(feature = "nfc", feature = "nfd", feature = "nfkc", feature = "nfkd"))]
-    use unicode_normalization::*;
-
-    #[cfg(feature = "nfc")]  pub fn normalize(s : &str) -> String { s.nfc().collect() }
-    #[cfg(feature = "nfd")]  pub fn normalize(s : &str) -> String { s.nfd().collect() }
-    #[cfg(feature = "nfkc")] pub fn normalize(s : &str) -> String { s.nfkc().collect() }
-    #[cfg(feature = "nfkd")] pub fn normalize(s : &str) -> String { s.nfkd().collect() }
+    use std::env;
+
+    // Patterns are normalized to the form named by `KL_HYPHENATE_NORMALIZATION`,
+    // such as `nfc`, which is recorded in the provenance of every dictionary.
+    // If the variable is unset or empty, normalization is avoided altogether.
+    use kl_hyphenate_commons::normalization::NormalizationForm;
+
+    pub fn normalization() -> Option<NormalizationForm> {
+        let name = env::var("KL_HYPHENATE_NORMALIZATION").unwrap_or_default();
+        if name.trim().is_empty() { return None }
+        match name.trim().parse() {
+            Ok(form) => Some(form),
+            Err(e) => panic!("{} in `KL_HYPHENATE_NORMALIZATION`", e)
+        }
+    }
+
+    pub fn normalize(form : Option<NormalizationForm>, s : &str) -> String {
+        match form {
+            Some(form) => form.apply(s),
+            None => s.to_owned()
+        }
+    }
+
+    // Patterns are stored in the double-array trie unless otherwise requested.
+    use kl_hyphenate_commons::storage::Backend;
+
+    #[cfg(not(feature = "hash_table"))] pub const BACKEND : Backend = Backend::DoubleArray;
+    #[cfg(feature = "hash_table")]      pub const BACKEND : Backend = Backend::HashTable;
 }
 
 
@@ -58,42 +71,12 @@ trait TryFromIterator<Tally> : Sized {
             + ExactSizeIterator;
 }
 
-fn uniques<I, T>(iter : I) -> (Vec<(String, u16)>, Vec<T>)
-where T : Eq + Clone + Hash
-    , I : IntoIterator<Item = (String, T)>
-        + ExactSizeIterator
-{
-    let mut pairs = Vec::with_capacity(iter.len());
-    let mut tally_ids = HashMap::with_capacity(iter.len());
-    let mut tallies : Vec<T> = Vec::with_capacity(256);
-    for (pattern, tally) in iter {
-        match tally_ids.get(&tally) {
-            Some(&id) => pairs.push((pattern, id)),
-            None => {
-                let id = tallies.len() as u16;
-                tallies.push(tally.clone());
-                tally_ids.insert(tally, id);
-                pairs.push((pattern, id));
-            }
-        }
-    }
-    pairs.sort_by(|a, b| a.0.cmp(&b.0));
-    pairs.dedup_by(|a, b| a.0 == b.0);
-    (pairs, tallies)
-}
-
 impl TryFromIterator<<Patterns as Parse>::Tally> for Patterns {
     fn try_from_iter<I>(iter : I) -> Result<Self, Error>
     where I : IntoIterator<Item = (String, <Patterns as Parse>::Tally)>
             + ExactSizeIterator
     {
-        let (kvs, tallies) = uniques(iter);
-        let builder = fst::Builder::from_iter(kvs.into_iter()) ?;
-        let automaton : fst::FST<u32, u16> = fst::FST::from_builder(&builder) ?;
-        Ok(Patterns {
-            tallies : tallies,
-            automaton : automaton
-        })
+        Ok(Patterns::from_pairs_in(BACKEND, iter) ?)
     }
 }
 
@@ -106,18 +89,21 @@ impl TryFromIterator<<Exceptions as Parse>::Tally> for Exceptions {
     }
 }
 
+impl TryFromIterator<<ext::Exceptions as Parse>::Tally> for ext::Exceptions {
+    fn try_from_iter<I>(iter : I) -> Result<Self, Error>
+    where I : IntoIterator<Item = (String, <ext::Exceptions as Parse>::Tally)>
+            + ExactSizeIterator
+    {
+        Ok(ext::Exceptions(HashMap::from_iter(iter)))
+    }
+}
+
 impl TryFromIterator<<ext::Patterns as Parse>::Tally> for ext::Patterns {
     fn try_from_iter<I>(iter : I) -> Result<Self, Error>
     where I : IntoIterator<Item = (String, <ext::Patterns as Parse>::Tally)>
             + ExactSizeIterator
     {
-        let (kvs, tallies) = uniques(iter);
-        let builder = fst::Builder::from_iter(kvs.into_iter()) ?;
-        let automaton : fst::FST<u32, u16> = fst::FST::from_builder(&builder) ?;
-        Ok(ext::Patterns {
-            tallies : tallies,
-            automaton : automaton
-        })
+        Ok(ext::Patterns::from_pairs_in(BACKEND, iter) ?)
     }
 }
 
@@ -127,15 +113,23 @@ impl TryFromIterator<<ext::Patterns as Parse>::Tally> for ext::Patterns {
 #[derive(Clone, Debug)]
 struct Paths {
     source : PathBuf,
-    out : PathBuf
+    out : PathBuf,
+    // Where pattern files fetched from upstream are kept, if any.
+    fetched : Option<PathBuf>
 }
 
 impl Paths {
     fn new() -> Result<Self, Error> {
-        let source = env::var("CARGO_MANIFEST_DIR").map(|p| PathBuf::from(p)) ?;
-        let out = source.clone();
-
-        Ok(Paths { source, out })
+        let source = env::var("CARGO_MANIFEST_DIR").map(PathBuf::from) ?;
+        // Dictionaries are built into `OUT_DIR`, leaving the source tree alone;
+        // without `build_dictionaries`, those bundled with the crate are used.
+        let out = if cfg!(feature = "build_dictionaries") {
+            env::var("OUT_DIR").map(PathBuf::from) ?
+        } else {
+            source.clone()
+        };
+
+        Ok(Paths { source, out, fetched : None })
     }
 
     fn dest_item<P : AsRef<Path>>(&self, p : P) -> PathBuf { self.out.join(p.as_ref()) }
@@ -143,7 +137,51 @@ impl Paths {
 
     fn source_pattern(&self, lang : Language, suffix : &str) -> PathBuf {
         let fname = format!("hyph-{}.{}.txt", lang.code(), suffix);
-        self.source_item("patterns").join(fname)
+        match self.fetched {
+            Some(ref dir) if dir.join(&fname).exists() => dir.join(fname),
+            _ => self.source_item("patterns").join(fname)
+        }
+    }
+
+    /// The same paths, with every source pinned by the manifest fetched
+    /// from upstream into `OUT_DIR`, and preferred to the files of the
+    /// `patterns` folder. Files fetched by a previous build are kept as long
+    /// as they match their checksum.
+    fn fetching(self, manifest : &Manifest) -> Result<Self, Error> {
+        use std::fs;
+        use std::process::Command;
+
+        let rev = match manifest.release {
+            Some(ref release) if release.starts_with("tex-hyphen@") => &release["tex-hyphen@".len() ..],
+            _ => return Err(Error::Fetch("the manifest pins no release of tex-hyphen".to_owned()))
+        };
+        let base = env::var("KL_HYPHENATE_PATTERNS_URL").unwrap_or_else(|_| format!(
+            "https://raw.githubusercontent.com/hyphenation/tex-hyphen/{}/hyph-utf8/tex/generic/hyph-utf8/patterns/txt",
+            rev));
+        let dir = env::var("OUT_DIR").map(PathBuf::from) ?.join("patterns");
+        fs::create_dir_all(&dir) ?;
+
+        for (file, checksum) in &manifest.checksums {
+            let path = dir.join(file);
+            if path.exists() && Manifest::checksum(&path) ? == *checksum {
+                continue
+            }
+            let url = format!("{}/{}", base.trim_end_matches('/'), file);
+            eprintln!("Fetching {}", url);
+            let status = Command::new("curl")
+                .args(["--fail", "--silent", "--show-error", "--location", "--output"])
+                .arg(&path).arg(&url)
+                .status() ?;
+            if !status.success() {
+                return Err(Error::Fetch(format!("{} could not be downloaded ({})", url, status)));
+            }
+            let found = Manifest::checksum(&path) ?;
+            if found != *checksum {
+                fs::remove_file(&path) ?;
+                return Err(Error::Fetch(format!("{} has checksum {}, not the pinned {}", url, found, checksum)));
+            }
+        }
+        Ok(Paths { fetched : Some(dir), .. self })
     }
 
     fn dest_dict(&self, lang : Language, suffix : &str) -> PathBuf {
@@ -164,22 +202,114 @@ trait Build : Sized + Parse + TryFromIterator<<Self as Parse>::Tally> {
     }
 
     fn build(lang : Language, paths : &Paths) -> Result<Self, Error> {
-        let file = File::open(Self::sourcepath(lang, paths)) ?;
-        let by_line = io::BufReader::new(file).lines();
-        let pairs : Vec<_> = by_line.map(|res| Self::pair(&res.unwrap(), normalize)).collect();
+        Self::build_from(&Self::sourcepath(lang, paths))
+    }
+
+    fn build_from(path : &Path) -> Result<Self, Error> {
+        Self::build_from_all(&[path])
+    }
+
+    /// Build from the lines of every given source, in order.
+    fn build_from_all(paths : &[&Path]) -> Result<Self, Error> {
+        let form = normalization();
+        let mut pairs = vec![];
+        for path in paths {
+            let file = File::open(path) ?;
+            let by_line = io::BufReader::new(file).lines();
+            pairs.extend(by_line.map(|res| res.unwrap())
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| Self::pair(&line, |s| normalize(form, s))));
+        }
 
         Self::try_from_iter(pairs.into_iter())
     }
 }
 
+/// The release, licenses and checksums of the pattern sources, as listed by
+/// `patterns/provenance.txt`.
+#[derive(Clone, Debug, Default)]
+struct Manifest {
+    release : Option<String>,
+    licenses : HashMap<String, String>,
+    checksums : BTreeMap<String, String>
+}
+
+impl Manifest {
+    fn read(paths : &Paths) -> Result<Self, Error> {
+        let mut manifest = Manifest::default();
+        let path = paths.source_item("patterns").join("provenance.txt");
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(manifest),
+            Err(e) => return Err(e.into())
+        };
+        for line in io::BufReader::new(file).lines() {
+            let line = line ?;
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next()) {
+                (Some("release"), Some(release)) => manifest.release = Some(release.to_owned()),
+                (Some("license"), Some(file)) => {
+                    let license = fields.collect::<Vec<_>>().join(" ");
+                    manifest.licenses.insert(file.to_owned(), license);
+                },
+                (Some("checksum"), Some(file)) => if let Some(checksum) = fields.next() {
+                    manifest.checksums.insert(file.to_owned(), checksum.to_owned());
+                },
+                _ => ()
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// The checksum by which the file at the given path is pinned: its
+    /// 128-bit XXH3 hash, in hexadecimal.
+    fn checksum(path : &Path) -> Result<String, Error> {
+        use std::fs;
+        Ok(format!("{:032x}", xxh3::xxh3_128(&fs::read(path) ?)))
+    }
+
+    /// The provenance of a dictionary built from the given source files.
+    fn provenance(&self, sources : &[PathBuf]) -> Provenance {
+        let names : Vec<String> = sources.iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect();
+        let licenses : Vec<&str> = names.iter()
+            .filter_map(|name| self.licenses.get(name))
+            .map(String::as_str)
+            .collect();
+        Provenance {
+            release : self.release.clone(),
+            license : if licenses.is_empty() { None } else { Some(licenses.join(" AND ")) },
+            sources : names,
+            built : None,
+            normalization : normalization().map(|form| form.name().to_owned())
+        }.dated_by_source_epoch()
+    }
+}
+
 impl Build for Patterns   { fn suffix() -> &'static str { "pat" } }
 impl Build for Exceptions { fn suffix() -> &'static str { "hyp" } }
 impl Build for ext::Patterns { fn suffix() -> &'static str { "ext" } }
+impl Build for ext::Exceptions { fn suffix() -> &'static str { "ext.hyp" } }
 
 
+/// The `bincode` configuration shared by dictionary serialization and loading.
+fn bincode_options() -> impl Options {
+    bin::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(5_000_000)
+}
+
 fn write<T>(item : &T, path : &Path) -> Result<(), Error> where T : ser::Serialize {
-    let mut buffer = File::create(&path).map(|f| io::BufWriter::new(f)) ?;
-    bin::config().limit(5_000_000).serialize_into(&mut buffer, item) ?;
+    let body = bincode_options().serialize(item) ?;
+    let mut buffer = File::create(path).map(io::BufWriter::new) ?;
+    buffer.write_all(MAGIC) ?;
+    buffer.write_all(&VERSION.to_le_bytes()) ?;
+    buffer.write_all(&[BINCODE]) ?;
+    buffer.write_all(&xxh3::xxh3_64(&body).to_le_bytes()) ?;
+    buffer.write_all(&body) ?;
     Ok(())
 }
 
@@ -188,16 +318,23 @@ fn main() {
     #[cfg(feature = "build_dictionaries")]
     {
         use std::fs;
+        use std::sync::Arc;
         use kl_hyphenate_commons::Language::*;
         let _std_out = "standard";
         let _ext_out = "extended";
-        let dict_folder = Path::new("dictionaries");
         let paths = Paths::new().unwrap();
-        let dict_out = paths.dest_item(dict_folder);
-
-        let ext_langs = vec![Catalan, Hungarian];
+        let dict_out = paths.dest_item("dictionaries");
+        let manifest = Manifest::read(&paths).unwrap();
+        #[cfg(feature = "fetch_patterns")]
+        let paths = paths.fetching(&manifest).unwrap();
+
+        let ext_langs = [Catalan, Dutch, German1901, Hungarian, NorwegianBokmal, NorwegianNynorsk, Swedish];
+        // The extended patterns of these languages are their standard ones,
+        // supplemented by the non-standard patterns of `hyph-xx.ext.txt`, if
+        // any, rather than a complete set of their own.
+        let supplemented = [Dutch, German1901, NorwegianBokmal, NorwegianNynorsk, Swedish];
         let std_langs =
-            vec![ Afrikaans, Armenian, Assamese, Basque, Belarusian, Bengali, Bulgarian, Catalan,
+            [ Afrikaans, Armenian, Assamese, Basque, Belarusian, Bengali, Bulgarian, Catalan,
                   Chinese, Coptic, Croatian, Czech, Danish, Dutch, EnglishGB, EnglishUS, Esperanto,
                   Estonian, Ethiopic, Finnish, French, Friulan, Galician, Georgian, German1901,
                   German1996, GermanSwiss, GreekAncient, GreekMono, GreekPoly, Gujarati, Hindi,
@@ -209,16 +346,48 @@ fn main() {
                   Slovenian, Spanish, Swedish, Tamil, Telugu, Thai, Turkish, Turkmen, Ukrainian,
                   Uppersorbian, Welsh ];
 
+        // Rerun whenever the sources change, or the variables which select,
+        // locate, normalize and date them.
+        println!("cargo:rerun-if-changed=build.rs");
+        println!("cargo:rerun-if-changed=patterns");
+        println!("cargo:rerun-if-env-changed=KL_HYPHENATE_LANGUAGES");
+        println!("cargo:rerun-if-env-changed=KL_HYPHENATE_PATTERNS_URL");
+        println!("cargo:rerun-if-env-changed=KL_HYPHENATE_NORMALIZATION");
+        println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+        // Iterating on a few languages need not wait for every dictionary,
+        // e.g. `KL_HYPHENATE_LANGUAGES=en-us,de-1996,fr`.
+        let selection = languages_from_env("KL_HYPHENATE_LANGUAGES");
+        if let Some(ref langs) = selection {
+            for language in langs {
+                if !std_langs.contains(language) && !ext_langs.contains(language) {
+                    panic!("No patterns to build for `{}`", language.code());
+                }
+            }
+        }
+        let selected = |language : &Language| selection.as_ref().is_none_or(|langs| langs.contains(language));
+        let std_langs : Vec<_> = std_langs.iter().cloned().filter(selected).collect();
+        let ext_langs : Vec<_> = ext_langs.iter().cloned().filter(selected).collect();
+
         fs::create_dir_all(&dict_out).unwrap();
 
         eprintln!("Building `Standard` dictionaries:");
         for &language in std_langs.iter() {
             eprintln!("{:?}", language);
+            let mut sources = vec![Patterns::sourcepath(language, &paths)];
+            let exceptions = Exceptions::build(language, &paths).ok();
+            if exceptions.is_some() {
+                sources.push(Exceptions::sourcepath(language, &paths));
+            }
             let dict = Standard {
                 language,
-                patterns : Patterns::build(language, &paths).unwrap(),
-                exceptions : Exceptions::build(language, &paths).unwrap_or_default(),
-                minima : language.minima()
+                patterns : Arc::new(Patterns::build(language, &paths).unwrap()),
+                exceptions : Arc::new(exceptions.unwrap_or_default()),
+                minima : language.minima(),
+                provenance : manifest.provenance(&sources),
+                max_length : MAX_LENGTH,
+                normalization : normalization(),
+                case_folding : Default::default()
             };
 
             write(&dict, &paths.dest_dict(language, _std_out)).unwrap();
@@ -227,16 +396,145 @@ fn main() {
         eprintln!("Building `Extended` dictionaries:");
         for &language in ext_langs.iter() {
             eprintln!("{:?}", language);
+            // The standard exceptions of the language hold for its extended
+            // patterns too, save those respelled by the extended exceptions.
+            let mut sources = vec![ext::Patterns::sourcepath(language, &paths)];
+            if supplemented.contains(&language) {
+                sources.retain(|path| path.exists());
+                sources.push(Patterns::sourcepath(language, &paths));
+            }
+            // Of patterns of the same letters, the first is kept, such that
+            // non-standard patterns prevail over standard ones.
+            let patterns = {
+                let paths : Vec<&Path> = sources.iter().map(PathBuf::as_path).collect();
+                ext::Patterns::build_from_all(&paths).unwrap()
+            };
+            let mut exceptions = ext::Exceptions::default();
+            for path in [Exceptions::sourcepath(language, &paths), ext::Exceptions::sourcepath(language, &paths)] {
+                if let Ok(ext::Exceptions(respelled)) = ext::Exceptions::build_from(&path) {
+                    exceptions.0.extend(respelled);
+                    sources.push(path);
+                }
+            }
             let dict = Extended {
                 language,
-                patterns : ext::Patterns::build(language, &paths).unwrap(),
-                exceptions : ext::Exceptions::default(),
-                minima : language.minima()
+                patterns : Arc::new(patterns),
+                exceptions : Arc::new(exceptions),
+                minima : language.minima(),
+                provenance : manifest.provenance(&sources),
+                max_length : MAX_LENGTH,
+                normalization : normalization(),
+                case_folding : Default::default()
             };
 
             write(&dict, &paths.dest_dict(language, _ext_out)).unwrap();
         }
     }
+
+    #[cfg(feature = "embed")]
+    {
+        // Dictionaries written by this very script must not trigger a rerun.
+        #[cfg(not(feature = "build_dictionaries"))]
+        println!("cargo:rerun-if-changed=dictionaries");
+        println!("cargo:rerun-if-env-changed=KL_HYPHENATE_EMBED");
+
+        let paths = Paths::new().unwrap();
+        let out = env::var("OUT_DIR").map(PathBuf::from).unwrap();
+        embed(&paths, &out, &out.join("embedded.rs")).unwrap();
+    }
+}
+
+
+// Dictionary embedding
+
+/// Write the table of embedded dictionaries, as an expression to be included
+/// by `load.rs`. With `embed_all`, every dictionary found in the `dictionaries`
+/// folder, that of `OUT_DIR` with `build_dictionaries`, is embedded; otherwise, only those of the languages whose feature is
+/// enabled, such as `embed-en-us`. In either case, the `KL_HYPHENATE_EMBED`
+/// environment variable may restrict the selection further to a
+/// comma-separated list of language codes.
+///
+/// With the `compress` feature, dictionaries are embedded as zstd frames
+/// written next to the table. Files within `OUT_DIR` are included by a path
+/// relative to it, such that the table does not depend on where it was built.
+fn embed(paths : &Paths, out : &Path, table : &Path) -> Result<(), Error> {
+    use std::fs;
+
+    let selection = languages_from_env("KL_HYPHENATE_EMBED");
+
+    let mut entries : Vec<_> = fs::read_dir(paths.dest_item("dictionaries")) ?
+        .collect::<Result<_, _>>() ?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut featured_vars = vec![];
+    let mut buffer = File::create(table).map(io::BufWriter::new) ?;
+    writeln!(buffer, "&[") ?;
+    for entry in entries {
+        let path = entry.path();
+        let name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if name.ends_with(".bincode") => name.trim_end_matches(".bincode"),
+            _ => continue
+        };
+        let (code, suffix) = match name.rfind('.') {
+            Some(i) => (&name[.. i], &name[i + 1 ..]),
+            None => continue
+        };
+        let language : Language = match code.parse() {
+            Ok(language) if suffix == "standard" || suffix == "extended" => language,
+            _ => continue
+        };
+        let featured = cfg!(feature = "embed_all") || env::var_os(feature_var(code)).is_some();
+        featured_vars.push(feature_var(code));
+        if featured && selection.as_ref().is_none_or(|langs| langs.contains(&language)) {
+            #[cfg(feature = "compress")]
+            let path = compress(&path, table.with_file_name(format!("{}.zst", name))) ?;
+            let included = match path.strip_prefix(out) {
+                Ok(relative) => {
+                    let relative = relative.to_str().ok_or(Error::Resource) ?.replace('\\', "/");
+                    format!("concat!(env!(\"OUT_DIR\"), {:?})", format!("/{}", relative))
+                },
+                Err(_) => format!("{:?}", path.to_str().ok_or(Error::Resource) ?)
+            };
+            writeln!(buffer, "    (Language::{:?}, {:?}, include_bytes!({})),", language, suffix, included) ?;
+        }
+    }
+    writeln!(buffer, "]") ?;
+
+    // Languages requested by feature must not go missing silently.
+    for (var, _) in env::vars_os() {
+        let var = var.to_string_lossy();
+        if var.starts_with("CARGO_FEATURE_EMBED_") && var != "CARGO_FEATURE_EMBED_ALL"
+            && !featured_vars.iter().any(|v| *v == var) {
+            panic!("No dictionary to embed for feature `{}`", var);
+        }
+    }
+    Ok(())
+}
+
+/// The languages listed by the given environment variable, as a
+/// comma-separated list of language codes, if it is set.
+fn languages_from_env(var : &str) -> Option<Vec<Language>> {
+    env::var(var).ok().map(|codes| {
+        codes.split(',').map(str::trim).filter(|code| !code.is_empty()).map(|code| {
+            code.parse().unwrap_or_else(|_| panic!("Unknown language in `{}`: `{}`", var, code))
+        }).collect()
+    })
+}
+
+/// The variable by which Cargo signals the embedding feature of a language.
+fn feature_var(code : &str) -> String {
+    format!("CARGO_FEATURE_EMBED_{}", code.to_uppercase().replace('-', "_"))
+}
+
+/// Compress a dictionary at the highest regular level of zstd, returning the
+/// path of the compressed copy.
+#[cfg(feature = "compress")]
+fn compress(path : &Path, dest : PathBuf) -> Result<PathBuf, Error> {
+    let source = File::open(path).map(io::BufReader::new) ?;
+    let compressed = zstd::encode_all(source, 19) ?;
+    let mut file = File::create(&dest) ?;
+    file.write_all(&compressed) ?;
+    Ok(dest)
 }
 
 
@@ -248,6 +546,7 @@ pub enum Error {
     Env(env::VarError),
     IO(io::Error),
     Serialization(bin::Error),
+    Fetch(String),
     Resource
     // TODO: Parsing
 }
@@ -271,6 +570,7 @@ impl fmt::Display for Error {
             Error::Env(ref e) => e.fmt(f),
             Error::IO(ref e) => e.fmt(f),
             Error::Serialization(ref e) => e.fmt(f),
+            Error::Fetch(ref message) => f.write_str(message),
             Error::Resource => f.write_str("dictionary could not be embedded")
         }
     }
@@ -0,0 +1,117 @@
+#ifndef KL_HYPHENATE_H
+#define KL_HYPHENATE_H
+
+/* Generated by cbindgen from `src/ffi.rs`; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// A standard dictionary, opaque to C.
+typedef struct HyphenationDictionary HyphenationDictionary;
+
+// The breaks of a word, as byte indices into it, in ascending order.
+//
+// The list is owned by the caller, and must be freed with
+// `hyphenation_free_breaks`, unless it belongs to a `HyphenationWord`. A
+// null `indices` denotes a failure, whereas a word without breaks has a
+// non-null `indices` of length 0.
+typedef struct HyphenationBreaks {
+  size_t *indices;
+  size_t len;
+} HyphenationBreaks;
+
+// A hyphenated word: the text given to `hyphenation_hyphenate_word`, and its
+// breaks.
+//
+// The text is borrowed from the caller, and is neither copied nor freed;
+// it must outlive the word. The breaks are owned by the word, and freed
+// along with it by `hyphenation_free_word`.
+typedef struct HyphenationWord {
+  const char *text;
+  size_t len;
+  struct HyphenationBreaks breaks;
+} HyphenationWord;
+
+// Load the standard dictionary for the language of the given code from the
+// serialized file at `path`, or return null if it cannot be loaded.
+//
+// # Safety
+//
+// `language` and `path` must be null or point to null-terminated strings.
+// The dictionary must be freed with `hyphenation_free`.
+HyphenationDictionary *hyphenation_load(const char *language, const char *path);
+
+// Hyphenate the given word, returning its segments joined by `marker`, or
+// the hyphen-minus if `marker` is null. Null is returned if the dictionary or
+// the word is null, or if either string is not valid UTF-8.
+//
+// # Safety
+//
+// `dict` must be null or have been returned by `hyphenation_load`, and not
+// yet freed. `word` and `marker` must be null or point to null-terminated
+// strings. The result must be freed with `hyphenation_free_string`.
+char *hyphenation_hyphenate(const HyphenationDictionary *dict,
+                            const char *word,
+                            const char *marker);
+
+// The breaks of the word of `len` bytes at `word`, which is not necessarily
+// null-terminated. The list is null if the dictionary or the word is null,
+// or if the word is not valid UTF-8.
+//
+// # Safety
+//
+// `dict` must be null or have been returned by `hyphenation_load`, and not
+// yet freed. `word` must be null or point to `len` readable bytes. The
+// result must be freed with `hyphenation_free_breaks`.
+struct HyphenationBreaks hyphenation_breaks(const HyphenationDictionary *dict,
+                                            const char *word,
+                                            size_t len);
+
+// Hyphenate the word of `len` bytes at `word`, as by `hyphenation_breaks`,
+// returning it along with its breaks.
+//
+// # Safety
+//
+// As for `hyphenation_breaks`; `word` must moreover outlive the result,
+// which must be freed with `hyphenation_free_word`.
+struct HyphenationWord hyphenation_hyphenate_word(const HyphenationDictionary *dict,
+                                                  const char *word,
+                                                  size_t len);
+
+// Free a dictionary returned by `hyphenation_load`. Null is ignored.
+//
+// # Safety
+//
+// `dict` must be null or have been returned by `hyphenation_load`, and not
+// yet freed.
+void hyphenation_free(HyphenationDictionary *dict);
+
+// Free a string returned by `hyphenation_hyphenate`. Null is ignored.
+//
+// # Safety
+//
+// `s` must be null or have been returned by `hyphenation_hyphenate`, and not
+// yet freed.
+void hyphenation_free_string(char *s);
+
+// Free a list of breaks returned by `hyphenation_breaks`. A failure, whose
+// `indices` is null, is ignored.
+//
+// # Safety
+//
+// `breaks` must have been returned by `hyphenation_breaks`, and not yet
+// freed.
+void hyphenation_free_breaks(struct HyphenationBreaks breaks);
+
+// Free the breaks of a word returned by `hyphenation_hyphenate_word`, leaving
+// its text to the caller.
+//
+// # Safety
+//
+// `word` must have been returned by `hyphenation_hyphenate_word`, and not
+// yet freed.
+void hyphenation_free_word(struct HyphenationWord word);
+
+#endif /* KL_HYPHENATE_H */
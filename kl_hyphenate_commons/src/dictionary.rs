@@ -1,10 +1,40 @@
 //! Data structures for the storage of hyphenation patterns and exceptions.
 
-use atlatl::fst::FST;
+use atlatl::fst;
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::hash::Hash;
+use std::io;
+use std::iter::FromIterator;
+use std::sync::Arc;
+use footprint::{Footprint, HeapSize};
 use language::Language;
+use normalization::{CaseFolding, NormalizationForm};
+use parse::{self, Parse};
+use provenance::Provenance;
+use storage::{sorted, Automaton, Backend, Storage};
 
 
+/// The leading bytes of every serialized dictionary, followed by the version
+/// of its format as a little-endian `u32`, the byte denoting the serializer
+/// of the rest of the dictionary, then by the xxh3 checksum of the rest as a
+/// little-endian `u64`.
+pub const MAGIC : &[u8] = b"KLHD";
+
+/// The version of the serialized dictionary format, raised whenever older
+/// dictionaries cease to be readable.
+pub const VERSION : u32 = 6;
+
+/// The byte denoting dictionaries serialized with `bincode`, in which the
+/// bundled dictionaries are built.
+pub const BINCODE : u8 = 0;
+
+/// The length in bytes beyond which dictionaries leave words unhyphenated by
+/// default, see `Standard::with_max_length`.
+pub const MAX_LENGTH : usize = 1024;
+
+fn max_length() -> usize { MAX_LENGTH }
+
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Locus {
     pub index : u8,
@@ -15,25 +45,353 @@ pub struct Locus {
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Patterns {
     pub tallies : Vec<Vec<Locus>>,
-    pub automaton : FST<u32, u16>
+    pub automaton : Automaton
+}
+
+impl Patterns {
+    /// Build a trie of the given patterns, each given by its letters and its
+    /// tally, such as `Parse::pair` produces. Patterns need not be sorted; of
+    /// duplicate patterns, the first is kept.
+    pub fn from_pairs<I>(pairs : I) -> Result<Self, fst::Error>
+    where I : IntoIterator<Item = (String, Vec<Locus>)> {
+        Patterns::from_pairs_in(Backend::default(), pairs)
+    }
+
+    /// Build the given patterns as by `from_pairs`, stored in the given
+    /// backend.
+    pub fn from_pairs_in<I>(backend : Backend, pairs : I) -> Result<Self, fst::Error>
+    where I : IntoIterator<Item = (String, Vec<Locus>)> {
+        let (pairs, tallies) = uniques(pairs) ?;
+        Ok(Patterns { tallies, automaton : Automaton::build(backend, pairs) ? })
+    }
+
+    /// The same patterns, stored in the given backend.
+    pub fn with_backend(&self, backend : Backend) -> Result<Self, fst::Error> {
+        let automaton = Automaton::build(backend, self.automaton.entries()) ?;
+        Ok(Patterns { tallies : self.tallies.clone(), automaton })
+    }
+
+    /// Every pattern of the trie, in lexicographic order, along with its tally.
+    pub fn entries(&self) -> Vec<(String, &Vec<Locus>)> {
+        self.automaton.entries().into_iter()
+            .filter_map(|(pattern, id)| self.tallies.get(id as usize).map(|tally| (pattern, tally)))
+            .collect()
+    }
+
+    /// Every pattern beginning with the given prefix, in lexicographic order,
+    /// along with its tally.
+    pub fn with_prefix(&self, prefix : &str) -> Vec<(String, &Vec<Locus>)> {
+        self.entries().into_iter().filter(|(pattern, _)| pattern.starts_with(prefix)).collect()
+    }
+
+    /// Every pattern which applies to the given lowercase word, as it is
+    /// scored between the dots that mark its boundaries, along with the byte
+    /// offset at which it applies within the dotted word, and its tally.
+    /// Patterns are in order of their offset, then of their length.
+    pub fn matches(&self, word : &str) -> Vec<(usize, String, &Vec<Locus>)> {
+        matches(self.entries(), word)
+    }
+}
+
+/// The given patterns which apply to the dotted word, by offset and length.
+fn matches<'p, T>(entries : Vec<(String, &'p T)>, word : &str) -> Vec<(usize, String, &'p T)> {
+    let dotted = [".", word, "."].concat();
+    let mut matches = vec![];
+    for (pattern, tally) in entries {
+        // Occurrences of a pattern may overlap, as "aa" does within "aaa".
+        for (i, _) in dotted.char_indices().filter(|&(i, _)| dotted[i ..].starts_with(pattern.as_str())) {
+            matches.push((i, pattern.clone(), tally));
+        }
+    }
+    matches.sort_by_key(|&(i, ref pattern, _)| (i, pattern.len()));
+    matches
+}
+
+/// The exceptions declared by the `\hyphenation{…}` groups of a TeX text,
+/// lowercased.
+fn tex_exceptions(tex : &str) -> Result<Vec<(String, Vec<usize>)>, &'static str> {
+    let (_, exceptions) = parse::tex_groups(tex) ?;
+    Ok(exceptions.into_iter()
+        .map(|exception| Exceptions::pair(&exception.to_lowercase(), |s| s.to_owned()))
+        .collect())
+}
+
+/// Patterns along with the index of their tally, and the tallies they index.
+type Numbered<T> = (Vec<(String, u16)>, Vec<T>);
+
+/// Sort the patterns and deduplicate them, along with their tallies, which
+/// patterns refer to by index.
+///
+/// Tallies are numbered in the order of the first pattern to bear each, such
+/// that the same patterns yield the same tallies, whatever the order in which
+/// they are given. Patterns bearing more distinct tallies than a `u16` can
+/// number are reported as `fst::Error::OutOfBounds`.
+fn uniques<I, T>(iter : I) -> Result<Numbered<T>, fst::Error>
+where T : Eq + Clone + Hash
+    , I : IntoIterator<Item = (String, T)>
+{
+    let mut patterns : Vec<(String, T)> = iter.into_iter().collect();
+    // The sort is stable, such that the first of duplicate patterns is kept.
+    patterns.sort_by(|a, b| a.0.cmp(&b.0));
+    patterns.dedup_by(|a, b| a.0 == b.0);
+
+    let mut pairs = Vec::with_capacity(patterns.len());
+    let mut tally_ids = HashMap::new();
+    let mut tallies : Vec<T> = Vec::with_capacity(256);
+    for (pattern, tally) in patterns {
+        match tally_ids.get(&tally) {
+            Some(&id) => pairs.push((pattern, id)),
+            None => {
+                let id = u16::try_from(tallies.len()).map_err(|_| fst::Error::OutOfBounds {
+                    reached : tallies.len() + 1,
+                    maximum : u16::MAX as usize + 1
+                }) ?;
+                tallies.push(tally.clone());
+                tally_ids.insert(tally, id);
+                pairs.push((pattern, id));
+            }
+        }
+    }
+    Ok((pairs, tallies))
 }
 
+/// The hasher of the maps of exceptions. With the `ahash` feature, aHash with
+/// fixed keys is used in place of the slower SipHash of the standard library,
+/// whose resistance to collision attacks is of no use for words read from
+/// trusted dictionaries.
+#[cfg(feature = "ahash")]
+pub type Hasher = ::std::hash::BuildHasherDefault<::ahash::AHasher>;
+#[cfg(not(feature = "ahash"))]
+pub type Hasher = ::std::collections::hash_map::RandomState;
+
 /// A specialized hashmap associating words to their known hyphenation.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
-pub struct Exceptions(pub HashMap<String, Vec<usize>>);
+pub struct Exceptions(#[serde(serialize_with = "sorted")] pub HashMap<String, Vec<usize>, Hasher>);
+
+impl Exceptions {
+    /// Exceptions given as words marked with hyphens at their breaks, such as
+    /// `"ta-ble"`. Words are lowercased, as dictionaries are searched for them.
+    pub fn from_strings<S>(exceptions : &[S]) -> Self
+    where S : AsRef<str> {
+        exceptions.iter().map(|e| Exceptions::pair(&e.as_ref().to_lowercase(), |s| s.to_owned())).collect()
+    }
+
+    /// Every word with a known hyphenation, along with the byte indices of
+    /// its breaks, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[usize])> + '_ {
+        self.0.iter().map(|(word, breaks)| (word.as_str(), breaks.as_slice()))
+    }
+
+    /// The number of words with a known hyphenation.
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Whether no word has a known hyphenation.
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+}
+
+impl FromIterator<(String, Vec<usize>)> for Exceptions {
+    fn from_iter<I>(iter : I) -> Self
+    where I : IntoIterator<Item = (String, Vec<usize>)> {
+        Exceptions(HashMap::from_iter(iter))
+    }
+}
 
 /// A dictionary for standard Knuth–Liang hyphenation.
 ///
 /// It comprises the working language, the pattern and exception sets,
 /// and the character boundaries for hyphenation.
+///
+/// The patterns and exceptions are shared between clones, such that cloning
+/// a dictionary is cheap; exceptions are copied when first modified, with
+/// `Arc::make_mut`.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Standard {
     pub language : Language,
-    pub patterns : Patterns,
-    pub exceptions : Exceptions,
+    pub patterns : Arc<Patterns>,
+    pub exceptions : Arc<Exceptions>,
     /// The minimum number of `char`s from the start and end of a word where breaks
     /// may not occur.
-    pub minima : (usize, usize)
+    pub minima : (usize, usize),
+    /// Where the patterns came from, as recorded when the dictionary was
+    /// built.
+    pub provenance : Provenance,
+    /// The normalization form of the patterns, if any, to which words are
+    /// normalized before they are hyphenated, as recorded when the dictionary
+    /// was built.
+    pub normalization : Option<NormalizationForm>,
+    /// The length in bytes beyond which words are left unhyphenated. Unlike
+    /// the minima, it is not serialized with the dictionary.
+    #[serde(skip, default = "max_length")]
+    pub max_length : usize,
+    /// How the words to hyphenate are folded. Like the maximum length, it is
+    /// a setting of the loaded dictionary rather than of its patterns: it is
+    /// neither serialized nor packed, and is `Lowercase` until set otherwise.
+    #[serde(skip)]
+    pub case_folding : CaseFolding
+}
+
+impl Standard {
+    /// Build a dictionary from TeX patterns, declared in the `\patterns{…}`
+    /// and `\hyphenation{…}` groups of the text read from `reader`. Unlike the
+    /// bundled dictionaries, the patterns are not normalized; the exceptions
+    /// are lowercased, as by `add_tex_exceptions`.
+    ///
+    /// Malformed TeX, such as unbalanced braces, is reported as an error of
+    /// kind `io::ErrorKind::InvalidData`.
+    pub fn from_tex<R>(language : Language, reader : &mut R) -> io::Result<Self>
+    where R : io::Read + ?Sized {
+        let invalid = |e : &str| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut tex = String::new();
+        reader.read_to_string(&mut tex) ?;
+        let (patterns, _) = parse::tex_groups(&tex).map_err(invalid) ?;
+
+        let mut dict = Standard::from_pattern_strings(language, &patterns) ?;
+        dict.add_tex_exceptions(&tex).map_err(invalid) ?;
+        Ok(dict)
+    }
+
+    /// Build a dictionary from a libhyphen dictionary, as used by LibreOffice
+    /// and Firefox, read from `reader`. Its `LEFTHYPHENMIN` and
+    /// `RIGHTHYPHENMIN` headers take precedence over the minima of the
+    /// language.
+    ///
+    /// Non-standard patterns are reduced to their standard part, and the
+    /// patterns of compound words, which follow `NEXTLEVEL`, are left out;
+    /// `parse::Dic` gives access to the whole dictionary. `Extended::from_dic`
+    /// honors non-standard patterns.
+    pub fn from_dic<R>(language : Language, reader : &mut R) -> io::Result<Self>
+    where R : io::Read + ?Sized {
+        let invalid = |e : &str| io::Error::new(io::ErrorKind::InvalidData, e);
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes) ?;
+        let dic = parse::Dic::parse(&bytes).map_err(invalid) ?;
+
+        let patterns : Vec<&str> = dic.patterns.iter()
+            .map(|p| p.split('/').next().unwrap_or(p))
+            .collect();
+        let dict = Standard::from_pattern_strings(language, &patterns) ?;
+        let (left, right) = language.minima();
+        Ok(dict.with_minima((dic.minima.0.unwrap_or(left), dic.minima.1.unwrap_or(right))))
+    }
+
+    /// Add the exceptions declared by the `\hyphenation{…}` groups of a TeX
+    /// text, such as a journal class file, and return how many were read.
+    /// Other groups are ignored.
+    ///
+    /// Words are lowercased, as the dictionary is searched for them; known
+    /// exceptions for the same words are replaced.
+    pub fn add_tex_exceptions(&mut self, tex : &str) -> Result<usize, &'static str> {
+        let exceptions = tex_exceptions(tex) ?;
+        let count = exceptions.len();
+        Arc::make_mut(&mut self.exceptions).0.extend(exceptions);
+        Ok(count)
+    }
+
+    /// Build a dictionary, without exceptions, from patterns given by their
+    /// letters and their tally.
+    pub fn from_pairs<I>(language : Language, pairs : I) -> Result<Self, fst::Error>
+    where I : IntoIterator<Item = (String, Vec<Locus>)> {
+        Ok(Standard {
+            language,
+            patterns : Arc::new(Patterns::from_pairs(pairs) ?),
+            exceptions : Arc::default(),
+            minima : language.minima(),
+            provenance : Provenance::default(),
+            normalization : None,
+            max_length : MAX_LENGTH,
+            case_folding : CaseFolding::default()
+        })
+    }
+
+    /// Build a dictionary, without exceptions, from patterns in TeX notation,
+    /// such as `"hy3ph"`.
+    ///
+    /// Patterns which cannot be built into a dictionary are reported as an
+    /// error of kind `io::ErrorKind::InvalidData`, as by
+    /// `Extended::from_pattern_strings`.
+    pub fn from_pattern_strings<S>(language : Language, patterns : &[S]) -> io::Result<Self>
+    where S : AsRef<str> {
+        let verbatim = |s : &str| s.to_owned();
+        Standard::from_pairs(language, patterns.iter().map(|p| Patterns::pair(p.as_ref(), verbatim)))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// The same dictionary, with the given exceptions in place of its own.
+    pub fn with_exceptions(self, exceptions : Exceptions) -> Self {
+        Standard { exceptions : Arc::new(exceptions), .. self }
+    }
+
+    /// A new dictionary holding the patterns and exceptions of both `self` and
+    /// `other`, such as a house set of patterns layered over those of hyph-utf8.
+    ///
+    /// Where both dictionaries hold a pattern of the same letters, or an
+    /// exception for the same word, that of `other` prevails. The language, the
+    /// minima and the backend of the patterns are those of `self`, and the
+    /// provenance that of both, as by `Provenance::merge`.
+    pub fn merge(&self, other : &Standard) -> Result<Self, fst::Error> {
+        let patterns = other.patterns.entries().into_iter()
+            .chain(self.patterns.entries())
+            .map(|(letters, tally)| (letters, tally.clone()));
+        let mut exceptions = (*self.exceptions).clone();
+        exceptions.0.extend(other.exceptions.0.iter().map(|(w, ops)| (w.clone(), ops.clone())));
+        Ok(Standard {
+            language : self.language,
+            patterns : Arc::new(Patterns::from_pairs_in(self.patterns.automaton.backend(), patterns) ?),
+            exceptions : Arc::new(exceptions),
+            minima : self.minima,
+            provenance : self.provenance.merge(&other.provenance),
+            normalization : self.normalization,
+            max_length : self.max_length,
+            case_folding : self.case_folding
+        })
+    }
+
+    /// The same dictionary, with the given minima in place of its own. Being
+    /// part of the dictionary, the minima are serialized along with it.
+    pub fn with_minima(self, minima : (usize, usize)) -> Self {
+        Standard { minima, .. self }
+    }
+
+    /// The same dictionary, with the given provenance in place of its own.
+    pub fn with_provenance(self, provenance : Provenance) -> Self {
+        Standard { provenance, .. self }
+    }
+
+    /// The same dictionary, normalizing the words it hyphenates to the given
+    /// form, which should be that of its patterns, in place of its own.
+    pub fn with_normalization(self, normalization : Option<NormalizationForm>) -> Self {
+        Standard { normalization, .. self }
+    }
+
+    /// The same dictionary, leaving words longer than `max_length` bytes
+    /// unhyphenated, in place of `MAX_LENGTH`. The length is not serialized,
+    /// and must be set anew whenever the dictionary is loaded.
+    pub fn with_max_length(self, max_length : usize) -> Self {
+        Standard { max_length, .. self }
+    }
+
+    /// The same dictionary, folding the words it hyphenates as given, in place
+    /// of their lowercase. Like the maximum length, the folding is neither
+    /// serialized nor packed.
+    pub fn with_case_folding(self, case_folding : CaseFolding) -> Self {
+        Standard { case_folding, .. self }
+    }
+
+    /// Whether the minima of the dictionary differ from the defaults of its
+    /// language, as given by `Language::minima`.
+    pub fn overrides_minima(&self) -> bool {
+        self.minima != self.language.minima()
+    }
+
+    /// The approximate heap usage of the dictionary's automaton, tallies and
+    /// exceptions.
+    pub fn memory_footprint(&self) -> Footprint {
+        Footprint {
+            automaton : self.patterns.automaton.heap_size(),
+            tallies : self.patterns.tallies.heap_size(),
+            exceptions : self.exceptions.0.heap_size()
+        }
+    }
 }
 
 
@@ -42,11 +400,20 @@ pub struct Standard {
 pub use self::extended::Extended;
 
 pub mod extended {
-    use atlatl::fst::FST;
+    use atlatl::fst;
     use std::collections::HashMap;
+    use std::io;
+    use std::iter::FromIterator;
+    use std::ops;
+    use std::sync::Arc;
 
+    use footprint::{Footprint, HeapSize};
     use language::Language;
-    use super::Locus;
+    use normalization::{CaseFolding, NormalizationForm};
+    use parse::{self, Parse};
+    use provenance::Provenance;
+    use storage::{sorted, Automaton, Backend, Storage};
+    use super::{Locus, MAX_LENGTH};
 
     /// The partial score carried by an extended hyphenation pattern.
     #[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -73,29 +440,321 @@ pub mod extended {
         pub breakpoint : usize,
     }
 
+    impl Subregion {
+        /// A subregion replacing `left` bytes before the break and `right`
+        /// bytes after it with `substitution`, which is broken at the byte
+        /// index `breakpoint`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `breakpoint` does not lie on a char boundary of the
+        /// substitution.
+        pub fn new<S>(left : usize, right : usize, substitution : S, breakpoint : usize) -> Self
+        where S : Into<String> {
+            let substitution = substitution.into();
+            assert!(substitution.is_char_boundary(breakpoint),
+                    "Breakpoint {} lies outside of the substitution `{}`", breakpoint, substitution);
+            Subregion { left, right, substitution, breakpoint }
+        }
+
+        /// The number of bytes replaced before the break.
+        pub fn left(&self) -> usize { self.left }
+
+        /// The number of bytes replaced after the break.
+        pub fn right(&self) -> usize { self.right }
+
+        /// The text replacing the bytes about the break.
+        pub fn substitution(&self) -> &str { &self.substitution }
+
+        /// The byte index within the substitution at which the word breaks.
+        pub fn breakpoint(&self) -> usize { self.breakpoint }
+
+        /// The range of bytes of the word replaced, given the index of the
+        /// break to which the subregion belongs, unless the subregion reaches
+        /// before the start of the word.
+        pub fn span(&self, index : usize) -> Option<ops::Range<usize>> {
+            Some(index.checked_sub(self.left) ? .. index.checked_add(self.right) ?)
+        }
+
+        /// The word broken at the given index, to which the subregion belongs,
+        /// as spelled before and after the break, unless the subregion reaches
+        /// past either end of the word, or within one of its chars.
+        pub fn split(&self, word : &str, index : usize) -> Option<(String, String)> {
+            let span = self.span(index) ?;
+            let (fore, aft) = self.substitution.split_at(self.breakpoint);
+            let (before, after) = (word.get(.. span.start) ?, word.get(span.end ..) ?);
+            Some(([before, fore].concat(), [aft, after].concat()))
+        }
+    }
+
     /// A trie mapping hyphenation patterns to their extended tallies.
     #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
     pub struct Patterns {
         pub tallies : Vec<Tally>,
-        pub automaton : FST<u32, u16>
+        pub automaton : Automaton
+    }
+
+    impl Patterns {
+        /// Build a trie of the given extended patterns, each given by its
+        /// letters and its tally, such as `Parse::pair` produces. Patterns
+        /// need not be sorted; of duplicate patterns, the first is kept.
+        pub fn from_pairs<I>(pairs : I) -> Result<Self, fst::Error>
+        where I : IntoIterator<Item = (String, Tally)> {
+            Patterns::from_pairs_in(Backend::default(), pairs)
+        }
+
+        /// Build the given patterns as by `from_pairs`, stored in the given
+        /// backend.
+        pub fn from_pairs_in<I>(backend : Backend, pairs : I) -> Result<Self, fst::Error>
+        where I : IntoIterator<Item = (String, Tally)> {
+            let (pairs, tallies) = super::uniques(pairs) ?;
+            Ok(Patterns { tallies, automaton : Automaton::build(backend, pairs) ? })
+        }
+
+        /// The same patterns, stored in the given backend.
+        pub fn with_backend(&self, backend : Backend) -> Result<Self, fst::Error> {
+            let automaton = Automaton::build(backend, self.automaton.entries()) ?;
+            Ok(Patterns { tallies : self.tallies.clone(), automaton })
+        }
+
+        /// Every pattern of the trie, in lexicographic order, along with its
+        /// tally.
+        pub fn entries(&self) -> Vec<(String, &Tally)> {
+            self.automaton.entries().into_iter()
+                .filter_map(|(pattern, id)| self.tallies.get(id as usize).map(|tally| (pattern, tally)))
+                .collect()
+        }
+
+        /// Every pattern beginning with the given prefix, in lexicographic
+        /// order, along with its tally.
+        pub fn with_prefix(&self, prefix : &str) -> Vec<(String, &Tally)> {
+            self.entries().into_iter().filter(|(pattern, _)| pattern.starts_with(prefix)).collect()
+        }
+
+        /// Every pattern which applies to the given lowercase word, along
+        /// with the byte offset at which it applies within the dotted word,
+        /// and its tally, as by the `matches` of standard patterns.
+        pub fn matches(&self, word : &str) -> Vec<(usize, String, &Tally)> {
+            super::matches(self.entries(), word)
+        }
     }
 
     /// A specialized hashmap associating words to their known hyphenation.
     #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
-    pub struct Exceptions(pub HashMap<String, Vec<(usize, Option<Subregion>)>>);
+    pub struct Exceptions(
+        #[serde(serialize_with = "sorted")]
+        pub HashMap<String, Vec<(usize, Option<Subregion>)>, super::Hasher>
+    );
+
+    impl Exceptions {
+        /// Exceptions in the extended source syntax: words marked with hyphens
+        /// at their breaks, such as `"ta-ble"`, or followed by their
+        /// hyphenation as spelled at line breaks, such as `"asszony
+        /// asz-szony"`. Words are lowercased, as dictionaries are searched for
+        /// them.
+        pub fn from_strings<S>(exceptions : &[S]) -> Self
+        where S : AsRef<str> {
+            exceptions.iter().map(|e| Exceptions::pair(&e.as_ref().to_lowercase(), |s| s.to_owned())).collect()
+        }
+
+        /// Every word with a known hyphenation, along with its breaks, in no
+        /// particular order.
+        pub fn iter(&self) -> impl Iterator<Item = (&str, &[(usize, Option<Subregion>)])> + '_ {
+            self.0.iter().map(|(word, breaks)| (word.as_str(), breaks.as_slice()))
+        }
+
+        /// The number of words with a known hyphenation.
+        pub fn len(&self) -> usize { self.0.len() }
+
+        /// Whether no word has a known hyphenation.
+        pub fn is_empty(&self) -> bool { self.0.is_empty() }
+    }
+
+    impl FromIterator<(String, Vec<(usize, Option<Subregion>)>)> for Exceptions {
+        fn from_iter<I>(iter : I) -> Self
+        where I : IntoIterator<Item = (String, Vec<(usize, Option<Subregion>)>)> {
+            Exceptions(HashMap::from_iter(iter))
+        }
+    }
 
     /// A dictionary for extended Knuth–Liang hyphenation, based on the strategy
     /// described by Németh in "Automatic non-standard hyphenation in OpenOffice.org".
     ///
     /// It comprises the working language, the set of extended patterns and
     /// exceptions, and the character boundaries for hyphenation.
+    ///
+    /// As with `Standard` dictionaries, the patterns and exceptions are shared
+    /// between clones.
     #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
     pub struct Extended {
         pub language : Language,
-        pub patterns : Patterns,
-        pub exceptions : Exceptions,
+        pub patterns : Arc<Patterns>,
+        pub exceptions : Arc<Exceptions>,
         /// The minimum number of `char`s from the start and end of a word where
         /// breaks may not occur.
-        pub minima: (usize, usize)
+        pub minima: (usize, usize),
+        /// Where the patterns came from, as recorded when the dictionary was
+        /// built.
+        pub provenance : Provenance,
+        /// The normalization form of the patterns, if any, as for `Standard`.
+        pub normalization : Option<NormalizationForm>,
+        /// The length in bytes beyond which words are left unhyphenated. Unlike
+        /// the minima, it is not serialized with the dictionary.
+        #[serde(skip, default = "super::max_length")]
+        pub max_length : usize,
+        /// How the words to hyphenate are folded, as for `Standard`.
+        #[serde(skip)]
+        pub case_folding : CaseFolding
+    }
+
+    impl Extended {
+        /// Build a dictionary from a libhyphen dictionary, as used by
+        /// LibreOffice and Firefox, read from `reader`. Its non-standard
+        /// patterns, which alter the spelling of words around their breaks,
+        /// are honored. Its `LEFTHYPHENMIN` and `RIGHTHYPHENMIN` headers take
+        /// precedence over the minima of the language.
+        ///
+        /// The patterns of compound words, which follow `NEXTLEVEL`, are left
+        /// out.
+        pub fn from_dic<R>(language : Language, reader : &mut R) -> io::Result<Self>
+        where R : io::Read + ?Sized {
+            let invalid = |e : &str| io::Error::new(io::ErrorKind::InvalidData, e);
+            let mut bytes = vec![];
+            reader.read_to_end(&mut bytes) ?;
+            let dic = parse::Dic::parse(&bytes).map_err(invalid) ?;
+
+            let pairs = dic.patterns.iter().map(|p| parse::libhyphen_pair(p))
+                .collect::<Result<Vec<_>, _>>().map_err(invalid) ?;
+            let patterns = Patterns::from_pairs(pairs).map_err(|e| invalid(&e.to_string())) ?;
+            let (left, right) = language.minima();
+            Ok(Extended {
+                language,
+                patterns : Arc::new(patterns),
+                exceptions : Arc::default(),
+                minima : (dic.minima.0.unwrap_or(left), dic.minima.1.unwrap_or(right)),
+                provenance : Provenance::default(),
+                normalization : None,
+                max_length : MAX_LENGTH,
+                case_folding : CaseFolding::default()
+            })
+        }
+
+        /// Build a dictionary, without exceptions, from extended patterns given
+        /// by their letters and their tally.
+        pub fn from_pairs<I>(language : Language, pairs : I) -> Result<Self, fst::Error>
+        where I : IntoIterator<Item = (String, Tally)> {
+            Ok(Extended {
+                language,
+                patterns : Arc::new(Patterns::from_pairs(pairs) ?),
+                exceptions : Arc::default(),
+                minima : language.minima(),
+                provenance : Provenance::default(),
+                normalization : None,
+                max_length : MAX_LENGTH,
+                case_folding : CaseFolding::default()
+            })
+        }
+
+        /// Build a dictionary, without exceptions, from patterns in the
+        /// extended source syntax of the `hyph-*.ext.txt` files, standard
+        /// ones such as `"hy3ph"` among them, such as `"c1k/k=k,1,2"`.
+        ///
+        /// Malformed patterns are reported as an error of kind
+        /// `io::ErrorKind::InvalidData`.
+        pub fn from_pattern_strings<S>(language : Language, patterns : &[S]) -> io::Result<Self>
+        where S : AsRef<str> {
+            let invalid = |e : &str| io::Error::new(io::ErrorKind::InvalidData, e);
+            let pairs = patterns.iter().map(|p| parse::extended_pair(p.as_ref()))
+                .collect::<Result<Vec<_>, _>>().map_err(invalid) ?;
+            Extended::from_pairs(language, pairs).map_err(|e| invalid(&e.to_string()))
+        }
+
+        /// Add the exceptions declared by the `\hyphenation{…}` groups of a
+        /// TeX text, such as a journal class file, and return how many were
+        /// read. Other groups are ignored.
+        ///
+        /// Words are lowercased, as the dictionary is searched for them; known
+        /// exceptions for the same words are replaced.
+        pub fn add_tex_exceptions(&mut self, tex : &str) -> Result<usize, &'static str> {
+            let exceptions = super::tex_exceptions(tex) ?;
+            let count = exceptions.len();
+            Arc::make_mut(&mut self.exceptions).0.extend(exceptions.into_iter().map(|(word, breaks)| {
+                (word, breaks.into_iter().map(|i| (i, None)).collect())
+            }));
+            Ok(count)
+        }
+
+        /// A new dictionary holding the patterns and exceptions of both `self`
+        /// and `other`. Where both hold a pattern of the same letters, or an
+        /// exception for the same word, that of `other` prevails. The language,
+        /// the minima and the backend of the patterns are those of `self`, and
+        /// the provenance that of both.
+        pub fn merge(&self, other : &Extended) -> Result<Self, fst::Error> {
+            let patterns = other.patterns.entries().into_iter()
+                .chain(self.patterns.entries())
+                .map(|(letters, tally)| (letters, tally.clone()));
+            let mut exceptions = (*self.exceptions).clone();
+            exceptions.0.extend(other.exceptions.0.iter().map(|(w, ops)| (w.clone(), ops.clone())));
+            Ok(Extended {
+                language : self.language,
+                patterns : Arc::new(Patterns::from_pairs_in(self.patterns.automaton.backend(), patterns) ?),
+                exceptions : Arc::new(exceptions),
+                minima : self.minima,
+                provenance : self.provenance.merge(&other.provenance),
+                normalization : self.normalization,
+                max_length : self.max_length,
+                case_folding : self.case_folding
+            })
+        }
+
+        /// The same dictionary, with the given minima in place of its own. Being
+        /// part of the dictionary, the minima are serialized along with it.
+        pub fn with_minima(self, minima : (usize, usize)) -> Self {
+            Extended { minima, .. self }
+        }
+
+        /// The same dictionary, with the given provenance in place of its own.
+        pub fn with_provenance(self, provenance : Provenance) -> Self {
+            Extended { provenance, .. self }
+        }
+
+        /// The same dictionary, with the given exceptions in place of its own.
+        pub fn with_exceptions(self, exceptions : Exceptions) -> Self {
+            Extended { exceptions : Arc::new(exceptions), .. self }
+        }
+
+        /// The same dictionary, normalizing the words it hyphenates to the
+        /// given form, as by `Standard::with_normalization`.
+        pub fn with_normalization(self, normalization : Option<NormalizationForm>) -> Self {
+            Extended { normalization, .. self }
+        }
+
+        /// The same dictionary, leaving words longer than `max_length` bytes
+        /// unhyphenated, as by `Standard::with_max_length`.
+        pub fn with_max_length(self, max_length : usize) -> Self {
+            Extended { max_length, .. self }
+        }
+
+        /// The same dictionary, folding the words it hyphenates as given, as by
+        /// `Standard::with_case_folding`.
+        pub fn with_case_folding(self, case_folding : CaseFolding) -> Self {
+            Extended { case_folding, .. self }
+        }
+
+        /// Whether the minima of the dictionary differ from the defaults of its
+        /// language, as given by `Language::minima`.
+        pub fn overrides_minima(&self) -> bool {
+            self.minima != self.language.minima()
+        }
+
+        /// The approximate heap usage of the dictionary's automaton, tallies
+        /// and exceptions.
+        pub fn memory_footprint(&self) -> Footprint {
+            Footprint {
+                automaton : self.patterns.automaton.heap_size(),
+                tallies : self.patterns.tallies.heap_size(),
+                exceptions : self.exceptions.0.heap_size()
+            }
+        }
     }
 }
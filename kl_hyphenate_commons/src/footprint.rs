@@ -0,0 +1,101 @@
+//! Approximate heap usage of dictionaries.
+//!
+//! Services holding many dictionaries resident may plan their capacity from
+//! the [`Footprint`] of each, as reported by `memory_footprint`. The sizes are
+//! estimated from the capacity of every allocation, excluding the overhead of
+//! the allocator; hash tables are reckoned at one control byte per bucket.
+//! Patterns and exceptions shared between clones of a dictionary are counted
+//! in full by each clone.
+//!
+//! [`Footprint`]: struct.Footprint.html
+
+use atlatl::fst::{FST, Stipe};
+use std::collections::HashMap;
+use std::mem::size_of;
+use dictionary::Locus;
+use dictionary::extended::{Subregion, Tally};
+use storage::{Automaton, Table};
+
+
+/// The approximate heap usage of a dictionary, in bytes, by component.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Footprint {
+    /// The structure mapping patterns to their tallies.
+    pub automaton : usize,
+    /// The tallies of the patterns.
+    pub tallies : usize,
+    /// The exceptions.
+    pub exceptions : usize
+}
+
+impl Footprint {
+    /// The approximate heap usage of the whole dictionary, in bytes.
+    pub fn total(&self) -> usize {
+        self.automaton + self.tallies + self.exceptions
+    }
+}
+
+/// Values whose heap allocations may be estimated.
+pub(crate) trait HeapSize {
+    /// The bytes allocated on the heap by the value, excluding its own size.
+    fn heap_size(&self) -> usize;
+}
+
+macro_rules! impl_heapless {
+    ($($t:ty),*) => { $(impl HeapSize for $t { #[inline] fn heap_size(&self) -> usize { 0 } })* }
+}
+
+impl_heapless!(u8, u16, u32, usize, Locus, Stipe);
+
+impl HeapSize for String {
+    #[inline] fn heap_size(&self) -> usize { self.capacity() }
+}
+
+impl<T : HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_size).sum::<usize>()
+    }
+}
+
+impl<T : HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize { self.as_ref().map_or(0, HeapSize::heap_size) }
+}
+
+impl<A : HeapSize, B : HeapSize> HeapSize for (A, B) {
+    fn heap_size(&self) -> usize { self.0.heap_size() + self.1.heap_size() }
+}
+
+impl<K : HeapSize, V : HeapSize, S> HeapSize for HashMap<K, V, S> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * (size_of::<(K, V)>() + 1)
+            + self.iter().map(|(k, v)| k.heap_size() + v.heap_size()).sum::<usize>()
+    }
+}
+
+impl HeapSize for Subregion {
+    #[inline] fn heap_size(&self) -> usize { self.substitution.heap_size() }
+}
+
+impl HeapSize for Tally {
+    fn heap_size(&self) -> usize { self.standard.heap_size() + self.subregion.heap_size() }
+}
+
+impl HeapSize for FST<u32, u16> {
+    fn heap_size(&self) -> usize {
+        let state_output = self.state_output.capacity() * (size_of::<(u32, u16)>() + 1);
+        self.da.stipe.heap_size() + self.da.next.heap_size() + self.da.output.heap_size() + state_output
+    }
+}
+
+impl HeapSize for Table {
+    #[inline] fn heap_size(&self) -> usize { self.patterns.heap_size() }
+}
+
+impl HeapSize for Automaton {
+    fn heap_size(&self) -> usize {
+        match *self {
+            Automaton::DoubleArray(ref fst) => fst.heap_size(),
+            Automaton::HashTable(ref table) => table.heap_size()
+        }
+    }
+}
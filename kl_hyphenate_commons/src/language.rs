@@ -1,43 +1,182 @@
 //! Available languages and related data.
 
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeSet;
+use std::error;
 use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
 
 macro_rules! fiant_linguae {
-    ( $($lang:ident, $bounds:expr, $code:expr;)* ) => {
-        fiant_linguae! { $($lang, $bounds, $code);* }
+    ( $($lang:ident, $bounds:expr, $code:expr, $script:ident,
+        $iso1:expr, $iso3:expr, $english:expr, $native:expr;)* ) => {
+        fiant_linguae! { $($lang, $bounds, $code, $script, $iso1, $iso3, $english, $native);* }
     };
-    ( $($lang:ident, $bounds:expr, $code:expr);* ) => {
+    ( $($lang:ident, $bounds:expr, $code:expr, $script:ident,
+        $iso1:expr, $iso3:expr, $english:expr, $native:expr);* ) => {
         /// The set of languages available for hyphenation.
         #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
         pub enum Language {
             $( $lang, )*
+            /// A language without bundled patterns, identified by a tag of the
+            /// user's choosing, preferably a BCP 47 language tag.
+            ///
+            /// Dictionaries built at runtime for such languages should carry
+            /// their own minima; the defaults are those of TeX.
+            Other(#[serde(deserialize_with = "deserialize_tag")] CustomTag),
         }
 
         impl Language {
             /// The default number of characters from the start and end of a word
-            /// where breaks may not occur.
+            /// where breaks may not occur, after TeX's `\lefthyphenmin` and
+            /// `\righthyphenmin`. Dictionaries may override them with
+            /// `with_minima`.
             pub fn minima(&self) -> (usize, usize) {
                 match *self {
                     $( Language::$lang => $bounds, )*
+                    Language::Other(_) => (2, 3)
                 }
             }
 
-            /// The TeX language code.
+            /// The TeX language code, or the tag of an `Other` language.
             pub fn code(&self) -> &'static str {
                 match *self {
                     $( Language::$lang => $code, )*
+                    Language::Other(tag) => tag
+                }
+            }
+
+            /// The script in which the hyphenation patterns are written, if
+            /// known.
+            ///
+            /// Sanskrit patterns cover several Indic scripts besides Latin
+            /// transliteration; Devanagari is given as the principal one.
+            pub fn script(&self) -> Option<Script> {
+                match *self {
+                    $( Language::$lang => Some(Script::$script), )*
+                    Language::Other(_) => None
+                }
+            }
+
+            /// The two-letter ISO 639-1 code, if the language has one.
+            pub fn iso_639_1(&self) -> Option<&'static str> {
+                match *self {
+                    $( Language::$lang => $iso1, )*
+                    Language::Other(tag) => primary_subtag(tag, 2)
+                }
+            }
+
+            /// The three-letter ISO 639-3 code. Macrolanguage codes are used
+            /// where the patterns do not address a specific member language.
+            ///
+            /// The codes of `Other` languages are taken from the primary
+            /// subtag of their tag, if it has the right length.
+            pub fn iso_639_3(&self) -> Option<&'static str> {
+                match *self {
+                    $( Language::$lang => Some($iso3), )*
+                    Language::Other(tag) => primary_subtag(tag, 3)
+                }
+            }
+
+            /// The name of the language in English, distinguishing variants
+            /// of the same language. `Other` languages are named by their tag.
+            pub fn english_name(&self) -> &'static str {
+                match *self {
+                    $( Language::$lang => $english, )*
+                    Language::Other(tag) => tag
+                }
+            }
+
+            /// The name of the language in that language, as it might appear
+            /// in a language menu. `Other` languages are named by their tag.
+            pub fn native_name(&self) -> &'static str {
+                match *self {
+                    $( Language::$lang => $native, )*
+                    Language::Other(tag) => tag
+                }
+            }
+
+            /// The language identified by the given TeX language code.
+            fn from_code(code : &str) -> Option<Language> {
+                match code {
+                    $( $code => Some(Language::$lang), )*
+                    _ => None
                 }
             }
         }
 
+        /// Languages are displayed by their TeX language code, such that they
+        /// may be parsed back with `FromStr`.
         impl fmt::Display for Language {
             fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
-                write!(f, "{:?}", *self)
+                f.write_str(self.code())
             }
         }
     }
 }
 
+/// The writing systems of the available languages.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Script {
+    Armenian,
+    Bengali,
+    Coptic,
+    Cyrillic,
+    Devanagari,
+    Ethiopic,
+    Georgian,
+    Greek,
+    Gujarati,
+    Gurmukhi,
+    Kannada,
+    Latin,
+    Malayalam,
+    Oriya,
+    Tamil,
+    Telugu,
+    Thai,
+}
+
+impl Script {
+    /// The ISO 15924 code.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Script::Armenian => "Armn",
+            Script::Bengali => "Beng",
+            Script::Coptic => "Copt",
+            Script::Cyrillic => "Cyrl",
+            Script::Devanagari => "Deva",
+            Script::Ethiopic => "Ethi",
+            Script::Georgian => "Geor",
+            Script::Greek => "Grek",
+            Script::Gujarati => "Gujr",
+            Script::Gurmukhi => "Guru",
+            Script::Kannada => "Knda",
+            Script::Latin => "Latn",
+            Script::Malayalam => "Mlym",
+            Script::Oriya => "Orya",
+            Script::Tamil => "Taml",
+            Script::Telugu => "Telu",
+            Script::Thai => "Thai",
+        }
+    }
+
+    /// The direction in which the script is written.
+    pub fn direction(&self) -> Direction {
+        // None of the supported scripts is written right to left; the
+        // distinction is kept so that callers need not assume as much.
+        Direction::LeftToRight
+    }
+}
+
+/// The direction in which text is written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+
 // NOTE: These hyphenation bounds were taken directly from the relevant TeX
 // packages, but it is not entirely clear how well they map to the notion of
 // Unicode `char` in Rust.
@@ -48,82 +187,252 @@ macro_rules! fiant_linguae {
 // well-behaved hyphenators are expected to validate hyphenation opportunities,
 // discarding any which arise outside `char` boundaries.
 fiant_linguae! {
-    Afrikaans,              (1, 2),     "af";
-    Armenian,               (1, 2),     "hy";
-    Assamese,               (1, 1),     "as";
-    Basque,                 (2, 2),     "eu";
-    Belarusian,             (2, 2),     "be";
-    Bengali,                (1, 1),     "bn";
-    Bulgarian,              (2, 2),     "bg";
-    Catalan,                (2, 2),     "ca";
-    Chinese,                (1, 1),     "zh-latn-pinyin";
-    Coptic,                 (1, 1),     "cop";
-    Croatian,               (2, 2),     "hr";
-    Czech,                  (2, 3),     "cs";
-    Danish,                 (2, 2),     "da";
-    Dutch,                  (2, 2),     "nl";
-    EnglishGB,              (2, 3),     "en-gb";
-    EnglishUS,              (2, 3),     "en-us";
-    Esperanto,              (2, 2),     "eo";
-    Estonian,               (2, 3),     "et";
-    Ethiopic,               (1, 1),     "mul-ethi";
-    Finnish,                (2, 2),     "fi";
-    French,                 (2, 3),     "fr";
-    Friulan,                (2, 2),     "fur";
-    Galician,               (2, 2),     "gl";
-    Georgian,               (1, 2),     "ka";
-    German1901,             (2, 2),     "de-1901";
-    German1996,             (2, 2),     "de-1996";
-    GermanSwiss,            (2, 2),     "de-ch-1901";
-    GreekAncient,           (1, 1),     "grc";
-    GreekMono,              (1, 1),     "el-monoton";
-    GreekPoly,              (1, 1),     "el-polyton";
-    Gujarati,               (1, 1),     "gu";
-    Hindi,                  (1, 1),     "hi";
-    Hungarian,              (2, 2),     "hu";
-    Icelandic,              (2, 2),     "is";
-    Indonesian,             (2, 2),     "id";
-    Interlingua,            (2, 2),     "ia";
-    Irish,                  (2, 3),     "ga";
-    Italian,                (2, 2),     "it";
-    Kannada,                (1, 1),     "kn";
-    Kurmanji,               (2, 2),     "kmr";
-    Latin,                  (2, 2),     "la";
-    LatinClassic,           (2, 2),     "la-x-classic";
-    LatinLiturgical,        (2, 2),     "la-x-liturgic";
-    Latvian,                (2, 2),     "lv";
-    Lithuanian,             (2, 2),     "lt";
-    Macedonian,             (2, 2),     "mk";
-    Malayalam,              (1, 1),     "ml";
-    Marathi,                (1, 1),     "mr";
-    Mongolian,              (2, 2),     "mn-cyrl";
-    NorwegianBokmal,        (2, 2),     "nb";
-    NorwegianNynorsk,       (2, 2),     "nn";
-    Occitan,                (2, 2),     "oc";
-    Oriya,                  (1, 1),     "or";
-    Pali,                   (1, 2),     "pi";
-    Panjabi,                (1, 1),     "pa";
-    Piedmontese,            (2, 2),     "pms";
-    Polish,                 (2, 2),     "pl";
-    Portuguese,             (2, 3),     "pt";
-    Romanian,               (2, 2),     "ro";
-    Romansh,                (2, 2),     "rm";
-    Russian,                (2, 2),     "ru";
-    Sanskrit,               (1, 3),     "sa";
-    SerbianCyrillic,        (2, 2),     "sr-cyrl";
-    SerbocroatianCyrillic,  (2, 2),     "sh-cyrl";
-    SerbocroatianLatin,     (2, 2),     "sh-latn";
-    SlavonicChurch,         (1, 2),     "cu";
-    Slovak,                 (2, 3),     "sk";
-    Slovenian,              (2, 2),     "sl";
-    Spanish,                (2, 2),     "es";
-    Swedish,                (2, 2),     "sv";
-    Tamil,                  (1, 1),     "ta";
-    Telugu,                 (1, 1),     "te";
-    Thai,                   (2, 3),     "th";
-    Turkish,                (2, 2),     "tr";
-    Turkmen,                (2, 2),     "tk";
-    Ukrainian,              (2, 2),     "uk";
-    Uppersorbian,           (2, 2),     "hsb";
-    Welsh,                  (2, 3),     "cy";
+    Afrikaans,              (1, 2),     "af",             Latin,      Some("af"), "afr", "Afrikaans", "Afrikaans";
+    Armenian,               (1, 2),     "hy",             Armenian,   Some("hy"), "hye", "Armenian", "Հայերեն";
+    Assamese,               (1, 1),     "as",             Bengali,    Some("as"), "asm", "Assamese", "অসমীয়া";
+    Basque,                 (2, 2),     "eu",             Latin,      Some("eu"), "eus", "Basque", "Euskara";
+    Belarusian,             (2, 2),     "be",             Cyrillic,   Some("be"), "bel", "Belarusian", "Беларуская";
+    Bengali,                (1, 1),     "bn",             Bengali,    Some("bn"), "ben", "Bengali", "বাংলা";
+    Bulgarian,              (2, 2),     "bg",             Cyrillic,   Some("bg"), "bul", "Bulgarian", "Български";
+    Catalan,                (2, 2),     "ca",             Latin,      Some("ca"), "cat", "Catalan", "Català";
+    Chinese,                (1, 1),     "zh-latn-pinyin", Latin,      Some("zh"), "zho", "Chinese (Pinyin)", "Hànyǔ Pīnyīn";
+    Coptic,                 (1, 1),     "cop",            Coptic,     None,       "cop", "Coptic", "Ⲙⲉⲧⲣⲉⲙⲛⲭⲏⲙⲓ";
+    Croatian,               (2, 2),     "hr",             Latin,      Some("hr"), "hrv", "Croatian", "Hrvatski";
+    Czech,                  (2, 3),     "cs",             Latin,      Some("cs"), "ces", "Czech", "Čeština";
+    Danish,                 (2, 2),     "da",             Latin,      Some("da"), "dan", "Danish", "Dansk";
+    Dutch,                  (2, 2),     "nl",             Latin,      Some("nl"), "nld", "Dutch", "Nederlands";
+    EnglishGB,              (2, 3),     "en-gb",          Latin,      Some("en"), "eng", "English (United Kingdom)", "English (United Kingdom)";
+    EnglishUS,              (2, 3),     "en-us",          Latin,      Some("en"), "eng", "English (United States)", "English (United States)";
+    Esperanto,              (2, 2),     "eo",             Latin,      Some("eo"), "epo", "Esperanto", "Esperanto";
+    Estonian,               (2, 3),     "et",             Latin,      Some("et"), "est", "Estonian", "Eesti";
+    Ethiopic,               (1, 1),     "mul-ethi",       Ethiopic,   None,       "mul", "Ethiopic", "ግዕዝ";
+    Finnish,                (2, 2),     "fi",             Latin,      Some("fi"), "fin", "Finnish", "Suomi";
+    French,                 (2, 3),     "fr",             Latin,      Some("fr"), "fra", "French", "Français";
+    Friulan,                (2, 2),     "fur",            Latin,      None,       "fur", "Friulan", "Furlan";
+    Galician,               (2, 2),     "gl",             Latin,      Some("gl"), "glg", "Galician", "Galego";
+    Georgian,               (1, 2),     "ka",             Georgian,   Some("ka"), "kat", "Georgian", "ქართული";
+    German1901,             (2, 2),     "de-1901",        Latin,      Some("de"), "deu", "German (Traditional Orthography)", "Deutsch (traditionelle Rechtschreibung)";
+    German1996,             (2, 2),     "de-1996",        Latin,      Some("de"), "deu", "German (Reformed Orthography)", "Deutsch (reformierte Rechtschreibung)";
+    GermanSwiss,            (2, 2),     "de-ch-1901",     Latin,      Some("de"), "deu", "German (Switzerland)", "Deutsch (Schweiz)";
+    GreekAncient,           (1, 1),     "grc",            Greek,      None,       "grc", "Ancient Greek", "Ἀρχαία Ἑλληνική";
+    GreekMono,              (1, 1),     "el-monoton",     Greek,      Some("el"), "ell", "Greek (Monotonic)", "Ελληνικά (μονοτονικό)";
+    GreekPoly,              (1, 1),     "el-polyton",     Greek,      Some("el"), "ell", "Greek (Polytonic)", "Ἑλληνικά (πολυτονικό)";
+    Gujarati,               (1, 1),     "gu",             Gujarati,   Some("gu"), "guj", "Gujarati", "ગુજરાતી";
+    Hindi,                  (1, 1),     "hi",             Devanagari, Some("hi"), "hin", "Hindi", "हिन्दी";
+    Hungarian,              (2, 2),     "hu",             Latin,      Some("hu"), "hun", "Hungarian", "Magyar";
+    Icelandic,              (2, 2),     "is",             Latin,      Some("is"), "isl", "Icelandic", "Íslenska";
+    Indonesian,             (2, 2),     "id",             Latin,      Some("id"), "ind", "Indonesian", "Bahasa Indonesia";
+    Interlingua,            (2, 2),     "ia",             Latin,      Some("ia"), "ina", "Interlingua", "Interlingua";
+    Irish,                  (2, 3),     "ga",             Latin,      Some("ga"), "gle", "Irish", "Gaeilge";
+    Italian,                (2, 2),     "it",             Latin,      Some("it"), "ita", "Italian", "Italiano";
+    Kannada,                (1, 1),     "kn",             Kannada,    Some("kn"), "kan", "Kannada", "ಕನ್ನಡ";
+    Kurmanji,               (2, 2),     "kmr",            Latin,      Some("ku"), "kmr", "Kurmanji", "Kurmancî";
+    Latin,                  (2, 2),     "la",             Latin,      Some("la"), "lat", "Latin", "Latina";
+    LatinClassic,           (2, 2),     "la-x-classic",   Latin,      Some("la"), "lat", "Latin (Classical)", "Latina (classica)";
+    LatinLiturgical,        (2, 2),     "la-x-liturgic",  Latin,      Some("la"), "lat", "Latin (Liturgical)", "Latina (liturgica)";
+    Latvian,                (2, 2),     "lv",             Latin,      Some("lv"), "lav", "Latvian", "Latviešu";
+    Lithuanian,             (2, 2),     "lt",             Latin,      Some("lt"), "lit", "Lithuanian", "Lietuvių";
+    Macedonian,             (2, 2),     "mk",             Cyrillic,   Some("mk"), "mkd", "Macedonian", "Македонски";
+    Malayalam,              (1, 1),     "ml",             Malayalam,  Some("ml"), "mal", "Malayalam", "മലയാളം";
+    Marathi,                (1, 1),     "mr",             Devanagari, Some("mr"), "mar", "Marathi", "मराठी";
+    Mongolian,              (2, 2),     "mn-cyrl",        Cyrillic,   Some("mn"), "mon", "Mongolian", "Монгол";
+    NorwegianBokmal,        (2, 2),     "nb",             Latin,      Some("nb"), "nob", "Norwegian Bokmål", "Norsk bokmål";
+    NorwegianNynorsk,       (2, 2),     "nn",             Latin,      Some("nn"), "nno", "Norwegian Nynorsk", "Norsk nynorsk";
+    Occitan,                (2, 2),     "oc",             Latin,      Some("oc"), "oci", "Occitan", "Occitan";
+    Oriya,                  (1, 1),     "or",             Oriya,      Some("or"), "ori", "Oriya", "ଓଡ଼ିଆ";
+    Pali,                   (1, 2),     "pi",             Latin,      Some("pi"), "pli", "Pali", "Pāli";
+    Panjabi,                (1, 1),     "pa",             Gurmukhi,   Some("pa"), "pan", "Panjabi", "ਪੰਜਾਬੀ";
+    Piedmontese,            (2, 2),     "pms",            Latin,      None,       "pms", "Piedmontese", "Piemontèis";
+    Polish,                 (2, 2),     "pl",             Latin,      Some("pl"), "pol", "Polish", "Polski";
+    Portuguese,             (2, 3),     "pt",             Latin,      Some("pt"), "por", "Portuguese", "Português";
+    Romanian,               (2, 2),     "ro",             Latin,      Some("ro"), "ron", "Romanian", "Română";
+    Romansh,                (2, 2),     "rm",             Latin,      Some("rm"), "roh", "Romansh", "Rumantsch";
+    Russian,                (2, 2),     "ru",             Cyrillic,   Some("ru"), "rus", "Russian", "Русский";
+    Sanskrit,               (1, 3),     "sa",             Devanagari, Some("sa"), "san", "Sanskrit", "संस्कृतम्";
+    SerbianCyrillic,        (2, 2),     "sr-cyrl",        Cyrillic,   Some("sr"), "srp", "Serbian (Cyrillic)", "Српски";
+    SerbocroatianCyrillic,  (2, 2),     "sh-cyrl",        Cyrillic,   Some("sh"), "hbs", "Serbo-Croatian (Cyrillic)", "Српскохрватски";
+    SerbocroatianLatin,     (2, 2),     "sh-latn",        Latin,      Some("sh"), "hbs", "Serbo-Croatian (Latin)", "Srpskohrvatski";
+    SlavonicChurch,         (1, 2),     "cu",             Cyrillic,   Some("cu"), "chu", "Church Slavonic", "Словѣньскъ";
+    Slovak,                 (2, 3),     "sk",             Latin,      Some("sk"), "slk", "Slovak", "Slovenčina";
+    Slovenian,              (2, 2),     "sl",             Latin,      Some("sl"), "slv", "Slovenian", "Slovenščina";
+    Spanish,                (2, 2),     "es",             Latin,      Some("es"), "spa", "Spanish", "Español";
+    Swedish,                (2, 2),     "sv",             Latin,      Some("sv"), "swe", "Swedish", "Svenska";
+    Tamil,                  (1, 1),     "ta",             Tamil,      Some("ta"), "tam", "Tamil", "தமிழ்";
+    Telugu,                 (1, 1),     "te",             Telugu,     Some("te"), "tel", "Telugu", "తెలుగు";
+    Thai,                   (2, 3),     "th",             Thai,       Some("th"), "tha", "Thai", "ไทย";
+    Turkish,                (2, 2),     "tr",             Latin,      Some("tr"), "tur", "Turkish", "Türkçe";
+    Turkmen,                (2, 2),     "tk",             Latin,      Some("tk"), "tuk", "Turkmen", "Türkmençe";
+    Ukrainian,              (2, 2),     "uk",             Cyrillic,   Some("uk"), "ukr", "Ukrainian", "Українська";
+    Uppersorbian,           (2, 2),     "hsb",            Latin,      None,       "hsb", "Upper Sorbian", "Hornjoserbšćina";
+    Welsh,                  (2, 3),     "cy",             Latin,      Some("cy"), "cym", "Welsh", "Cymraeg";
+}
+
+
+impl Language {
+    /// The direction in which the language is written, if known.
+    pub fn direction(&self) -> Option<Direction> {
+        self.script().map(|script| script.direction())
+    }
+
+    /// An `Other` language with the given tag, which need not be `'static`.
+    ///
+    /// Tags are interned for the lifetime of the program: each distinct tag
+    /// is only ever allocated once.
+    pub fn other(tag : &str) -> Language {
+        Language::Other(intern(tag))
+    }
+
+    /// The closest supported variant for the given BCP 47 language tag, such
+    /// as `"en-GB"`, `"de-1901"`, `"sr-Latn"` or `"pt-BR"`.
+    ///
+    /// Tags are matched case-insensitively, and may be separated by either
+    /// hyphens or underscores. TeX language codes, as returned by `code()`,
+    /// are accepted as well.
+    ///
+    /// Script and variant subtags select among the variants of a language,
+    /// whereas region subtags are only considered where they bear on
+    /// orthography (as in English and German).
+    pub fn from_tag(tag : &str) -> Option<Language> {
+        use self::Language::*;
+
+        let tag = tag.trim().replace('_', "-").to_lowercase();
+        if let Some(lang) = Language::from_code(&tag) {
+            return Some(lang)
+        }
+
+        let Tag { primary, script, region, variants } = Tag::parse(&tag) ?;
+        let latin = script.is_none() || script == Some("latn");
+        let cyrillic = script.is_none() || script == Some("cyrl");
+        let region = region.unwrap_or("");
+        let variant = |v : &str| variants.contains(&v);
+
+        let lang = match primary {
+            "en" => match region {
+                "us" | "" => EnglishUS,
+                "ca" | "ph" | "pr" | "um" | "vi" | "as" | "gu" | "mp" => EnglishUS,
+                _ => EnglishGB
+            },
+            "de" => match (region, variant("1901")) {
+                ("ch", _) | ("li", _) => GermanSwiss,
+                (_, true) => German1901,
+                (_, false) => German1996
+            },
+            "gsw" => GermanSwiss,
+            "el" if variant("polyton") => GreekPoly,
+            "el" => GreekMono,
+            "grc" => GreekAncient,
+            "la" if variant("classic") => LatinClassic,
+            "la" if variant("liturgic") => LatinLiturgical,
+            "la" => Latin,
+            "sr" if script == Some("latn") => SerbocroatianLatin,
+            "sr" => SerbianCyrillic,
+            "sh" | "hbs" if script == Some("cyrl") => SerbocroatianCyrillic,
+            "sh" | "hbs" | "bs" => SerbocroatianLatin,
+            "zh" if latin => Chinese,
+            "mn" if cyrillic => Mongolian,
+            "no" => NorwegianBokmal,
+            "am" | "ti" | "gez" => Ethiopic,
+            "ku" | "kmr" if latin => Kurmanji,
+            "zh" | "mn" | "ku" | "kmr" => return None,
+            _ => Language::from_code(primary) ?
+        };
+        Some(lang)
+    }
+}
+
+/// Parse a language from its TeX language code, as returned by `code()`,
+/// ignoring case. For lenient matching against arbitrary language tags, see
+/// `Language::from_tag`.
+///
+/// Unknown codes are rejected rather than taken for `Other` languages, which
+/// must be constructed explicitly with `Language::other`.
+impl FromStr for Language {
+    type Err = UnknownLanguage;
+
+    fn from_str(code : &str) -> Result<Language, UnknownLanguage> {
+        Language::from_code(&code.to_lowercase()).ok_or_else(|| UnknownLanguage(code.to_owned()))
+    }
+}
+
+/// Failure to parse a language code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownLanguage(pub String);
+
+impl fmt::Display for UnknownLanguage {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown language code: `{}`", self.0)
+    }
+}
+
+impl error::Error for UnknownLanguage {}
+
+
+/// Leak the given tag, unless it was already leaked.
+fn intern(tag : &str) -> &'static str {
+    static TAGS : Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let mut tags = TAGS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match tags.get(tag) {
+        Some(&interned) => interned,
+        None => {
+            let interned : &'static str = Box::leak(tag.to_owned().into_boxed_str());
+            tags.insert(interned);
+            interned
+        }
+    }
+}
+
+/// The tag of an `Other` language. The alias keeps `serde` from assuming that
+/// the tag is borrowed from the deserializer, which would only be possible
+/// from `'static` input.
+type CustomTag = &'static str;
+
+/// Deserialize the tag of an `Other` language, which is interned.
+fn deserialize_tag<'de, D>(deserializer : D) -> Result<&'static str, D::Error>
+where D : Deserializer<'de> {
+    String::deserialize(deserializer).map(|tag| intern(&tag))
+}
+
+/// The primary subtag of a language tag, if it is alphabetic and of the
+/// given length.
+fn primary_subtag(tag : &'static str, len : usize) -> Option<&'static str> {
+    let primary = tag.split(&['-', '_'][..]).next().unwrap_or(tag);
+    Some(primary).filter(|p| p.len() == len && p.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+
+/// The subtags of a BCP 47 language tag that bear on hyphenation.
+struct Tag<'t> {
+    primary : &'t str,
+    script : Option<&'t str>,
+    region : Option<&'t str>,
+    /// Variant subtags, along with private use subtags.
+    variants : Vec<&'t str>
+}
+
+impl<'t> Tag<'t> {
+    /// Split a lowercase language tag into its subtags. Extended language
+    /// subtags and extensions are ignored.
+    fn parse(tag : &'t str) -> Option<Tag<'t>> {
+        let mut subtags = tag.split('-');
+        let primary = subtags.next().filter(|p| p.len() >= 2 && p.len() <= 8) ?;
+        let mut parsed = Tag { primary, script : None, region : None, variants : vec![] };
+
+        let (mut extension, mut private) = (false, false);
+        for subtag in subtags {
+            let alphabetic = subtag.chars().all(|c| c.is_ascii_alphabetic());
+            let numeric = subtag.chars().all(|c| c.is_ascii_digit());
+            match subtag.len() {
+                _ if private => parsed.variants.push(subtag),
+                1 => { private = subtag == "x"; extension = !private; },
+                _ if extension => (),
+                4 if alphabetic => parsed.script = Some(subtag),
+                2 if alphabetic => parsed.region = Some(subtag),
+                3 if numeric => parsed.region = Some(subtag),
+                3 => (),
+                _ => parsed.variants.push(subtag)
+            }
+        }
+        Some(parsed)
+    }
 }
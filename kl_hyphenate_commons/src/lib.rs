@@ -4,9 +4,15 @@ Hyphenation internals
 
 #[macro_use] extern crate serde;
 extern crate atlatl;
+extern crate unicode_normalization;
+#[cfg(feature = "ahash")] extern crate ahash;
 
 mod language;
 pub mod dictionary;
+pub mod footprint;
+pub mod normalization;
 pub mod parse;
+pub mod provenance;
+pub mod storage;
 
 pub use language::*;
@@ -0,0 +1,140 @@
+//! Unicode normalization of patterns and words.
+//!
+//! Patterns are matched bytewise, such that a word must be spelled in the same
+//! normalization form as the patterns which hyphenate it: the decomposed `é`
+//! of a word in NFD matches none of the precomposed `é` of patterns in NFC.
+//! Dictionaries whose patterns were normalized thus normalize the words they
+//! hyphenate likewise, and map the opportunities found back onto the word as
+//! it was given.
+//!
+//! Words are folded before they are normalized, by their lowercase unless
+//! dictionaries are told to apply full Unicode case folding, see
+//! `CaseFolding`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use unicode_normalization::{self as un, IsNormalized, UnicodeNormalization};
+use unicode_normalization::char::canonical_combining_class;
+
+
+/// A Unicode normalization form, as defined by UAX #15.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NormalizationForm { Nfc, Nfd, Nfkc, Nfkd }
+
+impl NormalizationForm {
+    /// The name of the form, such as `NFC`.
+    pub fn name(self) -> &'static str {
+        match self {
+            NormalizationForm::Nfc => "NFC",
+            NormalizationForm::Nfd => "NFD",
+            NormalizationForm::Nfkc => "NFKC",
+            NormalizationForm::Nfkd => "NFKD"
+        }
+    }
+
+    /// The string normalized to this form.
+    pub fn apply(self, s : &str) -> String {
+        match self {
+            NormalizationForm::Nfc => s.nfc().collect(),
+            NormalizationForm::Nfd => s.nfd().collect(),
+            NormalizationForm::Nfkc => s.nfkc().collect(),
+            NormalizationForm::Nfkd => s.nfkd().collect()
+        }
+    }
+
+    /// Whether the string is already in this form. Most strings, and every
+    /// ASCII string, are told so without being normalized.
+    pub fn is_normalized(self, s : &str) -> bool {
+        let quick = match self {
+            NormalizationForm::Nfc => un::is_nfc_quick(s.chars()),
+            NormalizationForm::Nfd => un::is_nfd_quick(s.chars()),
+            NormalizationForm::Nfkc => un::is_nfkc_quick(s.chars()),
+            NormalizationForm::Nfkd => un::is_nfkd_quick(s.chars())
+        };
+        match quick {
+            IsNormalized::Yes => true,
+            IsNormalized::No => false,
+            IsNormalized::Maybe => self.apply(s) == s
+        }
+    }
+
+    /// The string normalized to this form, along with the byte offset at
+    /// which each of its segments starts, paired with the offset of the same
+    /// segment in the original string.
+    ///
+    /// Segments start at the starters of the original, the characters of
+    /// canonical combining class 0, which combining marks follow. Should the
+    /// string not normalize as its segments do, as where Hangul jamo compose,
+    /// it is taken as a single segment.
+    pub fn apply_aligned(self, s : &str) -> (String, Vec<(usize, usize)>) {
+        let mut starts : Vec<usize> = s.char_indices()
+            .filter(|&(i, c)| i == 0 || canonical_combining_class(c) == 0)
+            .map(|(i, _)| i)
+            .collect();
+        starts.push(s.len());
+
+        let mut normalized = String::with_capacity(s.len());
+        let mut alignment = Vec::with_capacity(starts.len() - 1);
+        for bounds in starts.windows(2) {
+            alignment.push((normalized.len(), bounds[0]));
+            normalized.push_str(&self.apply(&s[bounds[0] .. bounds[1]]));
+        }
+        let whole = self.apply(s);
+        if normalized != whole {
+            return (whole, vec![(0, 0)])
+        }
+        (normalized, alignment)
+    }
+}
+
+impl fmt::Display for NormalizationForm {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How dictionaries fold the case of the words they hyphenate.
+///
+/// Patterns are spelled in lowercase, which is how words are folded by
+/// default. Full case folding, as defined by `CaseFolding.txt`, further maps
+/// letters to the sequences of their caseless matching, such as `ß` to `ss`,
+/// `ﬀ` to `ff`, or final `ς` to `σ`, which suits patterns spelled likewise;
+/// the German patterns of hyph-utf8, which spell `ß`, do not.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CaseFolding {
+    /// Fold words by their lowercase, with the refolding of the dotted `İ`,
+    /// of presentation ligatures such as `ﬁ`, and of the apostrophe `’`.
+    #[default]
+    Lowercase,
+    /// Fold words by full Unicode case folding, with its Turkish tailoring
+    /// for dictionaries of Turkish, and the refolding of the apostrophe `’`.
+    Full
+}
+
+/// Parse a normalization form from its name, such as `NFC`, ignoring case.
+impl FromStr for NormalizationForm {
+    type Err = UnknownNormalizationForm;
+
+    fn from_str(name : &str) -> Result<NormalizationForm, UnknownNormalizationForm> {
+        match name.to_lowercase().as_str() {
+            "nfc" => Ok(NormalizationForm::Nfc),
+            "nfd" => Ok(NormalizationForm::Nfd),
+            "nfkc" => Ok(NormalizationForm::Nfkc),
+            "nfkd" => Ok(NormalizationForm::Nfkd),
+            _ => Err(UnknownNormalizationForm(name.to_owned()))
+        }
+    }
+}
+
+/// Failure to parse a normalization form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownNormalizationForm(pub String);
+
+impl fmt::Display for UnknownNormalizationForm {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown normalization form: `{}`", self.0)
+    }
+}
+
+impl ::std::error::Error for UnknownNormalizationForm {}
@@ -1,5 +1,7 @@
 //! Pattern and exception parsing.
 
+use std::cmp;
+
 use dictionary::*;
 use dictionary::extended::{self as ext, Subregion};
 
@@ -7,11 +9,11 @@ use dictionary::extended::{self as ext, Subregion};
 pub trait Parse {
     type Tally : Eq;
 
-    fn value(char) -> Option<u8>;
-    fn tally(&str) -> Self::Tally;
+    fn value(c : char) -> Option<u8>;
+    fn tally(s : &str) -> Self::Tally;
 
     fn alphabetical(s : &str) -> String {
-        s.chars().filter(|c| Self::value(c.clone()) == None).collect()
+        s.chars().filter(|&c| Self::value(c).is_none()).collect()
     }
 
     fn pair<N>(str_klp : &str, normalize : N) -> (String, Self::Tally)
@@ -22,7 +24,7 @@ pub trait Parse {
     }
 }
 
-impl<'a> Parse for Patterns {
+impl Parse for Patterns {
     type Tally = Vec<Locus>;
 
     #[inline] fn value(c : char) -> Option<u8> { c.to_digit(10).map(|n| n as u8) }
@@ -37,7 +39,7 @@ impl<'a> Parse for Patterns {
     }
 }
 
-impl<'a> Parse for Exceptions {
+impl Parse for Exceptions {
     type Tally = Vec<usize>;
 
     #[inline]
@@ -58,7 +60,67 @@ impl<'a> Parse for Exceptions {
     }
 }
 
-impl<'a> Parse for ext::Patterns {
+/// Extended exceptions are given as hyphen-marked words, like standard ones,
+/// or as a word followed by its hyphenation as spelled at line breaks, such as
+/// `asszony asz-szony`. Breaks at which the spelling changes are given the
+/// subregion replacing the letters in which word and hyphenation differ.
+impl Parse for ext::Exceptions {
+    type Tally = Vec<(usize, Option<Subregion>)>;
+
+    #[inline] fn value(c : char) -> Option<u8> { Exceptions::value(c) }
+
+    fn alphabetical(s : &str) -> String {
+        Exceptions::alphabetical(s.split_whitespace().next().unwrap_or(""))
+    }
+
+    fn tally(exception : &str) -> Self::Tally {
+        let mut fields = exception.split_whitespace();
+        match (fields.next(), fields.next()) {
+            (Some(word), Some(hyphenated)) => respelled(&Self::alphabetical(word), hyphenated),
+            (word, _) => Exceptions::tally(word.unwrap_or("")).into_iter().map(|i| (i, None)).collect()
+        }
+    }
+}
+
+/// The opportunities of a word, given its hyphenation as spelled at breaks.
+/// The letters which differ from those of the word are respelled by the
+/// break nearest to them; the other breaks are left as they are.
+fn respelled(word : &str, hyphenated : &str) -> Vec<(usize, Option<Subregion>)> {
+    let letters = Exceptions::alphabetical(hyphenated);
+    let prefix = common_prefix(word, &letters);
+    let suffix = common_suffix(&word[prefix ..], &letters[prefix ..]);
+    let (end, respelled) = (word.len() - suffix, letters.len() - suffix);
+    Exceptions::tally(hyphenated).into_iter().map(|b| {
+        if word == letters || b < prefix {
+            return (b, None)
+        }
+        if b > respelled {
+            return (b + end - respelled, None)
+        }
+        // The letters between the common prefix and suffix are replaced,
+        // about an opportunity as near to the break as their bounds allow.
+        let mut index = cmp::min(cmp::max(b, prefix), end);
+        while !word.is_char_boundary(index) { index -= 1 }
+        (index, Some(Subregion {
+            left : index - prefix,
+            right : end - index,
+            substitution : letters[prefix .. respelled].to_owned(),
+            breakpoint : b - prefix
+        }))
+    }).collect()
+}
+
+/// The length in bytes of the longest common prefix of two strings.
+fn common_prefix(a : &str, b : &str) -> usize {
+    a.char_indices().zip(b.chars()).find(|&((_, x), y)| x != y).map_or(cmp::min(a.len(), b.len()), |((i, _), _)| i)
+}
+
+/// The length in bytes of the longest common suffix of two strings.
+fn common_suffix(a : &str, b : &str) -> usize {
+    a.chars().rev().zip(b.chars().rev()).take_while(|&(x, y)| x == y).map(|(x, _)| x.len_utf8()).sum()
+}
+
+impl Parse for ext::Patterns {
     type Tally = ext::Tally;
 
     #[inline] fn value(c : char) -> Option<u8> { c.to_digit(10).map(|n| n as u8) }
@@ -71,53 +133,229 @@ impl<'a> Parse for ext::Patterns {
     }
 
     fn tally(pattern : &str) -> Self::Tally {
-        use std::str::FromStr;
-
-        // TODO: refactor
-        match pattern.find('/') {
-            None => ext::Tally {
-                standard : Patterns::tally(pattern),
-                subregion : None,
-            },
-            Some(i) => {
-                // Exoneration: we unwrap liberally within this match arm, since failure
-                // would denote a malformed pattern.
-                let err = &["Malformed extended hyphenation pattern: ", pattern].concat();
-
-                let (standard, extension) = (&pattern[.. i], &pattern[i + 1 ..]);
-                let breakpoint = extension.find('=').expect(err);
-                let sub_pattern_end = extension.find(',').expect(err);
-                let sub_pattern = &extension[.. sub_pattern_end];
-                let sub_idxs = &extension[sub_pattern_end + 1 ..];
-
-                let dot_offset = if standard.starts_with('.') { 1 } else { 0 };
-                let (chars_to_op, span) = {
-                    let v : Vec<_> =
-                        sub_idxs.split(',').map(|s| usize::from_str(s).expect(err)).collect();
-
-                    assert!(v.len() == 2, "Malformed extended hyphenation pattern: {}", pattern);
-                    (v[0] + dot_offset, v[1])
-                };
-
-                let tally = Patterns::tally(standard);
-                let alphabetical = Patterns::alphabetical(standard);
-                let substitution = sub_pattern.chars().filter(|&c| c.is_alphabetic()).collect();
-                // Németh always starts the subregion at the character immediately preceding
-                // the opportunity.
-                let chars_to_start = chars_to_op.saturating_sub(1);
-                let start = alphabetical.char_indices().nth(chars_to_start).expect(err).0;
-                let end = alphabetical.char_indices().nth(chars_to_start + span).expect(err).0;
-                let index = alphabetical.char_indices().nth(chars_to_op).expect(err).0 as u8;
-                let (left, right) = (index as usize - start, end - index as usize);
-                let value = tally.iter().find(|&&locus| locus.index == index)
-                                        .map(|&locus| locus.value).expect(err);
-
-                ext::Tally {
-                    standard : tally,
-                    subregion : ( Locus { index, value }
-                                , Subregion { left, right, substitution, breakpoint } ).into()
+        extended_tally(pattern)
+            .unwrap_or_else(|_| panic!("Malformed extended hyphenation pattern: {}", pattern))
+    }
+}
+
+/// Parse an extended pattern, such as `c1k/k=k,1,2`, into its letters and its
+/// tally, as `ext::Patterns::pair` does, reporting malformed patterns rather
+/// than panicking.
+pub fn extended_pair(pattern : &str) -> Result<(String, ext::Tally), &'static str> {
+    Ok((ext::Patterns::alphabetical(pattern), extended_tally(pattern) ?))
+}
+
+fn extended_tally(pattern : &str) -> Result<ext::Tally, &'static str> {
+    let malformed = "malformed extended hyphenation pattern";
+    let i = match pattern.find('/') {
+        None => return Ok(ext::Tally { standard : Patterns::tally(pattern), subregion : None }),
+        Some(i) => i
+    };
+
+    let (standard, extension) = (&pattern[.. i], &pattern[i + 1 ..]);
+    let breakpoint = extension.find('=').ok_or(malformed) ?;
+    let sub_pattern_end = extension.find(',').ok_or(malformed) ?;
+    let sub_pattern = &extension[.. sub_pattern_end];
+    let sub_idxs = &extension[sub_pattern_end + 1 ..];
+
+    let dot_offset = if standard.starts_with('.') { 1 } else { 0 };
+    let (chars_to_op, span) = {
+        let v = sub_idxs.split(',').map(|s| s.parse::<usize>().map_err(|_| malformed))
+            .collect::<Result<Vec<_>, _>>() ?;
+        if v.len() != 2 {
+            return Err(malformed)
+        }
+        (v[0] + dot_offset, v[1])
+    };
+
+    let tally = Patterns::tally(standard);
+    let alphabetical = Patterns::alphabetical(standard);
+    let substitution = sub_pattern.chars().filter(|&c| c.is_alphabetic()).collect();
+    // Németh always starts the subregion at the character immediately preceding
+    // the opportunity.
+    let chars_to_start = chars_to_op.saturating_sub(1);
+    let start = alphabetical.char_indices().nth(chars_to_start).ok_or(malformed) ?.0;
+    // The subregion may extend to the end of the pattern.
+    let end = alphabetical.char_indices().map(|(i, _)| i).chain(Some(alphabetical.len()))
+        .nth(chars_to_start + span).ok_or(malformed) ?;
+    let index = alphabetical.char_indices().nth(chars_to_op).ok_or(malformed) ?.0 as u8;
+    let (left, right) = (index as usize - start, end.checked_sub(index as usize).ok_or(malformed) ?);
+    let value = tally.iter().find(|&&locus| locus.index == index)
+                            .map(|&locus| locus.value).ok_or(malformed) ?;
+
+    Ok(ext::Tally {
+        standard : tally,
+        subregion : ( Locus { index, value }
+                    , Subregion { left, right, substitution, breakpoint } ).into()
+    })
+}
+
+
+/// The kinds of TeX groups.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Group { Patterns, Hyphenation, Other }
+
+/// The patterns and exceptions declared by a TeX file, in `\patterns{…}` and
+/// `\hyphenation{…}` groups respectively. Comments are skipped, as are the
+/// contents of any other group.
+pub fn tex_groups(tex : &str) -> Result<(Vec<&str>, Vec<&str>), &'static str> {
+    let (mut patterns, mut exceptions) = (vec![], vec![]);
+    let mut groups = vec![];
+    let mut pending = Group::Other;
+    for line in tex.lines() {
+        let line = line.split('%').next().unwrap_or("");
+        for token in line.split_whitespace().flat_map(braces) {
+            match token {
+                "{" => groups.push(pending),
+                "}" => { groups.pop().ok_or("unbalanced braces in TeX patterns") ?; },
+                "\\patterns" => pending = Group::Patterns,
+                "\\hyphenation" => pending = Group::Hyphenation,
+                _ => match groups.last() {
+                    Some(&Group::Patterns) if !token.starts_with('\\') => patterns.push(token),
+                    Some(&Group::Hyphenation) if !token.starts_with('\\') => exceptions.push(token),
+                    _ => ()
                 }
             }
+            if token != "\\patterns" && token != "\\hyphenation" {
+                pending = Group::Other;
+            }
+        }
+    }
+    if groups.is_empty() { Ok((patterns, exceptions)) }
+    else { Err("unbalanced braces in TeX patterns") }
+}
+
+/// Split a word around the braces it contains, keeping them as tokens.
+fn braces(word : &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut start = 0;
+    for (i, c) in word.char_indices() {
+        if c == '{' || c == '}' {
+            if start < i { tokens.push(&word[start .. i]) }
+            tokens.push(&word[i .. i + 1]);
+            start = i + 1;
         }
     }
+    if start < word.len() { tokens.push(&word[start ..]) }
+    tokens
+}
+
+
+/// The contents of a libhyphen dictionary, as used by LibreOffice and Firefox
+/// in their `hyph_xx.dic` files.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Dic {
+    /// The encoding declared on the first line, such as `UTF-8`.
+    pub encoding : String,
+    /// The `LEFTHYPHENMIN` and `RIGHTHYPHENMIN` headers, if given.
+    pub minima : (Option<usize>, Option<usize>),
+    /// The `COMPOUNDLEFTHYPHENMIN` and `COMPOUNDRIGHTHYPHENMIN` headers, if
+    /// given, which apply to the parts of compound words.
+    pub compound_minima : (Option<usize>, Option<usize>),
+    /// The comma-separated strings of the `NOHYPHEN` header, around which
+    /// words are not to be broken.
+    pub no_hyphen : Vec<String>,
+    /// The patterns of the first level.
+    pub patterns : Vec<String>,
+    /// The patterns following `NEXTLEVEL`, which hyphenate the parts of
+    /// compound words.
+    pub next_level : Vec<String>
+}
+
+impl Dic {
+    /// Decode and parse a libhyphen dictionary. Dictionaries encoded in
+    /// `UTF-8`, `ISO8859-1` and `ISO8859-15` are supported; others should
+    /// first be converted to `UTF-8`.
+    pub fn parse(bytes : &[u8]) -> Result<Dic, &'static str> {
+        let end = bytes.iter().position(|&b| b == b'\n').unwrap_or(bytes.len());
+        let encoding = ::std::str::from_utf8(&bytes[.. end])
+            .map_err(|_| "malformed encoding line in libhyphen dictionary") ?
+            .trim().to_owned();
+        let rest = bytes.get(end + 1 ..).unwrap_or(&[]);
+        let text = match encoding.to_uppercase().replace('-', "").as_str() {
+            "UTF8" => ::std::str::from_utf8(rest)
+                .map_err(|_| "libhyphen dictionary is not valid UTF-8") ?.to_owned(),
+            "ISO88591" | "LATIN1" => rest.iter().map(|&b| b as char).collect(),
+            "ISO885915" | "LATIN9" => rest.iter().map(|&b| latin9(b)).collect(),
+            _ => return Err("unsupported libhyphen dictionary encoding; convert it to UTF-8")
+        };
+
+        let mut dic = Dic { encoding, .. Dic::default() };
+        let mut next_level = false;
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('%') || line.starts_with('#') {
+                continue
+            }
+            let mut words = line.splitn(2, char::is_whitespace);
+            let (key, value) = (words.next().unwrap_or(""), words.next().map(str::trim));
+            let number = || value.and_then(|v| v.parse().ok())
+                .ok_or("malformed header in libhyphen dictionary");
+            match key {
+                "LEFTHYPHENMIN" => dic.minima.0 = Some(number() ?),
+                "RIGHTHYPHENMIN" => dic.minima.1 = Some(number() ?),
+                "COMPOUNDLEFTHYPHENMIN" => dic.compound_minima.0 = Some(number() ?),
+                "COMPOUNDRIGHTHYPHENMIN" => dic.compound_minima.1 = Some(number() ?),
+                "NOHYPHEN" => dic.no_hyphen = value.unwrap_or("").split(',').map(str::to_owned).collect(),
+                "NEXTLEVEL" => next_level = true,
+                pattern if next_level => dic.next_level.push(pattern.to_owned()),
+                pattern => dic.patterns.push(pattern.to_owned())
+            }
+        }
+        Ok(dic)
+    }
+}
+
+/// The character of the given byte in ISO 8859-15, which departs from
+/// ISO 8859-1 in eight places.
+fn latin9(b : u8) -> char {
+    match b {
+        0xa4 => '€', 0xa6 => 'Š', 0xa8 => 'š', 0xb4 => 'Ž',
+        0xb8 => 'ž', 0xbc => 'Œ', 0xbd => 'œ', 0xbe => 'Ÿ',
+        _ => b as char
+    }
+}
+
+/// Parse a pattern of a libhyphen dictionary, which may carry a non-standard
+/// rule as `pattern/replacement,index,cut`: the `cut` characters starting
+/// from the `index`th letter, counted from one, are replaced around the odd
+/// value of the pattern, whose break the replacement marks with `=`. Should
+/// `index` and `cut` be omitted, the whole pattern is replaced.
+pub(crate) fn libhyphen_pair(pattern : &str) -> Result<(String, ext::Tally), &'static str> {
+    let malformed = "malformed non-standard pattern in libhyphen dictionary";
+    let verbatim = |s : &str| s.to_owned();
+    let (standard, rule) = match pattern.find('/') {
+        None => return Ok(ext::Patterns::pair(pattern, verbatim)),
+        Some(i) => (&pattern[.. i], &pattern[i + 1 ..])
+    };
+    let (letters, tally) = Patterns::pair(standard, verbatim);
+    let dots = if letters.starts_with('.') { 1 } else { 0 };
+    let bounds : Vec<usize> = letters.char_indices().map(|(i, _)| i)
+        .chain(Some(letters.len()))
+        .collect();
+
+    let mut fields = rule.split(',');
+    let replacement = fields.next().unwrap_or("");
+    let (index, cut) : (usize, usize) = match (fields.next(), fields.next(), fields.next()) {
+        (None, _, _) => (1, letters.trim_matches('.').chars().count()),
+        (Some(index), Some(cut), None) =>
+            (index.trim().parse().map_err(|_| malformed) ?, cut.trim().parse().map_err(|_| malformed) ?),
+        _ => return Err(malformed)
+    };
+    let breakpoint = replacement.find('=').ok_or(malformed) ?;
+    let first = (index + dots).checked_sub(1).ok_or(malformed) ?;
+    let (start, end) = match (bounds.get(first), bounds.get(first + cut)) {
+        (Some(&start), Some(&end)) => (start, end),
+        _ => return Err(malformed)
+    };
+    let locus = *tally.iter()
+        .find(|locus| locus.value % 2 == 1 && start <= locus.index as usize && locus.index as usize <= end)
+        .ok_or(malformed) ?;
+
+    let subregion = Subregion {
+        left : locus.index as usize - start,
+        right : end - locus.index as usize,
+        substitution : replacement.replace('=', ""),
+        breakpoint
+    };
+    Ok((letters, ext::Tally { standard : tally, subregion : Some((locus, subregion)) }))
 }
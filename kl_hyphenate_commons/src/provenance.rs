@@ -0,0 +1,73 @@
+//! The provenance of dictionaries.
+//!
+//! Every dictionary records where its patterns came from: the release and
+//! the files of their sources, their license, the date on which it was
+//! built, if any, and the normalization applied to them. The record is serialized
+//! along with the dictionary, such that distributions can tell exactly which
+//! patterns a binary embeds.
+
+use std::env;
+
+
+/// Where the patterns of a dictionary came from, and how they were built.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Provenance {
+    /// The release of the sources, such as a tag or commit of hyph-utf8.
+    pub release : Option<String>,
+    /// The names of the source files, in the order they were read.
+    pub sources : Vec<String>,
+    /// The license of the patterns, as an SPDX expression.
+    pub license : Option<String>,
+    /// The date on which the dictionary was built, as `YYYY-MM-DD`, if it was
+    /// dated.
+    pub built : Option<String>,
+    /// The Unicode normalization form applied to the sources, such as `NFC`,
+    /// if any.
+    pub normalization : Option<String>
+}
+
+impl Provenance {
+    /// The same provenance, dated by the `SOURCE_DATE_EPOCH` environment
+    /// variable, in seconds since the Unix epoch, or else left undated. Builds
+    /// are thus reproducible: the same sources always yield the same bytes,
+    /// unless a date is explicitly asked for.
+    pub fn dated_by_source_epoch(self) -> Self {
+        let epoch = env::var("SOURCE_DATE_EPOCH").ok().and_then(|s| s.trim().parse().ok());
+        Provenance { built : epoch.map(date), .. self }
+    }
+
+    /// The provenance of a dictionary merged from dictionaries of the given
+    /// provenances, as by `Standard::merge`: the sources of both, under both
+    /// licenses. The release and normalization are kept only where both
+    /// agree, whereas the date is that of `self`.
+    pub fn merge(&self, other : &Provenance) -> Provenance {
+        let agreed = |a : &Option<String>, b : &Option<String>| if a == b { a.clone() } else { None };
+        let license = match (&self.license, &other.license) {
+            (Some(a), Some(b)) if a != b => Some(format!("({}) AND ({})", a, b)),
+            (a, b) => a.clone().or_else(|| b.clone())
+        };
+        Provenance {
+            release : agreed(&self.release, &other.release),
+            sources : self.sources.iter().chain(&other.sources).cloned().collect(),
+            license,
+            built : self.built.clone(),
+            normalization : agreed(&self.normalization, &other.normalization)
+        }
+    }
+}
+
+/// The date of the given second since the Unix epoch, as `YYYY-MM-DD`, in
+/// the proleptic Gregorian calendar.
+pub fn date(seconds : u64) -> String {
+    // Days are counted from 0000-03-01, such that leap days end each year.
+    let days = (seconds / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
@@ -0,0 +1,181 @@
+//! Structures for the storage of hyphenation patterns.
+//!
+//! Dictionaries look up every pattern found among the prefixes of each
+//! suffix of a word, which the [`Storage`] trait abstracts over, such that
+//! alternative structures may be benchmarked against each other. Each
+//! dictionary records the [`Backend`] in which its patterns were built.
+//!
+//! [`Storage`]: trait.Storage.html
+//! [`Backend`]: enum.Backend.html
+
+use atlatl::fst::{self, FST, Output, Terminal};
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+
+
+/// The structures which may hold the patterns of a dictionary.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Backend {
+    /// The transducer of `atlatl`, laid out as a double-array trie.
+    #[default]
+    DoubleArray,
+    /// A hash table of patterns, probed for every prefix of the text up to
+    /// the longest pattern, as by libhyphen.
+    HashTable
+}
+
+/// A structure mapping patterns to the ids of their tallies.
+pub trait Storage {
+    /// The backend of the structure.
+    fn backend(&self) -> Backend;
+
+    /// Call `found` with the tally id of every pattern which is a prefix of
+    /// the query, from the shortest to the longest. The empty pattern, if
+    /// any, is only found if `rooted`.
+    fn reap<F>(&self, query : &[u8], rooted : bool, found : F) where F : FnMut(u16);
+
+    /// Every pattern, in lexicographic order, along with its tally id.
+    fn entries(&self) -> Vec<(String, u16)>;
+}
+
+impl Storage for FST<u32, u16> {
+    #[inline] fn backend(&self) -> Backend { Backend::DoubleArray }
+
+    #[inline]
+    fn reap<F>(&self, query : &[u8], rooted : bool, found : F) where F : FnMut(u16) {
+        if rooted {
+            FST::reap(self, query).map(|(_, id)| id).for_each(found)
+        } else {
+            self.reap_past_root(query).map(|(_, id)| id).for_each(found)
+        }
+    }
+
+    fn entries(&self) -> Vec<(String, u16)> {
+        let mut entries : Vec<(Vec<u8>, u16)> = vec![];
+        let root = match self.da.stipe.first() {
+            Some(root) => root.terminal,
+            None => return vec![]
+        };
+        if root.is() {
+            entries.push((vec![], self.state_output.get(&0).cloned().unwrap_or(0)));
+        }
+        walk(self, 0, &mut vec![], 0, &mut entries);
+        entries.into_iter().map(|(key, output)| (String::from_utf8_lossy(&key).into_owned(), output)).collect()
+    }
+}
+
+fn walk(automaton : &FST<u32, u16>, state : u32, key : &mut Vec<u8>, output : u16,
+        entries : &mut Vec<(Vec<u8>, u16)>) {
+    let da = &automaton.da;
+    // The null byte is skipped, as unused cells also check for it.
+    for label in 1 ..= 255u8 {
+        let e = state as usize + 1 + label as usize;
+        match da.stipe.get(e) {
+            Some(stipe) if stipe.check == label => {
+                let (next, output) = (da.next[e], output.mappend(da.output[e]));
+                key.push(label);
+                match stipe.terminal {
+                    Terminal::Not => (),
+                    Terminal::Empty => entries.push((key.clone(), output)),
+                    Terminal::Inner => {
+                        let inner = automaton.state_output.get(&next).cloned().unwrap_or(0);
+                        entries.push((key.clone(), output.mappend(inner)));
+                    }
+                }
+                walk(automaton, next, key, output, entries);
+                key.pop();
+            },
+            _ => ()
+        }
+    }
+}
+
+/// A hash table of patterns, along with the length of the longest.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Table {
+    #[serde(serialize_with = "sorted")]
+    pub patterns : HashMap<Vec<u8>, u16>,
+    pub depth : usize
+}
+
+impl Storage for Table {
+    #[inline] fn backend(&self) -> Backend { Backend::HashTable }
+
+    #[inline]
+    fn reap<F>(&self, query : &[u8], rooted : bool, mut found : F) where F : FnMut(u16) {
+        let shortest = if rooted { 0 } else { 1 };
+        for n in shortest ..= self.depth.min(query.len()) {
+            if let Some(&id) = self.patterns.get(&query[.. n]) {
+                found(id);
+            }
+        }
+    }
+
+    fn entries(&self) -> Vec<(String, u16)> {
+        let mut entries : Vec<_> = self.patterns.iter().map(|(key, &id)| (key.as_slice(), id)).collect();
+        entries.sort_unstable();
+        entries.into_iter().map(|(key, id)| (String::from_utf8_lossy(key).into_owned(), id)).collect()
+    }
+}
+
+/// Serialize a hash map with its entries sorted by key, rather than in the
+/// order of its hasher, such that the same map is always serialized to the
+/// same bytes. Maps so serialized deserialize as any other.
+pub(crate) fn sorted<K, V, H, S>(map : &HashMap<K, V, H>, serializer : S) -> Result<S::Ok, S::Error>
+where K : Ord + Serialize, V : Serialize, S : Serializer {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// The patterns of a dictionary, held in the backend chosen when it was built.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Automaton {
+    DoubleArray(FST<u32, u16>),
+    HashTable(Table)
+}
+
+impl Default for Automaton {
+    fn default() -> Self { Automaton::DoubleArray(FST::default()) }
+}
+
+impl Automaton {
+    /// Store the given patterns, sorted and free of duplicates, in the given
+    /// backend.
+    pub fn build(backend : Backend, pairs : Vec<(String, u16)>) -> Result<Self, fst::Error> {
+        Ok(match backend {
+            Backend::DoubleArray => {
+                let builder = fst::Builder::from_iter(pairs) ?;
+                Automaton::DoubleArray(FST::from_builder(&builder) ?)
+            },
+            Backend::HashTable => {
+                let depth = pairs.iter().map(|(pattern, _)| pattern.len()).max().unwrap_or(0);
+                let patterns = pairs.into_iter().map(|(pattern, id)| (pattern.into_bytes(), id)).collect();
+                Automaton::HashTable(Table { patterns, depth })
+            }
+        })
+    }
+}
+
+impl Storage for Automaton {
+    #[inline]
+    fn backend(&self) -> Backend {
+        match *self {
+            Automaton::DoubleArray(ref fst) => fst.backend(),
+            Automaton::HashTable(ref table) => table.backend()
+        }
+    }
+
+    #[inline]
+    fn reap<F>(&self, query : &[u8], rooted : bool, found : F) where F : FnMut(u16) {
+        match *self {
+            Automaton::DoubleArray(ref fst) => Storage::reap(fst, query, rooted, found),
+            Automaton::HashTable(ref table) => table.reap(query, rooted, found)
+        }
+    }
+
+    fn entries(&self) -> Vec<(String, u16)> {
+        match *self {
+            Automaton::DoubleArray(ref fst) => fst.entries(),
+            Automaton::HashTable(ref table) => table.entries()
+        }
+    }
+}
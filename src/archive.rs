@@ -0,0 +1,148 @@
+/*! Many dictionaries bundled into a single file
+
+Shipping every dictionary as a file of its own is awkward for installers and
+for bundling web assets. An [`Archive`] gathers them into a single blob,
+headed by an index of their names and locations. Opening an archive only
+reads its index; each dictionary is then located by a table lookup and
+deserialized when it is requested.
+
+```ignore
+use kl_hyphenate::archive::{self, Archive};
+
+let bytes = archive::archive_dir("dictionaries") ?;
+fs::write("dictionaries.archive", &bytes) ?;
+
+// Later, or elsewhere:
+let bytes = fs::read("dictionaries.archive") ?;
+let archive = Archive::from_bytes(&bytes) ?;
+let en_us : Standard = archive.load(Language::EnglishUS) ?;
+```
+
+Dictionaries are filed under the names given by [`Load::file_name`], and
+kept in whichever format they were serialized.
+
+[`Archive`]: struct.Archive.html
+[`Load::file_name`]: ../load/trait.Load.html#tymethod.file_name
+*/
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str;
+
+use kl_hyphenate_commons::Language;
+use load::{Error, Load, Result};
+use packed::u32_at;
+
+
+/// The leading bytes of every archive.
+const MAGIC : &[u8] = b"KLHA";
+
+const TRUNCATED : &str = "the archive is truncated";
+
+/// Bundle the given named dictionaries into an archive read by `Archive`.
+pub fn archive<'e, I>(entries : I) -> Vec<u8>
+where I : IntoIterator<Item = (&'e str, &'e [u8])>
+{
+    let mut entries : Vec<_> = entries.into_iter().collect();
+    entries.sort_by_key(|&(name, _)| name);
+    entries.dedup_by_key(|&mut (name, _)| name);
+
+    let index_len : usize = entries.iter().map(|&(name, _)| 12 + name.len()).sum();
+    let mut offset = MAGIC.len() + 4 + index_len;
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for &(name, dict) in &entries {
+        bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&(offset as u32).to_le_bytes());
+        bytes.extend_from_slice(&(dict.len() as u32).to_le_bytes());
+        offset += dict.len();
+    }
+    for &(_, dict) in &entries {
+        bytes.extend_from_slice(dict);
+    }
+    bytes
+}
+
+/// Bundle every dictionary of a directory, such as the `dictionaries` folder
+/// written by the build, into an archive.
+pub fn archive_dir<P>(dir : P) -> io::Result<Vec<u8>> where P : AsRef<Path> {
+    let mut dicts = vec![];
+    for entry in fs::read_dir(dir) ? {
+        let path = entry ?.path();
+        let name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if name.ends_with(".bincode") => name.to_owned(),
+            _ => continue
+        };
+        dicts.push((name, fs::read(&path) ?));
+    }
+    Ok(archive(dicts.iter().map(|(name, dict)| (name.as_str(), dict.as_slice()))))
+}
+
+/// A bundle of dictionaries, read in place from its bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Archive<'a> {
+    /// The name and bytes of each dictionary, sorted by name.
+    index : Vec<(&'a str, &'a [u8])>
+}
+
+impl<'a> Archive<'a> {
+    /// Read the index of an archive, ensuring that every dictionary it lists
+    /// lies within its bytes.
+    pub fn from_bytes(bytes : &'a [u8]) -> Result<Self> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(Error::Malformed("not a dictionary archive"))
+        }
+        let mut at = MAGIC.len();
+        let mut take = |n : usize| -> Result<&'a [u8]> {
+            let end = at.checked_add(n).ok_or(Error::Malformed(TRUNCATED)) ?;
+            let taken = bytes.get(at .. end).ok_or(Error::Malformed(TRUNCATED)) ?;
+            at = end;
+            Ok(taken)
+        };
+
+        let count = u32_at(take(4) ?, 0) as usize;
+        let mut index = Vec::with_capacity(count.min(bytes.len()));
+        for _ in 0 .. count {
+            let n = u32_at(take(4) ?, 0) as usize;
+            let name = str::from_utf8(take(n) ?)
+                .map_err(|_| Error::Malformed("the archive holds invalid UTF-8")) ?;
+            let location = take(8) ?;
+            let (offset, len) = (u32_at(location, 0) as usize, u32_at(location, 1) as usize);
+            let dict = offset.checked_add(len).and_then(|end| bytes.get(offset .. end));
+            index.push((name, dict.ok_or(Error::Malformed(TRUNCATED)) ?));
+        }
+        if index.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err(Error::Malformed("the archive index is not sorted"))
+        }
+        Ok(Archive { index })
+    }
+
+    /// The names of the archived dictionaries, in order.
+    pub fn names(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.index.iter().map(|&(name, _)| name)
+    }
+
+    /// The serialized bytes of the dictionary of the given name, if archived.
+    pub fn get(&self, name : &str) -> Option<&'a [u8]> {
+        self.index.binary_search_by_key(&name, |&(name, _)| name).ok()
+            .map(|i| self.index[i].1)
+    }
+
+    /// The languages for which a dictionary of type `D` is archived, in order
+    /// of their codes.
+    pub fn languages<D : Load>(&self) -> Vec<Language> {
+        self.names().filter_map(|name| {
+            let code = name.split('.').next() ?;
+            code.parse().ok().filter(|&lang| D::file_name(lang) == name)
+        }).collect()
+    }
+
+    /// Deserialize the archived dictionary of type `D` for the given language,
+    /// failing with `Error::Resource` if there is none.
+    pub fn load<D : Load>(&self, lang : Language) -> Result<D> {
+        let bytes = self.get(&D::file_name(lang)).ok_or(Error::Resource) ?;
+        D::from_bytes(lang, bytes)
+    }
+}
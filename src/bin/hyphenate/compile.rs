@@ -0,0 +1,265 @@
+//! The `compile` subcommand, which builds dictionaries from pattern sources.
+//!
+//! ```text
+//! $ hyphenate compile -l de-1996 --normalize nfc hyph-de-1996.pat.txt house.hyp -o de.bincode
+//! $ hyphenate compile -l hu --extended hyph_hu_HU.dic
+//! ```
+//!
+//! Sources are merged in the order given, the patterns and exceptions of later
+//! sources prevailing over those of earlier ones, as by `Standard::merge`.
+
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use kl_hyphenate::{CaseFolding, Language, Load, NormalizationForm, Standard};
+use kl_hyphenate::extended::Extended;
+use kl_hyphenate_commons::dictionary::{Exceptions, Patterns, MAX_LENGTH};
+use kl_hyphenate_commons::dictionary::extended as ext;
+use kl_hyphenate_commons::parse::{Dic, Parse};
+use kl_hyphenate_commons::provenance::Provenance;
+use Args;
+use Usage;
+
+
+pub const USAGE : &str = "\
+Usage: hyphenate compile -l <LANGUAGE> [OPTIONS] <SOURCE>...
+
+Build a dictionary from the given sources, in the format loaded by the crate.
+Sources are recognized by their extension, ignoring a trailing .txt:
+
+  .pat  Standard patterns, one per line, as distributed by hyph-utf8
+  .ext  Extended patterns, one per line, as found under patterns/
+  .hyp  Exceptions, one per line, with breaks marked by hyphens
+  .tex  TeX patterns and exceptions, in \\patterns{...} and \\hyphenation{...}
+  .dic  libhyphen dictionaries, as used by LibreOffice and Firefox
+
+Options:
+  -l, --language <TAG>      The language of the dictionary, e.g. en-us or de-CH
+  -o, --output <PATH>       The file to write [default: named after the language
+                            and type, e.g. en-us.standard.bincode]
+  -n, --normalize <FORM>    The normalization applied to the sources: nfc, nfd,
+                            nfkc, nfkd or none [default: none]
+  -e, --extended            Build an extended dictionary, which sources of
+                            either kind may feed, rather than a standard one
+      --minima <LEFT,RIGHT> The minimum number of chars before the first and
+                            after the last break [default: those of the first
+                            source, or of the language]
+  -h, --help                Print this message
+";
+
+/// The options given to the subcommand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    language : Language,
+    output : Option<PathBuf>,
+    normalization : Option<NormalizationForm>,
+    extended : bool,
+    minima : Option<(usize, usize)>,
+    sources : Vec<PathBuf>
+}
+
+impl Options {
+    /// Parse the arguments following the subcommand, or `None` if help was
+    /// requested.
+    pub fn parse<I>(args : I) -> Result<Option<Options>, Usage> where I : Iterator<Item = String> {
+        let mut args = Args::new(args, USAGE);
+        let (mut language, mut output, mut minima) = (None, None, None);
+        let (mut normalization, mut extended, mut sources) = (None, false, vec![]);
+        while let Some((flag, inline)) = args.next() {
+            match flag.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-l" | "--language" => language = Some(args.language(&flag, inline) ?),
+                "-o" | "--output" => output = Some(PathBuf::from(args.value(&flag, inline) ?)),
+                "-n" | "--normalize" => normalization = match args.value(&flag, inline) ?.as_str() {
+                    form if form.eq_ignore_ascii_case("none") => None,
+                    form => Some(form.parse().map_err(|_| args.error(format!("unknown normalization form: {}", form))) ?)
+                },
+                "-e" | "--extended" => extended = true,
+                "--minima" => minima = Some(args.minima(&flag, inline) ?),
+                _ if flag.starts_with('-') && flag != "-" => {
+                    return Err(args.error(format!("unexpected argument: {}", flag)))
+                },
+                _ => sources.push(PathBuf::from(flag))
+            }
+        }
+
+        if sources.is_empty() {
+            return Err(args.error("at least one source is required".to_owned()))
+        }
+        Ok(Some(Options {
+            language : language.ok_or_else(|| args.error("a language is required".to_owned())) ?,
+            output, normalization, extended, minima, sources
+        }))
+    }
+}
+
+/// The kinds of sources, by extension.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Kind { Patterns, Extended, Exceptions, Tex, Dic }
+
+impl Kind {
+    fn of(path : &Path) -> Result<Kind, String> {
+        let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        let name = name.strip_suffix(".txt").unwrap_or(name);
+        match name.rsplit('.').next() {
+            Some("pat") => Ok(Kind::Patterns),
+            Some("ext") => Ok(Kind::Extended),
+            Some("hyp") => Ok(Kind::Exceptions),
+            Some("tex") => Ok(Kind::Tex),
+            Some("dic") => Ok(Kind::Dic),
+            _ => Err(format!("{}: unknown kind of source", path.display()))
+        }
+    }
+}
+
+/// The text normalized to the given form, if any.
+fn normalize(s : &str, normalization : Option<NormalizationForm>) -> String {
+    match normalization {
+        Some(form) => form.apply(s),
+        None => s.to_owned()
+    }
+}
+
+/// The entries of a source listing one per line, normalized.
+fn lines(text : &str, normalization : Option<NormalizationForm>) -> impl Iterator<Item = String> + '_ {
+    text.lines().map(str::trim).filter(|line| !line.is_empty()).map(move |line| normalize(line, normalization))
+}
+
+/// A libhyphen dictionary re-encoded as UTF-8, its patterns normalized.
+fn normalized_dic(bytes : Vec<u8>, normalization : Option<NormalizationForm>) -> Result<Vec<u8>, &'static str> {
+    if normalization.is_none() { return Ok(bytes) }
+    let dic = Dic::parse(&bytes) ?;
+    let mut text = String::from("UTF-8\n");
+    if let Some(left) = dic.minima.0 { text += &format!("LEFTHYPHENMIN {}\n", left) }
+    if let Some(right) = dic.minima.1 { text += &format!("RIGHTHYPHENMIN {}\n", right) }
+    for pattern in dic.patterns {
+        text += &normalize(&pattern, normalization);
+        text.push('\n');
+    }
+    Ok(text.into_bytes())
+}
+
+/// A standard dictionary built from a single source, which must not hold
+/// extended patterns.
+fn standard(options : &Options, path : &Path, kind : Kind) -> Result<Standard, String> {
+    let lang = options.language;
+    let at = |e : &dyn Display| format!("{}: {}", path.display(), e);
+    let bytes = fs::read(path).map_err(|e| at(&e)) ?;
+    let text = || String::from_utf8(bytes.clone()).map_err(|e| at(&e));
+    match kind {
+        Kind::Patterns => {
+            let pairs = lines(&text() ?, options.normalization).map(|p| Patterns::pair(&p, |s| s.to_owned())).collect::<Vec<_>>();
+            Standard::from_pairs(lang, pairs).map_err(|e| at(&e))
+        },
+        Kind::Exceptions => {
+            let mut dict = Standard::from_pairs(lang, vec![]).map_err(|e| at(&e)) ?;
+            Arc::make_mut(&mut dict.exceptions).0
+                .extend(lines(&text() ?, options.normalization).map(|e| Exceptions::pair(&e, |s| s.to_owned())));
+            Ok(dict)
+        },
+        Kind::Tex => {
+            let tex = normalize(&text() ?, options.normalization);
+            Standard::from_tex(lang, &mut tex.as_bytes()).map_err(|e| at(&e))
+        },
+        Kind::Dic => {
+            let dic = normalized_dic(bytes, options.normalization).map_err(|e| at(&e)) ?;
+            Standard::from_dic(lang, &mut dic.as_slice()).map_err(|e| at(&e))
+        },
+        Kind::Extended => Err(at(&"extended patterns require --extended"))
+    }
+}
+
+/// An extended dictionary built from a single source.
+fn extended(options : &Options, path : &Path, kind : Kind) -> Result<Extended, String> {
+    let lang = options.language;
+    let at = |e : &dyn Display| format!("{}: {}", path.display(), e);
+    match kind {
+        Kind::Extended => {
+            let text = fs::read_to_string(path).map_err(|e| at(&e)) ?;
+            let pairs = lines(&text, options.normalization).map(|p| ext::Patterns::pair(&p, |s| s.to_owned())).collect::<Vec<_>>();
+            Ok(Extended {
+                language : lang,
+                patterns : Arc::new(ext::Patterns::from_pairs(pairs).map_err(|e| at(&e)) ?),
+                exceptions : Arc::default(),
+                minima : lang.minima(),
+                provenance : Provenance::default(),
+                max_length : MAX_LENGTH,
+                normalization : None,
+                case_folding : CaseFolding::default()
+            })
+        },
+        Kind::Dic => {
+            let bytes = fs::read(path).map_err(|e| at(&e)) ?;
+            let dic = normalized_dic(bytes, options.normalization).map_err(|e| at(&e)) ?;
+            Extended::from_dic(lang, &mut dic.as_slice()).map_err(|e| at(&e))
+        },
+        kind => standard(options, path, kind).and_then(|dict| {
+            let pairs = dict.patterns.entries().into_iter()
+                .map(|(letters, tally)| (letters, ext::Tally { standard : tally.clone(), subregion : None }));
+            let exceptions = dict.exceptions.0.iter()
+                .map(|(word, breaks)| (word.clone(), breaks.iter().map(|&i| (i, None)).collect()));
+            Ok(Extended {
+                language : lang,
+                patterns : Arc::new(ext::Patterns::from_pairs(pairs).map_err(|e| at(&e)) ?),
+                exceptions : Arc::new(ext::Exceptions(exceptions.collect())),
+                minima : dict.minima,
+                provenance : dict.provenance,
+                max_length : MAX_LENGTH,
+                normalization : None,
+                case_folding : CaseFolding::default()
+            })
+        })
+    }
+}
+
+pub fn run(options : &Options) -> Result<(), String> {
+    let kinds = options.sources.iter().map(|path| Kind::of(path)).collect::<Result<Vec<_>, _>>() ?;
+    let sources = options.sources.iter().zip(kinds);
+    let written = if options.extended {
+        let mut dict : Option<Extended> = None;
+        for (path, kind) in sources {
+            let part = extended(options, path, kind) ?;
+            dict = Some(match dict {
+                Some(dict) => dict.merge(&part).map_err(|e| e.to_string()) ?,
+                None => part
+            });
+        }
+        let dict = dict.expect("at least one source is required");
+        let dict = match options.minima { Some(minima) => dict.with_minima(minima), None => dict };
+        dict.with_provenance(provenance(options)).with_normalization(options.normalization)
+            .save(output(options, Extended::file_name))
+    } else {
+        let mut dict : Option<Standard> = None;
+        for (path, kind) in sources {
+            let part = standard(options, path, kind) ?;
+            dict = Some(match dict {
+                Some(dict) => dict.merge(&part).map_err(|e| e.to_string()) ?,
+                None => part
+            });
+        }
+        let dict = dict.expect("at least one source is required");
+        let dict = match options.minima { Some(minima) => dict.with_minima(minima), None => dict };
+        dict.with_provenance(provenance(options)).with_normalization(options.normalization)
+            .save(output(options, Standard::file_name))
+    };
+    written.map_err(|e| format!("cannot write the dictionary: {}", e))
+}
+
+/// The provenance of the dictionary built from the sources, dated by
+/// `SOURCE_DATE_EPOCH` if it is set.
+fn provenance(options : &Options) -> Provenance {
+    Provenance {
+        sources : options.sources.iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect(),
+        normalization : options.normalization.map(|form| form.name().to_owned()),
+        .. Provenance::default()
+    }.dated_by_source_epoch()
+}
+
+fn output<F>(options : &Options, file_name : F) -> PathBuf where F : Fn(Language) -> String {
+    options.output.clone().unwrap_or_else(|| PathBuf::from(file_name(options.language)))
+}
@@ -0,0 +1,105 @@
+//! The `diff` subcommand, which reports the words two dictionaries hyphenate
+//! differently.
+//!
+//! ```text
+//! $ hyphenate diff -l en-us old/en-us.standard.bincode new/en-us.standard.bincode words.txt
+//! associate       as-so-ciate     as-so-ci-ate
+//! 1 of 3 words differ
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+
+use kl_hyphenate::{Language, Load, Standard};
+use kl_hyphenate::diff::diff;
+use Args;
+use Usage;
+
+
+pub const USAGE : &str = "\
+Usage: hyphenate diff -l <LANGUAGE> [OPTIONS] <BEFORE> <AFTER> [WORDS]
+
+Hyphenate every word of the list WORDS, one per line, or of standard input,
+with both of the dictionaries at BEFORE and AFTER. Each word whose breaks
+differ is written to standard output, followed by its hyphenation before and
+after, separated by tabs; a summary is written to standard error.
+
+Options:
+  -l, --language <TAG>      The language of both dictionaries, e.g. en-us
+  -m, --marker <MARK>       The mark inserted at breaks [default: -]
+  -h, --help                Print this message
+";
+
+/// The options given to the subcommand.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    language : Language,
+    marker : String,
+    before : PathBuf,
+    after : PathBuf,
+    words : Option<PathBuf>
+}
+
+impl Options {
+    /// Parse the arguments following the subcommand, or `None` if help was
+    /// requested.
+    pub fn parse<I>(args : I) -> Result<Option<Options>, Usage> where I : Iterator<Item = String> {
+        let mut args = Args::new(args, USAGE);
+        let (mut language, mut marker, mut paths) = (None, None, vec![]);
+        while let Some((flag, inline)) = args.next() {
+            match flag.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-l" | "--language" => language = Some(args.language(&flag, inline) ?),
+                "-m" | "--marker" => marker = Some(args.value(&flag, inline) ?),
+                _ if flag.starts_with('-') => {
+                    return Err(args.error(format!("unexpected argument: {}", flag)))
+                },
+                _ => paths.push(PathBuf::from(flag))
+            }
+        }
+
+        let language = language.ok_or_else(|| args.error("a language is required".to_owned())) ?;
+        let mut paths = paths.into_iter();
+        match (paths.next(), paths.next(), paths.next(), paths.next()) {
+            (Some(before), Some(after), words, None) => Ok(Some(Options {
+                language,
+                marker : marker.unwrap_or_else(|| "-".to_owned()),
+                before, after, words
+            })),
+            (_, None, _, _) => Err(args.error("two dictionaries are required".to_owned())),
+            _ => Err(args.error("at most one word list may be given".to_owned()))
+        }
+    }
+}
+
+pub fn run(options : &Options) -> Result<(), String> {
+    let load = |path : &PathBuf| Standard::from_path(options.language, path)
+        .map_err(|e| format!("{}: {}", path.display(), e));
+    let (before, after) = (load(&options.before) ?, load(&options.after) ?);
+
+    let words : io::Result<Vec<String>> = match options.words {
+        Some(ref path) => {
+            let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e)) ?;
+            BufReader::new(file).lines().collect()
+        },
+        None => io::stdin().lock().lines().collect()
+    };
+    let words = words.map_err(|e| format!("cannot read the words: {}", e)) ?;
+    let words = words.iter().map(|word| word.trim()).filter(|word| !word.is_empty()).collect::<Vec<_>>();
+
+    let differences = diff(&before, &after, &words);
+    let stdout = io::stdout();
+    let mut output = io::BufWriter::new(stdout.lock());
+    let written = differences.iter().try_for_each(|difference| {
+        let (old, new) = difference.marked(&options.marker);
+        writeln!(output, "{}\t{}\t{}", difference.word, old, new)
+    }).and_then(|_| output.flush());
+    match written {
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => (),
+        written => written.map_err(|e| e.to_string()) ?
+    }
+
+    eprintln!("{} of {} words differ", differences.len(), words.len());
+    Ok(())
+}
@@ -0,0 +1,208 @@
+//! Hyphenate the text or words read from standard input.
+//!
+//! ```text
+//! $ echo "I know noble accents" | hyphenate -l en-us
+//! I know no-ble ac-cents
+//! $ printf "anfractuous\nhyphenation\n" | hyphenate -l en-us --words --marker shy
+//! ```
+//!
+//! Dictionaries are looked up among those embedded in the binary, then in the
+//! directory given by `--dictionaries`, `$KL_HYPHENATE_DICTIONARIES` or else
+//! `dictionaries`, as they are named when built. Custom dictionaries are built
+//! from pattern sources by the `compile` subcommand, and compared over word
+//! lists by the `diff` subcommand.
+
+extern crate kl_hyphenate;
+extern crate kl_hyphenate_commons;
+
+use std::env;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::process;
+
+use kl_hyphenate::{Hyphenator, Language, Standard};
+use kl_hyphenate::registry::{Registry, Source};
+use kl_hyphenate::text::Stream;
+
+mod compile;
+mod diff;
+
+
+const USAGE : &str = "\
+Usage: hyphenate -l <LANGUAGE> [OPTIONS]
+       hyphenate compile --help
+       hyphenate diff --help
+
+Hyphenate the text read from standard input, writing it to standard output
+with breaks marked.
+
+Options:
+  -l, --language <TAG>      The language of the dictionary, e.g. en-us or de-CH
+  -d, --dictionaries <DIR>  The directory of dictionaries, searched after those
+                            embedded [default: $KL_HYPHENATE_DICTIONARIES,
+                            or dictionaries]
+  -m, --marker <MARK>       The mark inserted at breaks: any string, such as
+                            \\-, or shy for the soft hyphen [default: -]
+  -w, --words               Read one word per line, rather than running text
+      --minima <LEFT,RIGHT> The minimum number of chars before the first and
+                            after the last break, in place of the dictionary's
+  -h, --help                Print this message
+";
+
+/// A failure to understand the command line, along with the usage of the
+/// command at fault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Usage(String, &'static str);
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\n\n{}", self.0, self.1)
+    }
+}
+
+/// The successive flags and arguments of a command line.
+struct Args<I> {
+    args : I,
+    usage : &'static str
+}
+
+impl<I> Args<I> where I : Iterator<Item = String> {
+    fn new(args : I, usage : &'static str) -> Self { Args { args, usage } }
+
+    /// The next argument, split from its value if given as `--flag=value`.
+    fn next(&mut self) -> Option<(String, Option<String>)> {
+        let arg = self.args.next() ?;
+        Some(match arg.find('=') {
+            Some(i) if arg.starts_with("--") => (arg[.. i].to_owned(), Some(arg[i + 1 ..].to_owned())),
+            _ => (arg, None)
+        })
+    }
+
+    /// The value of the given flag, inline or following it.
+    fn value(&mut self, flag : &str, inline : Option<String>) -> Result<String, Usage> {
+        inline.or_else(|| self.args.next()).ok_or_else(|| self.error(format!("{} requires a value", flag)))
+    }
+
+    fn error(&self, message : String) -> Usage { Usage(message, self.usage) }
+
+    /// The language tagged by the value of the given flag.
+    fn language(&mut self, flag : &str, inline : Option<String>) -> Result<Language, Usage> {
+        let tag = self.value(flag, inline) ?;
+        Language::from_tag(&tag).ok_or_else(|| self.error(format!("unknown language: {}", tag)))
+    }
+
+    /// The minima given as `left,right` by the value of the given flag.
+    fn minima(&mut self, flag : &str, inline : Option<String>) -> Result<(usize, usize), Usage> {
+        let pair = self.value(flag, inline) ?;
+        let parsed = match pair.split_once(',') {
+            Some((l, r)) => l.trim().parse().ok().zip(r.trim().parse().ok()),
+            None => None
+        };
+        parsed.ok_or_else(|| self.error(format!("invalid minima: {}", pair)))
+    }
+}
+
+/// The options given on the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Options {
+    language : Language,
+    dictionaries : PathBuf,
+    marker : String,
+    words : bool,
+    minima : Option<(usize, usize)>
+}
+
+impl Options {
+    /// Parse the arguments following the name of the program, or `None` if
+    /// help was requested.
+    fn parse<I>(args : I) -> Result<Option<Options>, Usage> where I : Iterator<Item = String> {
+        let mut args = Args::new(args, USAGE);
+        let (mut language, mut dictionaries, mut marker, mut words, mut minima) = (None, None, None, false, None);
+        while let Some((flag, inline)) = args.next() {
+            match flag.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-l" | "--language" => language = Some(args.language(&flag, inline) ?),
+                "-d" | "--dictionaries" => dictionaries = Some(PathBuf::from(args.value(&flag, inline) ?)),
+                "-m" | "--marker" => marker = Some(match args.value(&flag, inline) ?.as_str() {
+                    "shy" | "SHY" => "\u{00ad}".to_owned(),
+                    mark => mark.to_owned()
+                }),
+                "-w" | "--words" => words = true,
+                "--minima" => minima = Some(args.minima(&flag, inline) ?),
+                _ => return Err(args.error(format!("unexpected argument: {}", flag)))
+            }
+        }
+
+        let dictionaries = dictionaries
+            .or_else(|| env::var_os("KL_HYPHENATE_DICTIONARIES").map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("dictionaries"));
+        Ok(Some(Options {
+            language : language.ok_or_else(|| args.error("a language is required".to_owned())) ?,
+            dictionaries,
+            marker : marker.unwrap_or_else(|| "-".to_owned()),
+            words,
+            minima
+        }))
+    }
+}
+
+fn run(options : &Options) -> Result<(), String> {
+    let registry : Registry<Standard> = Registry::new()
+        .source(Source::Embedded)
+        .source(Source::Dir(options.dictionaries.clone()));
+    let dict = registry.get(options.language)
+        .map_err(|e| format!("cannot load the dictionary for {}: {}", options.language.code(), e)) ?;
+    let dict = match options.minima {
+        Some(minima) => (*dict).clone().with_minima(minima),
+        None => (*dict).clone()
+    };
+
+    let (stdin, stdout) = (io::stdin(), io::stdout());
+    let written = if options.words {
+        let mut output = io::BufWriter::new(stdout.lock());
+        stdin.lock().lines().try_for_each(|line| {
+            let line = line ?;
+            let mut segments = dict.hyphenate(line.trim()).into_iter();
+            segments.mark_with(&options.marker);
+            writeln!(output, "{}", segments.collect::<String>())
+        }).and_then(|_| output.flush())
+    } else {
+        Stream::new(&dict).mark_with(&options.marker).copy(stdin.lock(), stdout.lock())
+    };
+    match written {
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        written => written.map_err(|e| e.to_string())
+    }
+}
+
+fn main() {
+    let mut args = env::args().skip(1).peekable();
+    let result = match args.peek().map(String::as_str) {
+        Some("compile") => {
+            args.next();
+            match compile::Options::parse(args) {
+                Ok(Some(options)) => compile::run(&options),
+                Ok(None) => { print!("{}", compile::USAGE); return },
+                Err(usage) => { eprintln!("hyphenate compile: {}", usage); process::exit(2) }
+            }
+        },
+        Some("diff") => {
+            args.next();
+            match diff::Options::parse(args) {
+                Ok(Some(options)) => diff::run(&options),
+                Ok(None) => { print!("{}", diff::USAGE); return },
+                Err(usage) => { eprintln!("hyphenate diff: {}", usage); process::exit(2) }
+            }
+        },
+        _ => match Options::parse(args) {
+            Ok(Some(options)) => run(&options),
+            Ok(None) => { print!("{}", USAGE); return },
+            Err(usage) => { eprintln!("hyphenate: {}", usage); process::exit(2) }
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("hyphenate: {}", e);
+        process::exit(1)
+    }
+}
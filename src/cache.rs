@@ -0,0 +1,378 @@
+/*! Memoizing hyphenation
+
+Words recur constantly in natural language: a few hundred account for half of
+most texts. A [`Cached`] hyphenator remembers the breaks of the words most
+recently hyphenated, up to a given number, and serves repeated words without
+consulting the dictionary again.
+
+```ignore
+use kl_hyphenate::cache::Cached;
+
+let en_us = Cached::new(en_us, 10_000);
+for word in text.split_whitespace() {
+    let hyphenated = en_us.hyphenate(word);
+    // ...
+}
+let stats = en_us.stats();
+println!("{:.0}% of words were cached", 100.0 * stats.hit_rate());
+```
+
+A cache may be warmed up before use, to spare the first requests the cost of
+hyphenation, with the most frequent words of the language. Frequency lists
+in the common formats, one word per line along with its count, are read by
+[`warm_from_list`].
+
+```ignore
+let list = BufReader::new(File::open("en_50k.txt") ?);
+en_us.warm_from_list(list) ?;
+```
+
+Words are cached as given, since their breaks are indices into them; `Table`
+and `table` are cached apart. Modifying the exceptions of the hyphenator
+empties the cache.
+
+Only hyphenators whose opportunities are plain indices, such as `Standard`
+dictionaries, can be cached; the opportunities of extended dictionaries
+borrow their subregions from the dictionary.
+
+Many more words share a stem than recur verbatim, particularly in
+morphologically rich languages. A [`Stemmed`] dictionary remembers the
+patterns matched within the stems of the words it recently hyphenated, such
+that "hyphenated" and "hyphenating" only match the patterns beyond
+"hyphenat"; see [`Stems`].
+
+```ignore
+use kl_hyphenate::cache::Stemmed;
+
+let hu = Stemmed::new(hu, 100_000);
+```
+
+[`Cached`]: struct.Cached.html
+[`warm_from_list`]: struct.Cached.html#method.warm_from_list
+[`Stemmed`]: struct.Stemmed.html
+[`Stems`]: ../score/struct.Stems.html
+*/
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead};
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::Standard;
+use kl_hyphenate_commons::dictionary::extended::{Extended, Subregion};
+use kl_hyphenate_commons::normalization::{CaseFolding, NormalizationForm};
+use hyphenator::{self, hyphenate_prepared, Hyphenator, Prepared, Word};
+use score::{Score, Stems};
+
+
+/// A hyphenator which remembers the breaks of the words it most recently
+/// hyphenated.
+#[derive(Debug)]
+pub struct Cached<H> {
+    inner : H,
+    cache : Mutex<Lru<Vec<usize>>>,
+    hits : AtomicU64,
+    misses : AtomicU64
+}
+
+/// The performance of a cache since its creation, or since its statistics
+/// were last reset.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of words found in the cache.
+    pub hits : u64,
+    /// The number of words hyphenated anew.
+    pub misses : u64,
+    /// The number of words currently cached.
+    pub len : usize
+}
+
+impl Stats {
+    /// The proportion of words found in the cache, or 0 if none were
+    /// hyphenated.
+    pub fn hit_rate(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            total => self.hits as f64 / total as f64
+        }
+    }
+}
+
+impl<H> Cached<H> {
+    /// Cache up to `capacity` words hyphenated by `inner`.
+    pub fn new(inner : H, capacity : usize) -> Self {
+        Cached {
+            inner,
+            cache : Mutex::new(Lru::new(capacity)),
+            hits : AtomicU64::new(0),
+            misses : AtomicU64::new(0)
+        }
+    }
+
+    /// The hyphenator underneath the cache.
+    pub fn inner(&self) -> &H { &self.inner }
+
+    /// Drop the cache, returning the hyphenator underneath.
+    pub fn into_inner(self) -> H { self.inner }
+
+    /// The performance of the cache so far.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            hits : self.hits.load(Ordering::Relaxed),
+            misses : self.misses.load(Ordering::Relaxed),
+            len : self.lock().entries.len()
+        }
+    }
+
+    /// Reset the counts of hits and misses, keeping the cached words.
+    pub fn reset_stats(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    /// Forget every cached word.
+    pub fn clear(&self) { self.lock().clear() }
+
+    // The cache is left consistent by every operation, even one that panics.
+    fn lock(&self) -> MutexGuard<'_, Lru<Vec<usize>>> {
+        self.cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Hyphenate and cache the given words, most frequent first, without
+    /// counting them in the statistics of the cache; should there be more
+    /// words than the cache holds, only the most frequent are cached. Returns
+    /// the number of words cached.
+    pub fn warm<'h, I, S>(&'h self, words : I) -> usize
+    where H : Hyphenator<'h, Opportunity = usize>
+        , I : IntoIterator<Item = S>
+        , S : AsRef<str>
+    {
+        let capacity = self.lock().capacity;
+        let words : Vec<S> = words.into_iter().take(capacity).collect();
+        // The most frequent words are cached last, so as to be evicted last.
+        for word in words.iter().rev() {
+            let word = word.as_ref();
+            let breaks = self.inner.hyphenate(word).breaks;
+            self.lock().insert(word, breaks);
+        }
+        words.len()
+    }
+
+    /// Warm the cache with the words of a frequency list, most frequent first.
+    /// Each line of the list holds a word, possibly preceded or followed by its
+    /// count and separated from it by spaces or a tab, as in `the 23135851162`
+    /// or `23135851162\tthe`. Blank lines and lines starting with `#` are
+    /// skipped.
+    pub fn warm_from_list<'h, R>(&'h self, reader : R) -> io::Result<usize>
+    where H : Hyphenator<'h, Opportunity = usize>, R : BufRead {
+        let mut words = vec![];
+        for line in reader.lines() {
+            let line = line ?;
+            if line.starts_with('#') {
+                continue
+            }
+            let word = line.split_whitespace()
+                .find(|field| !field.chars().all(|c| c.is_ascii_digit() || c == '.'));
+            if let Some(word) = word {
+                words.push(word.to_owned());
+            }
+        }
+        Ok(self.warm(words))
+    }
+}
+
+impl<'h, H> Hyphenator<'h> for Cached<H> where H : Hyphenator<'h, Opportunity = usize> {
+    type Opportunity = usize;
+    type Exact = H::Exact;
+
+    fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, usize> {
+        if let Some(breaks) = self.lock().get(word) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Word { text : word, breaks }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let hyphenated = self.inner.hyphenate(word);
+        self.lock().insert(word, hyphenated.breaks.clone());
+        hyphenated
+    }
+
+    #[inline]
+    fn opportunities_within(&'h self, word : &str, bounds : (usize, usize)) -> Vec<usize> {
+        self.inner.opportunities_within(word, bounds)
+    }
+
+    #[inline]
+    fn exact_within(&'h self, word : &str, bounds : (usize, usize)) -> Option<Vec<usize>> {
+        self.inner.exact_within(word, bounds)
+    }
+
+    fn add_exact(&mut self, word : String, ops : Vec<Self::Exact>) -> Option<Vec<Self::Exact>> {
+        self.clear();
+        self.inner.add_exact(word, ops)
+    }
+
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<Self::Exact>> {
+        self.clear();
+        self.inner.add_exact_marked(marked)
+    }
+
+    fn remove_exact(&mut self, word : &str) -> Option<Vec<Self::Exact>> {
+        self.clear();
+        self.inner.remove_exact(word)
+    }
+
+    fn clear_exceptions(&mut self) {
+        self.clear();
+        self.inner.clear_exceptions()
+    }
+
+    #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.inner.unbreakable_chars() }
+
+    #[inline] fn max_length(&self) -> usize { self.inner.max_length() }
+
+    #[inline] fn normalization(&self) -> Option<NormalizationForm> { self.inner.normalization() }
+
+    #[inline] fn language(&self) -> Option<Language> { self.inner.language() }
+
+    #[inline] fn case_folding(&self) -> CaseFolding { self.inner.case_folding() }
+}
+
+
+/// A dictionary which memoizes the patterns matched within the stems of the
+/// words it hyphenates, for the words which share them.
+#[derive(Debug)]
+pub struct Stemmed<D> {
+    inner : D,
+    stems : Stems
+}
+
+impl<D> Stemmed<D> {
+    /// Memoize the patterns matched within up to `capacity` stems of the
+    /// words hyphenated by `inner`.
+    pub fn new(inner : D, capacity : usize) -> Self {
+        Stemmed { inner, stems : Stems::new(capacity) }
+    }
+
+    /// The dictionary underneath the memo.
+    pub fn inner(&self) -> &D { &self.inner }
+
+    /// Drop the memo, returning the dictionary underneath.
+    pub fn into_inner(self) -> D { self.inner }
+
+    /// Forget every memoized stem.
+    pub fn clear(&self) { self.stems.clear() }
+}
+
+macro_rules! impl_stemmed {
+    ($dict:ty, $opportunity:ty, $exact:ty, $from_index:expr, $from_value:expr, $realign:expr) => {
+        impl<'h> Hyphenator<'h> for Stemmed<$dict> {
+            type Opportunity = $opportunity;
+            type Exact = $exact;
+
+            fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, Self::Opportunity> {
+                hyphenate_prepared(self, word, $from_index, $realign)
+            }
+
+            fn opportunities_within(&'h self, word : &str, bounds : (usize, usize))
+                -> Vec<Self::Opportunity>
+            {
+                let scores = self.inner.score_with(word, &self.stems);
+                hyphenator::scored(word, scores, bounds, <$dict>::denotes_opportunity, $from_value).collect()
+            }
+
+            #[inline]
+            fn exact_within(&'h self, word : &str, bounds : (usize, usize))
+                -> Option<Vec<Self::Opportunity>>
+            {
+                self.inner.exact_within(word, bounds)
+            }
+
+            #[inline]
+            fn add_exact(&mut self, word : String, ops : Vec<Self::Exact>) -> Option<Vec<Self::Exact>> {
+                self.inner.add_exact(word, ops)
+            }
+
+            #[inline]
+            fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<Self::Exact>> {
+                self.inner.add_exact_marked(marked)
+            }
+
+            #[inline]
+            fn remove_exact(&mut self, word : &str) -> Option<Vec<Self::Exact>> {
+                self.inner.remove_exact(word)
+            }
+
+            #[inline] fn clear_exceptions(&mut self) { self.inner.clear_exceptions() }
+
+            #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.inner.unbreakable_chars() }
+
+            #[inline] fn max_length(&self) -> usize { self.inner.max_length() }
+
+            #[inline] fn normalization(&self) -> Option<NormalizationForm> { self.inner.normalization() }
+
+            #[inline] fn language(&self) -> Option<Language> { self.inner.language() }
+
+            #[inline] fn case_folding(&self) -> CaseFolding { self.inner.case_folding() }
+        }
+    }
+}
+
+impl_stemmed! {
+    Standard, usize, usize,
+    |i| i, |i, _| i,
+    |i, prepared : &Prepared| prepared.realign(i)
+}
+impl_stemmed! {
+    Extended, (usize, Option<&'h Subregion>), (usize, Option<Subregion>),
+    |i| (i, None), |i, (_, subr)| (i, subr),
+    |(i, subr), prepared : &Prepared| prepared.realign_respelled(i, subr).map(|i| (i, subr))
+}
+
+
+/// A map of words to values, such as their breaks, which evicts the least
+/// recently used word once it holds `capacity` words.
+#[derive(Debug)]
+pub(crate) struct Lru<V> {
+    capacity : usize,
+    /// Incremented upon every use of the cache, to order its words by recency.
+    clock : u64,
+    entries : HashMap<String, (V, u64)>,
+    recency : BTreeMap<u64, String>
+}
+
+impl<V : Clone> Lru<V> {
+    pub(crate) fn new(capacity : usize) -> Self {
+        Lru { capacity, clock : 0, entries : HashMap::new(), recency : BTreeMap::new() }
+    }
+
+    pub(crate) fn get(&mut self, word : &str) -> Option<V> {
+        let &mut (ref value, ref mut used) = self.entries.get_mut(word) ?;
+        self.clock += 1;
+        let key = self.recency.remove(used).expect("cached words are ordered by recency");
+        *used = self.clock;
+        self.recency.insert(self.clock, key);
+        Some(value.clone())
+    }
+
+    pub(crate) fn insert(&mut self, word : &str, value : V) {
+        if self.capacity == 0 {
+            return
+        }
+        self.clock += 1;
+        if let Some((_, used)) = self.entries.insert(word.to_owned(), (value, self.clock)) {
+            self.recency.remove(&used);
+        }
+        self.recency.insert(self.clock, word.to_owned());
+        while self.entries.len() > self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
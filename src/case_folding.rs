@@ -13,11 +13,11 @@ Although the approach works quite well when matching against `str`, two
 discrepancies remain:
 
 - Firstly, folding may shift character boundaries, invalidating the indices
-of opportunities found by our dictionaries, which are byte-based and have
-no notion of `char`. Thus, any opportunity found in a folded word must be
-mapped back to its correct position in the original, unfolded word.
+  of opportunities found by our dictionaries, which are byte-based and have
+  no notion of `char`. Thus, any opportunity found in a folded word must be
+  mapped back to its correct position in the original, unfolded word.
 - Secondly, patterns may not account for all concrete sequences that occur
-when using `str::to_lowercase` as a loose folding pass.
+  when using `str::to_lowercase` as a loose folding pass.
 
 A zealous solution would be to adopt proper caseless matching, and pre-fold
 the bundled patterns. Presently, however, we rely on an ad-hoc remedy based
@@ -36,28 +36,106 @@ or tailoring. It is thus necessary to fold it regardless of language or
 context, because the equivalence-preserving lowercase "i\u{307}" not only
 disrupts pattern matching – be it byte-based or char-based – but also
 shifts and invalidates any opportunity arising after it.
+
+
+# Turkish
+
+Turkish pairs dotted `İ` with `i`, and dotless `I` with `ı`, such that
+lowercasing its words as other languages do turns `KIRMIZI` into `kirmizi`,
+which its patterns do not spell. Words are thus folded by the tailored
+mappings of Unicode's `SpecialCasing.txt` for dictionaries of Turkish, which
+turn the one-byte `I` into the two-byte `ı`, and shift the breaks after it.
+
+
+# Full case folding
+
+Dictionaries may instead be told to fold words by full Unicode case folding,
+see `CaseFolding`, which maps some chars to several, such as `ß` to `ss`, and
+shifts the breaks after them. Breaks between the chars folded from a single
+one, as between the two `s` of `ß`, have no counterpart in the original and
+are dropped.
+
+
+# Typography
+
+Text extracted from PDFs and other typeset documents is often spelled with
+the presentation ligatures of Unicode's Alphabetic Presentation Forms, such as
+`ﬁ` and `ﬄ`, which no pattern spells. Words are folded with each ligature
+turned into its component letters, which shifts the breaks after it; breaks
+between the letters of a ligature, as between the two `f` of `ﬀ`, have no
+counterpart in the original and are dropped, as are those of full case
+folding.
+
+Likewise, typographically correct text elides with the right single quotation
+mark `’` where patterns spell the ASCII apostrophe, as in `l’homme`; it is
+folded into `'`, and the breaks after it shifted back by two bytes.
+
+Persian and Indic words hold the zero-width non-joiner U+200C and joiner
+U+200D, which govern the shaping of the letters about them but which
+patterns do not spell. They are dropped from the folded word; the breaks
+where they stood fall after them in the original, as they extend the
+grapheme cluster before them, rather than start the one after.
+
+
+# Latin-1
+
+Words spelled in ASCII and Latin-1 alone, which make up the bulk of Western
+texts, hold no such codepoint. Their uppercase letters all lie 32 code
+points before their lowercase, and thus 32 before them in UTF-8 as well,
+such that they are folded bytewise. With the `simd` feature, x86-64 targets
+fold them sixteen bytes at a time with SSE2 instructions.
+
+
+# Normalization
+
+Dictionaries whose patterns were normalized, see `Standard::normalization`,
+normalize the words they hyphenate after folding them. Normalization may
+compose or decompose characters, and thus shift their boundaries as well.
+Words are normalized by segments, each a starter followed by its combining
+marks, whose bounds are known in both the folded and the normalized word;
+opportunities found within a segment, as between the letters of a ligature
+decomposed by NFKC, have no counterpart in the original and are dropped.
 */
 
 use std::borrow::Cow;
 use std::borrow::Cow::*;
 
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::normalization::NormalizationForm;
+
 
-#[derive(Copy, Clone, Debug)]
+/// The difference in length between a string and its altered spelling, up to
+/// some index of the altered string. The shifts of a string are ordered by
+/// index, and each holds the total difference up to its index.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Shift {
     index : usize,
     delta : isize
 }
 
+impl Shift {
+    /// A shift of `delta` bytes, measured from the original string to the
+    /// altered one, for indices past `index` in the altered string.
+    pub fn new(index : usize, delta : isize) -> Self { Shift { index, delta } }
+}
+
+/// A folded string, along with the index shifts induced by folding it, and
+/// the bounds within it of every char folded from a single one.
+pub type Folded<'a> = (Cow<'a, str>, Vec<Shift>, Vec<(usize, usize)>);
+
 /// The opportunity `i`, index-corrected for use in the original string.
 pub fn realign(i : usize, shifts : &[Shift]) -> usize {
     (i as isize - shift_at(i, shifts)) as usize
 }
 
-/// The shift at index `i` in the refolded string.
+/// The shift at index `i` in the refolded string. Shifts are ordered by
+/// index, such that realigning every opportunity of a word takes loglinear
+/// time even should the word be shifted throughout.
 fn shift_at(i : usize, shifts : &[Shift]) -> isize {
-    shifts.iter().rev()
-        .find(|&&shift| i > shift.index)
-        .map_or(0, |&shift| shift.delta)
+    match shifts.partition_point(|shift| i > shift.index) {
+        0 => 0,
+        n => shifts[n - 1].delta
+    }
 }
 
 fn shifts(word : &str) -> Vec<Shift> {
@@ -71,10 +149,125 @@ fn shifts(word : &str) -> Vec<Shift> {
 }
 
 
+/// Refold the original string as `refold` does, by the case mappings of the
+/// given language, tailored for Turkish.
+pub fn refold_in(original : &str, language : Option<Language>) -> (Cow<'_, str>, Vec<Shift>) {
+    match language {
+        Some(Language::Turkish) if original.contains(['I', 'İ']) => {
+            let (folded, shifts, _) = fold_chars(original, |c, folded| match c {
+                'I' => folded.push('ı'),
+                'İ' => folded.push('i'),
+                c => folded.extend(c.to_lowercase())
+            });
+            (Owned(folded), shifts)
+        },
+        _ => refold(original)
+    }
+}
+
+/// Should the original string hold presentation ligatures, typographic
+/// apostrophes or zero-width joiners, refold it as `refold_in` does, with
+/// every ligature turned into its component letters, every apostrophe into
+/// `'`, and every joiner dropped, and provide the induced index shifts, along
+/// with the bounds within the folded string of the letters of every ligature.
+pub fn refold_typography_in(original : &str, language : Option<Language>) -> Option<Folded<'_>> {
+    if original.is_ascii() || !original.contains(|c| typographic(c).is_some()) {
+        return None
+    }
+    let turkish = language == Some(Language::Turkish);
+    let (folded, shifts, expansions) = fold_chars(original, |c, folded| match c {
+        'I' if turkish => folded.push('ı'),
+        'İ' => folded.push('i'),
+        c => match typographic(c) {
+            Some(plain) => folded.push_str(plain),
+            None => folded.extend(c.to_lowercase())
+        }
+    });
+    Some((Owned(folded), shifts, expansions))
+}
+
+/// The plain spelling of a presentation ligature, as its component letters,
+/// of a typographic apostrophe, or of a joiner, as nothing.
+fn typographic(c : char) -> Option<&'static str> {
+    match c {
+        '\u{2019}' => Some("'"),
+        '\u{200C}' | '\u{200D}' => Some(""),
+        '\u{FB00}' => Some("ff"),
+        '\u{FB01}' => Some("fi"),
+        '\u{FB02}' => Some("fl"),
+        '\u{FB03}' => Some("ffi"),
+        '\u{FB04}' => Some("ffl"),
+        '\u{FB05}' | '\u{FB06}' => Some("st"),
+        _ => None
+    }
+}
+
+/// Fold the original string by full Unicode case folding, tailored for the
+/// given language, and provide the induced index shifts, along with the bounds
+/// within the folded string of every char folded to several.
+pub fn fold_fully(original : &str, language : Option<Language>) -> Folded<'_> {
+    if original.is_ascii() {
+        return (fold_latin1(original).unwrap_or(Borrowed(original)), vec![], vec![])
+    }
+    let turkish = language == Some(Language::Turkish);
+    let (folded, shifts, expansions) = fold_chars(original, |c, folded| match c {
+        'I' if turkish => folded.push('ı'),
+        'İ' if turkish => folded.push('i'),
+        '\u{2019}' => folded.push('\''),
+        '\u{200C}' | '\u{200D}' => (),
+        // Cherokee folds to its uppercase letters, which came first.
+        '\u{13A0}' ..= '\u{13F5}' => folded.push(c),
+        '\u{13F8}' ..= '\u{13FD}' => folded.extend(char::from_u32(c as u32 - 0x8)),
+        '\u{AB70}' ..= '\u{ABBF}' => folded.extend(char::from_u32(c as u32 - 0x97D0)),
+        c => match FULL_FOLDING.binary_search_by_key(&c, |&(from, _)| from) {
+            Ok(k) => folded.push_str(FULL_FOLDING[k].1),
+            Err(_) => folded.extend(c.to_lowercase())
+        }
+    });
+    (Owned(folded), shifts, expansions)
+}
+
+/// Fold the original string one char at a time, and provide the shifts
+/// induced by those chars whose folding differs in length, along with the
+/// bounds of those folded to several chars.
+pub(crate) fn fold_chars<F>(original : &str, fold : F) -> (String, Vec<Shift>, Vec<(usize, usize)>)
+where F : Fn(char, &mut String) {
+    let mut folded = String::with_capacity(original.len());
+    let (mut shifts, mut expansions) : (Vec<Shift>, _) = (vec![], vec![]);
+    for c in original.chars() {
+        let start = folded.len();
+        fold(c, &mut folded);
+        let delta = (folded.len() - start) as isize - c.len_utf8() as isize;
+        if delta != 0 {
+            let total = shifts.last().map_or(0, |shift| shift.delta) + delta;
+            // Breaks where a char was dropped fall after it.
+            let index = if folded.len() == start { start.saturating_sub(1) } else { start };
+            shifts.push(Shift { index, delta : total });
+        }
+        if folded[start ..].chars().nth(1).is_some() {
+            expansions.push((start, folded.len()));
+        }
+    }
+    (folded, shifts, expansions)
+}
+
+/// Whether the break `i` of a folded string falls within a char folded from
+/// a single one, given the bounds of every such char, in order.
+pub fn within_expansion(i : usize, expansions : &[(usize, usize)]) -> bool {
+    match expansions.partition_point(|&(start, _)| start < i) {
+        0 => false,
+        n => i < expansions[n - 1].1
+    }
+}
+
 /// Should the original string contain special-cased codepoints, refold it
 /// for hyphenation and provide the induced index shifts. Otherwise, merely
 /// ensure that it is lowercase.
-pub fn refold(original : &str) -> (Cow<str>, Vec<Shift>) {
+pub fn refold(original : &str) -> (Cow<'_, str>, Vec<Shift>) {
+    // Latin-1 words are neither special-cased nor shifted.
+    if let Some(folded) = fold_latin1(original) {
+        return (folded, vec![])
+    }
     if original.chars().any(|c| c.is_uppercase()) {
         let lowercase = original.to_lowercase();
         // There is only one code point which changes size when lowercased,
@@ -86,8 +279,212 @@ pub fn refold(original : &str) -> (Cow<str>, Vec<Shift>) {
     } else { (Borrowed(original), vec![]) }
 }
 
+/// Should the folded string not be in the given form already, normalize it,
+/// and provide the alignment of its segments onto the folded string.
+pub fn normalize(folded : &str, form : Option<NormalizationForm>) -> Option<(String, Vec<(usize, usize)>)> {
+    match form {
+        Some(form) if !form.is_normalized(folded) => Some(form.apply_aligned(folded)),
+        _ => None
+    }
+}
+
+/// The opportunity `i` of a normalized string, index-corrected for use in the
+/// string before normalization, or `None` should it fall within a segment.
+pub fn denormalize(i : usize, alignment : &[(usize, usize)]) -> Option<usize> {
+    alignment.binary_search_by_key(&i, |&(normalized, _)| normalized).ok().map(|k| alignment[k].1)
+}
+
+/// The lowercase of a word spelled in ASCII and Latin-1 alone, folded
+/// bytewise; `None` for any other word.
+fn fold_latin1(original : &str) -> Option<Cow<'_, str>> {
+    let mut bytes = match latin1::scan(original.as_bytes()) {
+        None => return None,
+        Some(false) => return Some(Borrowed(original)),
+        Some(true) => original.as_bytes().to_vec()
+    };
+    latin1::lowercase(&mut bytes);
+    // SAFETY: folding turns ASCII letters into ASCII letters, and the
+    // trailing bytes of Latin-1 letters into trailing bytes, preserving UTF-8.
+    Some(Owned(unsafe { String::from_utf8_unchecked(bytes) }))
+}
+
+/// Folding of UTF-8 text spelled in ASCII and Latin-1, one byte at a time.
+///
+/// Valid UTF-8 holds no byte above `0xC3` exactly when it is spelled in
+/// ASCII and Latin-1, all of whose two-byte sequences begin with `0xC2` or
+/// `0xC3`. Uppercase letters are `A` to `Z`, and the sequences of `0xC3`
+/// followed by `0x80` to `0x9E` but `0x97`, the multiplication sign; setting
+/// their bit `0x20` folds them, and leaves every `0xC3` as it was.
+mod bytewise {
+    #[inline]
+    fn is_upper(previous : u8, byte : u8) -> bool {
+        byte.is_ascii_uppercase() || previous == 0xC3 && (0x80 ..= 0x9E).contains(&byte) && byte != 0x97
+    }
+
+    /// Whether the text, preceded by the byte `previous`, is spelled in ASCII
+    /// and Latin-1 alone, and if so, whether it holds uppercase letters.
+    pub fn scan(mut previous : u8, bytes : &[u8]) -> Option<bool> {
+        let mut uppercase = false;
+        for &byte in bytes {
+            if byte > 0xC3 {
+                return None
+            }
+            uppercase |= is_upper(previous, byte);
+            previous = byte;
+        }
+        Some(uppercase)
+    }
+
+    /// Lowercase text spelled in ASCII and Latin-1, preceded by the byte
+    /// `previous`.
+    pub fn lowercase(mut previous : u8, bytes : &mut [u8]) {
+        for byte in bytes.iter_mut() {
+            let original = *byte;
+            if is_upper(previous, original) {
+                *byte |= 0x20;
+            }
+            previous = original;
+        }
+    }
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+mod latin1 {
+    use super::bytewise;
+
+    #[inline] pub fn scan(bytes : &[u8]) -> Option<bool> { bytewise::scan(0, bytes) }
+
+    #[inline] pub fn lowercase(bytes : &mut [u8]) { bytewise::lowercase(0, bytes) }
+}
+
+/// Folding of UTF-8 text spelled in ASCII and Latin-1 sixteen bytes at a
+/// time, the remainder being folded bytewise. SSE2 is available on every
+/// x86-64 processor.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod latin1 {
+    use std::arch::x86_64::*;
+    use super::bytewise;
+
+    const LANES : usize = 16;
+
+    /// The sixteen bytes from `i` onwards, and the sixteen bytes before them.
+    #[inline]
+    unsafe fn load(bytes : &[u8], i : usize) -> (__m128i, __m128i) {
+        let chunk = _mm_loadu_si128(bytes.as_ptr().add(i) as *const __m128i);
+        let previous = if i == 0 { _mm_slli_si128(chunk, 1) } else {
+            _mm_loadu_si128(bytes.as_ptr().add(i - 1) as *const __m128i)
+        };
+        (previous, chunk)
+    }
+
+    /// The lanes of `chunk` which hold uppercase letters, given the bytes
+    /// which precede them.
+    #[inline]
+    unsafe fn upper(previous : __m128i, chunk : __m128i) -> __m128i {
+        // As signed bytes, those from `0x80` onwards are negative.
+        let ascii = _mm_and_si128(_mm_cmpgt_epi8(chunk, _mm_set1_epi8(0x40)),
+                                  _mm_cmplt_epi8(chunk, _mm_set1_epi8(0x5B)));
+        let trailing = _mm_andnot_si128(_mm_cmpeq_epi8(chunk, _mm_set1_epi8(0x97u8 as i8)),
+                                        _mm_cmplt_epi8(chunk, _mm_set1_epi8(0x9Fu8 as i8)));
+        let after_lead = _mm_cmpeq_epi8(previous, _mm_set1_epi8(0xC3u8 as i8));
+        _mm_or_si128(ascii, _mm_and_si128(after_lead, trailing))
+    }
+
+    pub fn scan(bytes : &[u8]) -> Option<bool> {
+        let chunks = bytes.len() / LANES * LANES;
+        // SAFETY: SSE2 is part of x86-64, and every load lies within `bytes`.
+        let uppercase = unsafe {
+            let (mut beyond, mut uppercase) = (_mm_setzero_si128(), _mm_setzero_si128());
+            for i in (0 .. chunks).step_by(LANES) {
+                let (previous, chunk) = load(bytes, i);
+                let max = _mm_max_epu8(chunk, _mm_set1_epi8(0xC4u8 as i8));
+                beyond = _mm_or_si128(beyond, _mm_cmpeq_epi8(max, chunk));
+                uppercase = _mm_or_si128(uppercase, upper(previous, chunk));
+            }
+            if _mm_movemask_epi8(beyond) != 0 {
+                return None
+            }
+            _mm_movemask_epi8(uppercase) != 0
+        };
+        let previous = if chunks > 0 { bytes[chunks - 1] } else { 0 };
+        bytewise::scan(previous, &bytes[chunks ..]).map(|rest| uppercase || rest)
+    }
+
+    pub fn lowercase(bytes : &mut [u8]) {
+        let chunks = bytes.len() / LANES * LANES;
+        // SAFETY: as above. The byte preceding each chunk may already be
+        // lowercase, which folding never turns into `0xC3` nor out of it.
+        unsafe {
+            for i in (0 .. chunks).step_by(LANES) {
+                let (previous, chunk) = load(bytes, i);
+                let folded = _mm_or_si128(chunk, _mm_and_si128(upper(previous, chunk), _mm_set1_epi8(0x20)));
+                _mm_storeu_si128(bytes.as_mut_ptr().add(i) as *mut __m128i, folded);
+            }
+        }
+        let previous = if chunks > 0 { bytes[chunks - 1] } else { 0 };
+        bytewise::lowercase(previous, &mut bytes[chunks ..]);
+    }
+}
+
 /// Substitute lowercase sequences that would interfere with hyphenation.
 /// Canonical equivalence is not necessarily preserved.
 fn refold_lowercase(lowercase : &str) -> String {
     lowercase.replace("i\u{307}", "i")
 }
+
+/// The full case foldings of `CaseFolding.txt`, of statuses C and F, which
+/// differ from the lowercase of their chars, but for Cherokee; sorted by char.
+const FULL_FOLDING : &[(char, &str)] = &[
+    ('\u{B5}', "\u{3BC}"), ('\u{DF}', "ss"), ('\u{149}', "\u{2BC}n"), ('\u{17F}', "s"),
+    ('\u{1F0}', "j\u{30C}"), ('\u{345}', "\u{3B9}"), ('\u{390}', "\u{3B9}\u{308}\u{301}"),
+    ('\u{3B0}', "\u{3C5}\u{308}\u{301}"), ('\u{3C2}', "\u{3C3}"), ('\u{3D0}', "\u{3B2}"),
+    ('\u{3D1}', "\u{3B8}"), ('\u{3D5}', "\u{3C6}"), ('\u{3D6}', "\u{3C0}"), ('\u{3F0}', "\u{3BA}"),
+    ('\u{3F1}', "\u{3C1}"), ('\u{3F5}', "\u{3B5}"), ('\u{587}', "\u{565}\u{582}"),
+    ('\u{1C80}', "\u{432}"), ('\u{1C81}', "\u{434}"), ('\u{1C82}', "\u{43E}"),
+    ('\u{1C83}', "\u{441}"), ('\u{1C84}', "\u{442}"), ('\u{1C85}', "\u{442}"),
+    ('\u{1C86}', "\u{44A}"), ('\u{1C87}', "\u{463}"), ('\u{1C88}', "\u{A64B}"),
+    ('\u{1E96}', "h\u{331}"), ('\u{1E97}', "t\u{308}"), ('\u{1E98}', "w\u{30A}"),
+    ('\u{1E99}', "y\u{30A}"), ('\u{1E9A}', "a\u{2BE}"), ('\u{1E9B}', "\u{1E61}"),
+    ('\u{1E9E}', "ss"), ('\u{1F50}', "\u{3C5}\u{313}"), ('\u{1F52}', "\u{3C5}\u{313}\u{300}"),
+    ('\u{1F54}', "\u{3C5}\u{313}\u{301}"), ('\u{1F56}', "\u{3C5}\u{313}\u{342}"),
+    ('\u{1F80}', "\u{1F00}\u{3B9}"), ('\u{1F81}', "\u{1F01}\u{3B9}"),
+    ('\u{1F82}', "\u{1F02}\u{3B9}"), ('\u{1F83}', "\u{1F03}\u{3B9}"),
+    ('\u{1F84}', "\u{1F04}\u{3B9}"), ('\u{1F85}', "\u{1F05}\u{3B9}"),
+    ('\u{1F86}', "\u{1F06}\u{3B9}"), ('\u{1F87}', "\u{1F07}\u{3B9}"),
+    ('\u{1F88}', "\u{1F00}\u{3B9}"), ('\u{1F89}', "\u{1F01}\u{3B9}"),
+    ('\u{1F8A}', "\u{1F02}\u{3B9}"), ('\u{1F8B}', "\u{1F03}\u{3B9}"),
+    ('\u{1F8C}', "\u{1F04}\u{3B9}"), ('\u{1F8D}', "\u{1F05}\u{3B9}"),
+    ('\u{1F8E}', "\u{1F06}\u{3B9}"), ('\u{1F8F}', "\u{1F07}\u{3B9}"),
+    ('\u{1F90}', "\u{1F20}\u{3B9}"), ('\u{1F91}', "\u{1F21}\u{3B9}"),
+    ('\u{1F92}', "\u{1F22}\u{3B9}"), ('\u{1F93}', "\u{1F23}\u{3B9}"),
+    ('\u{1F94}', "\u{1F24}\u{3B9}"), ('\u{1F95}', "\u{1F25}\u{3B9}"),
+    ('\u{1F96}', "\u{1F26}\u{3B9}"), ('\u{1F97}', "\u{1F27}\u{3B9}"),
+    ('\u{1F98}', "\u{1F20}\u{3B9}"), ('\u{1F99}', "\u{1F21}\u{3B9}"),
+    ('\u{1F9A}', "\u{1F22}\u{3B9}"), ('\u{1F9B}', "\u{1F23}\u{3B9}"),
+    ('\u{1F9C}', "\u{1F24}\u{3B9}"), ('\u{1F9D}', "\u{1F25}\u{3B9}"),
+    ('\u{1F9E}', "\u{1F26}\u{3B9}"), ('\u{1F9F}', "\u{1F27}\u{3B9}"),
+    ('\u{1FA0}', "\u{1F60}\u{3B9}"), ('\u{1FA1}', "\u{1F61}\u{3B9}"),
+    ('\u{1FA2}', "\u{1F62}\u{3B9}"), ('\u{1FA3}', "\u{1F63}\u{3B9}"),
+    ('\u{1FA4}', "\u{1F64}\u{3B9}"), ('\u{1FA5}', "\u{1F65}\u{3B9}"),
+    ('\u{1FA6}', "\u{1F66}\u{3B9}"), ('\u{1FA7}', "\u{1F67}\u{3B9}"),
+    ('\u{1FA8}', "\u{1F60}\u{3B9}"), ('\u{1FA9}', "\u{1F61}\u{3B9}"),
+    ('\u{1FAA}', "\u{1F62}\u{3B9}"), ('\u{1FAB}', "\u{1F63}\u{3B9}"),
+    ('\u{1FAC}', "\u{1F64}\u{3B9}"), ('\u{1FAD}', "\u{1F65}\u{3B9}"),
+    ('\u{1FAE}', "\u{1F66}\u{3B9}"), ('\u{1FAF}', "\u{1F67}\u{3B9}"),
+    ('\u{1FB2}', "\u{1F70}\u{3B9}"), ('\u{1FB3}', "\u{3B1}\u{3B9}"), ('\u{1FB4}', "\u{3AC}\u{3B9}"),
+    ('\u{1FB6}', "\u{3B1}\u{342}"), ('\u{1FB7}', "\u{3B1}\u{342}\u{3B9}"),
+    ('\u{1FBC}', "\u{3B1}\u{3B9}"), ('\u{1FBE}', "\u{3B9}"), ('\u{1FC2}', "\u{1F74}\u{3B9}"),
+    ('\u{1FC3}', "\u{3B7}\u{3B9}"), ('\u{1FC4}', "\u{3AE}\u{3B9}"), ('\u{1FC6}', "\u{3B7}\u{342}"),
+    ('\u{1FC7}', "\u{3B7}\u{342}\u{3B9}"), ('\u{1FCC}', "\u{3B7}\u{3B9}"),
+    ('\u{1FD2}', "\u{3B9}\u{308}\u{300}"), ('\u{1FD3}', "\u{3B9}\u{308}\u{301}"),
+    ('\u{1FD6}', "\u{3B9}\u{342}"), ('\u{1FD7}', "\u{3B9}\u{308}\u{342}"),
+    ('\u{1FE2}', "\u{3C5}\u{308}\u{300}"), ('\u{1FE3}', "\u{3C5}\u{308}\u{301}"),
+    ('\u{1FE4}', "\u{3C1}\u{313}"), ('\u{1FE6}', "\u{3C5}\u{342}"),
+    ('\u{1FE7}', "\u{3C5}\u{308}\u{342}"), ('\u{1FF2}', "\u{1F7C}\u{3B9}"),
+    ('\u{1FF3}', "\u{3C9}\u{3B9}"), ('\u{1FF4}', "\u{3CE}\u{3B9}"), ('\u{1FF6}', "\u{3C9}\u{342}"),
+    ('\u{1FF7}', "\u{3C9}\u{342}\u{3B9}"), ('\u{1FFC}', "\u{3C9}\u{3B9}"), ('\u{FB00}', "ff"),
+    ('\u{FB01}', "fi"), ('\u{FB02}', "fl"), ('\u{FB03}', "ffi"), ('\u{FB04}', "ffl"),
+    ('\u{FB05}', "st"), ('\u{FB06}', "st"), ('\u{FB13}', "\u{574}\u{576}"),
+    ('\u{FB14}', "\u{574}\u{565}"), ('\u{FB15}', "\u{574}\u{56B}"), ('\u{FB16}', "\u{57E}\u{576}"),
+    ('\u{FB17}', "\u{574}\u{56D}"),
+];
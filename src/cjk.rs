@@ -0,0 +1,110 @@
+/*! Breaking Chinese and Japanese text
+
+Chinese and Japanese are not hyphenated; rather, lines may generally be
+broken between any two characters. The [`CjkBreaker`] finds such
+opportunities, with the exception of those forbidden by the basic rules of
+*kinsoku shori*: closing brackets, most punctuation, small kana and
+iteration marks may not begin a line, and opening brackets may not end one.
+
+The breaker implements [`Hyphenator`] so that it may be used alongside a
+dictionary, such as the pinyin `Chinese` dictionary. In mixed text, the
+breaks found by each can simply be merged:
+
+```ignore
+use kl_hyphenate::cjk::CjkBreaker;
+use kl_hyphenate::text::Text;
+
+let text = "漢字の「かな」と hyphenation";
+let mut breaks = en_us.hyphenate_text(text).breaks;
+breaks.extend(CjkBreaker.hyphenate_text(text).breaks);
+breaks.sort();
+```
+
+Since no hyphen is set at the end of a line broken between characters,
+breaks should be marked with an empty string when iterating.
+
+[`CjkBreaker`]: struct.CjkBreaker.html
+[`Hyphenator`]: ../hyphenator/trait.Hyphenator.html
+*/
+
+use hyphenator::{Hyphenator, Word};
+
+
+/// Characters which may not begin a line.
+const NO_START : &str = "\
+    、。，．・：；？！ー）」』】〕〉》〗〙〟’”｝］｠〜…‥\
+    ぁぃぅぇぉっゃゅょゎゕゖァィゥェォッャュョヮヵヶㇰㇱㇲㇳㇴㇵㇶㇷㇸㇹㇺㇻㇼㇽㇾㇿ\
+    々〻ゝゞヽヾ";
+
+/// Characters which may not end a line.
+const NO_END : &str = "（「『【〔〈《〖〘〝‘“｛［｟";
+
+/// Whether the character belongs to the Chinese or Japanese writing systems,
+/// including their punctuation and fullwidth forms.
+pub fn is_cjk(c : char) -> bool {
+    match c {
+        '\u{3000}' ..= '\u{303F}'       // CJK Symbols and Punctuation
+      | '\u{3040}' ..= '\u{309F}'       // Hiragana
+      | '\u{30A0}' ..= '\u{30FF}'       // Katakana
+      | '\u{3100}' ..= '\u{312F}'       // Bopomofo
+      | '\u{31F0}' ..= '\u{31FF}'       // Katakana Phonetic Extensions
+      | '\u{3400}' ..= '\u{4DBF}'       // CJK Unified Ideographs Extension A
+      | '\u{4E00}' ..= '\u{9FFF}'       // CJK Unified Ideographs
+      | '\u{F900}' ..= '\u{FAFF}'       // CJK Compatibility Ideographs
+      | '\u{FF00}' ..= '\u{FFEF}'       // Halfwidth and Fullwidth Forms
+      | '\u{20000}' ..= '\u{2FFFF}'     // Supplementary Ideographic Plane
+          => true,
+        _ => false
+    }
+}
+
+/// A non-dictionary hyphenator for Chinese and Japanese, which yields
+/// opportunities between characters, as permitted by basic line breaking
+/// rules.
+///
+/// No breaks are found between characters outside of these writing systems,
+/// nor between such a character and a Chinese or Japanese neighbor.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CjkBreaker;
+
+impl CjkBreaker {
+    /// Whether a line may be broken between these two characters.
+    fn permits(fore : char, aft : char) -> bool {
+        is_cjk(fore) && is_cjk(aft) && !NO_END.contains(fore) && !NO_START.contains(aft)
+    }
+}
+
+impl<'h> Hyphenator<'h> for CjkBreaker {
+    type Opportunity = usize;
+    type Exact = usize;
+
+    /// Find the breaks within a run of text.
+    fn hyphenate<'t>(&'h self, text : &'t str) -> Word<'t, usize> {
+        Word { text, breaks : self.opportunities(text) }
+    }
+
+    fn opportunities_within(&'h self, text : &str, (l, r) : (usize, usize)) -> Vec<usize> {
+        let chars : Vec<_> = text.char_indices().collect();
+        chars.windows(2)
+            .filter(|pair| Self::permits(pair[0].1, pair[1].1))
+            .map(|pair| pair[1].0)
+            .filter(|&i| i >= l && i <= r)
+            .collect()
+    }
+
+    /// The breaker knows no exact hyphenations.
+    #[inline]
+    fn exact_within(&'h self, _ : &str, _ : (usize, usize)) -> Option<Vec<usize>> { None }
+
+    /// The breaker knows no exact hyphenations, and ignores those provided.
+    #[inline]
+    fn add_exact(&mut self, _ : String, _ : Vec<usize>) -> Option<Vec<usize>> { None }
+
+    fn add_exact_marked(&mut self, _ : &str) -> Option<Vec<usize>> { None }
+
+    fn remove_exact(&mut self, _ : &str) -> Option<Vec<usize>> { None }
+
+    fn clear_exceptions(&mut self) {}
+
+    #[inline] fn unbreakable_chars(&self) -> (usize, usize) { (1, 1) }
+}
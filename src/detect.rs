@@ -0,0 +1,116 @@
+/*! Language detection
+
+With the `detection` feature enabled, the language of a text can be
+identified by the [`whatlang`] crate, so that the text may be hyphenated
+without prior knowledge of its language.
+
+```ignore
+use kl_hyphenate::detect::Detection;
+use kl_hyphenate::registry::{Registry, Source};
+use kl_hyphenate::text::Multilingual;
+
+let registry = Registry::new().source(Source::Dir("dictionaries".into()));
+let mut hyphenator = Multilingual::new(&registry);
+hyphenator.detect_with(Detection { threshold : 0.8, fallback : Some(EnglishUS) });
+let hyphenated = hyphenator.hyphenate_text_detecting("Une phrase en français");
+```
+
+Detection is only as reliable as the text is long; short texts are best
+hyphenated in a known language.
+
+[`whatlang`]: https://crates.io/crates/whatlang
+*/
+
+use whatlang::{self, Lang, Script};
+
+use kl_hyphenate_commons::Language;
+
+
+/// The parameters of language detection.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Detection {
+    /// The minimum confidence, between 0 and 1, with which a language must
+    /// be detected to be retained.
+    pub threshold : f64,
+    /// The language to assume should detection fail, fall short of the
+    /// threshold, or find a language for which no dictionary exists.
+    pub fallback : Option<Language>
+}
+
+impl Default for Detection {
+    fn default() -> Self { Detection { threshold : 0.5, fallback : None } }
+}
+
+impl Detection {
+    /// The language in which the text is written, as far as can be told.
+    pub fn detect(&self, text : &str) -> Option<Language> {
+        whatlang::detect(text)
+            .filter(|info| info.confidence() >= self.threshold)
+            .and_then(|info| language(info.lang(), info.script()))
+            .or(self.fallback)
+    }
+}
+
+/// The hyphenation language corresponding to a language detected by
+/// `whatlang`, if any.
+///
+/// For languages with several hyphenation variants, the most current one is
+/// chosen: `EnglishUS`, `German1996`, `GreekMono`.
+pub fn language(lang : Lang, script : Script) -> Option<Language> {
+    use kl_hyphenate_commons::Language::*;
+
+    let found = match lang {
+        Lang::Afr => Afrikaans,
+        Lang::Amh => Ethiopic,
+        Lang::Bel => Belarusian,
+        Lang::Ben => Bengali,
+        Lang::Bul => Bulgarian,
+        Lang::Cat => Catalan,
+        Lang::Ces => Czech,
+        Lang::Dan => Danish,
+        Lang::Deu => German1996,
+        Lang::Ell => GreekMono,
+        Lang::Eng => EnglishUS,
+        Lang::Epo => Esperanto,
+        Lang::Est => Estonian,
+        Lang::Fin => Finnish,
+        Lang::Fra => French,
+        Lang::Guj => Gujarati,
+        Lang::Hin => Hindi,
+        Lang::Hrv => Croatian,
+        Lang::Hun => Hungarian,
+        Lang::Hye => Armenian,
+        Lang::Ind => Indonesian,
+        Lang::Ita => Italian,
+        Lang::Kan => Kannada,
+        Lang::Kat => Georgian,
+        Lang::Lat => Latin,
+        Lang::Lav => Latvian,
+        Lang::Lit => Lithuanian,
+        Lang::Mal => Malayalam,
+        Lang::Mar => Marathi,
+        Lang::Mkd => Macedonian,
+        Lang::Nld => Dutch,
+        Lang::Nob => NorwegianBokmal,
+        Lang::Ori => Oriya,
+        Lang::Pan => Panjabi,
+        Lang::Pol => Polish,
+        Lang::Por => Portuguese,
+        Lang::Ron => Romanian,
+        Lang::Rus => Russian,
+        Lang::Slk => Slovak,
+        Lang::Slv => Slovenian,
+        Lang::Spa => Spanish,
+        Lang::Srp if script == Script::Latin => SerbocroatianLatin,
+        Lang::Srp => SerbianCyrillic,
+        Lang::Swe => Swedish,
+        Lang::Tam => Tamil,
+        Lang::Tel => Telugu,
+        Lang::Tha => Thai,
+        Lang::Tuk => Turkmen,
+        Lang::Tur => Turkish,
+        Lang::Ukr => Ukrainian,
+        _ => return None
+    };
+    Some(found)
+}
@@ -0,0 +1,130 @@
+/*! Global accessors for the embedded dictionaries
+
+With the `statics` feature, which implies `embed_all`, every embedded
+dictionary can be reached through a function of this module, which loads it
+on first use and returns the same `&'static` dictionary thereafter.
+
+```ignore
+use kl_hyphenate::dictionaries;
+
+let hyphenated = dictionaries::english_us().hyphenate("anfractuous");
+```
+
+These accessors spare the handling of loading errors, which cannot occur for
+dictionaries known to be embedded. They panic, however, if the dictionary
+was left out of the binary by `KL_HYPHENATE_EMBED`; when that is a
+possibility, [`Load::from_embedded`] should be used instead.
+
+[`Load::from_embedded`]: ../load/trait.Load.html#tymethod.from_embedded
+*/
+
+use once_cell::sync::OnceCell;
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::{Standard, Extended};
+use load::Load;
+
+
+macro_rules! accessors {
+    ($dict:ident, $($name:ident, $lang:ident;)*) => {
+        $(
+            #[doc = concat!("The embedded `", stringify!($dict), "` dictionary for `",
+                            stringify!($lang), "`, loaded on first use.")]
+            pub fn $name() -> &'static $dict {
+                static DICTIONARY : OnceCell<$dict> = OnceCell::new();
+                DICTIONARY.get_or_init(|| {
+                    $dict::from_embedded(Language::$lang).unwrap_or_else(|e| {
+                        panic!("The `{}` dictionary for {:?} is not embedded: {}",
+                               stringify!($dict), Language::$lang, e)
+                    })
+                })
+            }
+        )*
+    }
+}
+
+accessors! { Standard,
+    afrikaans,               Afrikaans;
+    armenian,                Armenian;
+    assamese,                Assamese;
+    basque,                  Basque;
+    belarusian,              Belarusian;
+    bengali,                 Bengali;
+    bulgarian,               Bulgarian;
+    catalan,                 Catalan;
+    chinese,                 Chinese;
+    coptic,                  Coptic;
+    croatian,                Croatian;
+    czech,                   Czech;
+    danish,                  Danish;
+    dutch,                   Dutch;
+    english_gb,              EnglishGB;
+    english_us,              EnglishUS;
+    esperanto,               Esperanto;
+    estonian,                Estonian;
+    ethiopic,                Ethiopic;
+    finnish,                 Finnish;
+    french,                  French;
+    friulan,                 Friulan;
+    galician,                Galician;
+    georgian,                Georgian;
+    german_1901,             German1901;
+    german_1996,             German1996;
+    german_swiss,            GermanSwiss;
+    greek_ancient,           GreekAncient;
+    greek_mono,              GreekMono;
+    greek_poly,              GreekPoly;
+    gujarati,                Gujarati;
+    hindi,                   Hindi;
+    hungarian,               Hungarian;
+    icelandic,               Icelandic;
+    indonesian,              Indonesian;
+    interlingua,             Interlingua;
+    irish,                   Irish;
+    italian,                 Italian;
+    kannada,                 Kannada;
+    kurmanji,                Kurmanji;
+    latin,                   Latin;
+    latin_classic,           LatinClassic;
+    latin_liturgical,        LatinLiturgical;
+    latvian,                 Latvian;
+    lithuanian,              Lithuanian;
+    macedonian,              Macedonian;
+    malayalam,               Malayalam;
+    marathi,                 Marathi;
+    mongolian,               Mongolian;
+    norwegian_bokmal,        NorwegianBokmal;
+    norwegian_nynorsk,       NorwegianNynorsk;
+    occitan,                 Occitan;
+    oriya,                   Oriya;
+    pali,                    Pali;
+    panjabi,                 Panjabi;
+    piedmontese,             Piedmontese;
+    polish,                  Polish;
+    portuguese,              Portuguese;
+    romanian,                Romanian;
+    romansh,                 Romansh;
+    russian,                 Russian;
+    sanskrit,                Sanskrit;
+    serbian_cyrillic,        SerbianCyrillic;
+    serbocroatian_cyrillic,  SerbocroatianCyrillic;
+    serbocroatian_latin,     SerbocroatianLatin;
+    slavonic_church,         SlavonicChurch;
+    slovak,                  Slovak;
+    slovenian,               Slovenian;
+    spanish,                 Spanish;
+    swedish,                 Swedish;
+    tamil,                   Tamil;
+    telugu,                  Telugu;
+    thai,                    Thai;
+    turkish,                 Turkish;
+    turkmen,                 Turkmen;
+    ukrainian,               Ukrainian;
+    uppersorbian,            Uppersorbian;
+    welsh,                   Welsh;
+}
+
+accessors! { Extended,
+    catalan_extended,        Catalan;
+    hungarian_extended,      Hungarian;
+}
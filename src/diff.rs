@@ -0,0 +1,63 @@
+/*! Comparing dictionaries over word lists
+
+Upgrading to a new snapshot of patterns silently changes the hyphenation of
+some words. Running both the old and the new dictionary over a word list,
+such as the vocabulary of a publication, reports each word whose breaks
+differ, so that the change may be reviewed before it is set in print.
+
+```ignore
+use kl_hyphenate::diff::diff;
+
+let words = ["hyphenation", "anfractuous", "associate"];
+for difference in diff(&old_en_us, &new_en_us, &words) {
+    let (before, after) = difference.marked("-");
+    println!("{}: {} → {}", difference.word, before, after);
+}
+```
+
+Any two hyphenators whose opportunities are of the same type may be
+compared; as such, a dictionary may also be compared with itself under
+different minima, or with an `Overlay` of it.
+*/
+
+use hyphenator::Hyphenator;
+use iter::Segments;
+
+
+/// A word which two hyphenators break differently.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difference<Break> {
+    /// The word, as given.
+    pub word : String,
+    /// The breaks found by the first hyphenator.
+    pub before : Vec<Break>,
+    /// The breaks found by the second hyphenator.
+    pub after : Vec<Break>
+}
+
+impl Difference<usize> {
+    /// The word as hyphenated by either hyphenator, its segments joined by
+    /// `mark`.
+    pub fn marked(&self, mark : &str) -> (String, String) {
+        let join = |breaks : &[usize]| Segments::new(&self.word, breaks.iter().cloned()).collect::<Vec<_>>().join(mark);
+        (join(&self.before), join(&self.after))
+    }
+}
+
+/// Hyphenate each of the given words with both `before` and `after`,
+/// returning those whose breaks differ, in the order given.
+pub fn diff<'h, A, B, I, S>(before : &'h A, after : &'h B, words : I) -> Vec<Difference<A::Opportunity>>
+where A : Hyphenator<'h>
+    , B : Hyphenator<'h, Opportunity = A::Opportunity>
+    , A::Opportunity : PartialEq
+    , I : IntoIterator<Item = S>
+    , S : AsRef<str>
+{
+    words.into_iter().filter_map(|word| {
+        let word = word.as_ref();
+        let (old, new) = (before.hyphenate(word).breaks, after.hyphenate(word).breaks);
+        if old == new { None } else {
+            Some(Difference { word : word.to_owned(), before : old, after : new })
+        }
+    }).collect()
+}
@@ -0,0 +1,94 @@
+/*! Opportunities as TeX discretionaries
+
+Typesetting backends model breaks after TeX's `\discretionary{pre}{post}{no
+break}`: a stretch of the word is set as `no_break` where the line runs on,
+or else as `pre` at the end of the line and `post` at the start of the next.
+A [`Discretionary`] describes any opportunity so, whichever the dictionary
+which found it: the breaks of `Standard` dictionaries replace nothing, and
+merely end the line with a hyphen, whereas those of `Extended` dictionaries
+respell the letters of their subregion.
+
+```ignore
+let word = hu.hyphenate("asszonnyal");
+let second = &word.discretionaries()[1];
+assert_eq!((&second.pre[..], &second.post[..], &second.no_break[..]), ("ny-", "ny", "nny"));
+assert_eq!(&word.text[second.span.clone()], "nny");
+```
+
+[`Discretionary`]: struct.Discretionary.html
+*/
+
+use std::ops::Range;
+
+use extended::Subregion;
+use hyphenator::Word;
+
+
+/// An opportunity, as the text to set in place of a stretch of the word on
+/// either side of the break, or where the word is left unbroken.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Discretionary {
+    /// The bytes of the word which the discretionary stands for, empty where
+    /// the break respells nothing.
+    pub span : Range<usize>,
+    /// The text ending the line broken here, such as a hyphen.
+    pub pre : String,
+    /// The text starting the line after the break.
+    pub post : String,
+    /// The text set where the word is not broken here, as spelled in the word.
+    pub no_break : String
+}
+
+/// Opportunities which can be described as discretionaries.
+pub trait ToDiscretionary {
+    /// This opportunity of the given word, as a discretionary whose `pre`
+    /// ends with `mark`.
+    fn to_discretionary(&self, text : &str, mark : &str) -> Discretionary;
+}
+
+impl ToDiscretionary for usize {
+    fn to_discretionary(&self, _ : &str, mark : &str) -> Discretionary {
+        Discretionary {
+            span : *self .. *self,
+            pre : mark.to_owned(),
+            post : String::new(),
+            no_break : String::new()
+        }
+    }
+}
+
+impl ToDiscretionary for (usize, Option<&Subregion>) {
+    fn to_discretionary(&self, text : &str, mark : &str) -> Discretionary {
+        let (i, subr) = *self;
+        let spanned = subr.and_then(|subr| {
+            let span = subr.span(i) ?;
+            Some((subr, text.get(span.clone()) ?, span))
+        });
+        match spanned {
+            None => i.to_discretionary(text, mark),
+            Some((subr, no_break, span)) => {
+                let (fore, aft) = subr.substitution().split_at(subr.breakpoint());
+                Discretionary {
+                    pre : [fore, mark].concat(),
+                    post : aft.to_owned(),
+                    no_break : no_break.to_owned(),
+                    span
+                }
+            }
+        }
+    }
+}
+
+impl<'t, Break> Word<'t, Break> where Break : ToDiscretionary {
+    /// Every opportunity of the word, as a discretionary ending its line
+    /// with a hyphen.
+    pub fn discretionaries(&self) -> Vec<Discretionary> {
+        self.discretionaries_marked("-")
+    }
+
+    /// Every opportunity of the word, as a discretionary ending its line
+    /// with the given mark.
+    pub fn discretionaries_marked(&self, mark : &str) -> Vec<Discretionary> {
+        self.breaks.iter().map(|b| b.to_discretionary(self.text, mark)).collect()
+    }
+}
@@ -0,0 +1,422 @@
+/*! Dictionaries queried on disk, for low-memory environments
+
+A [`Packed`] dictionary reads its patterns in place, but the whole layout
+must still be addressable, whether read into memory or mapped from a file;
+devices such as e-readers may have neither the memory nor the mapping to
+spare. An [`OnDisk`] dictionary instead queries the packed layout through
+any seekable source, reading only the transitions, tallies and exceptions
+which each word requires. Its resident memory is that of a few offsets,
+whatever the size of the dictionary; every transition costs a read in turn.
+
+```ignore
+use kl_hyphenate::disk::OnDisk;
+
+fs::write("en-us.standard.packed", packed::pack(&en_us)) ?;
+
+// On the device:
+let en_us = OnDisk::from_path(Language::EnglishUS, "en-us.standard.packed") ?;
+let hyphenated = en_us.hyphenate("anfractuous");
+```
+
+Opening the dictionary only verifies its header and the extent of its
+sections; should the source fail or prove inconsistent while a word is
+looked up, the word is left unhyphenated. Reads are serialized behind a
+lock, such that the dictionary may be shared across threads, and buffered
+sources are best avoided, since each read is preceded by a seek. As the
+matches of shared stems would have to be held in memory, `score_with` scores
+every word from scratch.
+
+[`Packed`]: ../packed/struct.Packed.html
+[`OnDisk`]: struct.OnDisk.html
+*/
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::{Exceptions, MAX_LENGTH};
+use kl_hyphenate_commons::normalization::{CaseFolding, NormalizationForm};
+use hyphenator::{self, Hyphenator, Word};
+use load::{Error, Result};
+use packed::{self, EMPTY, INNER, MAGIC, VERSION};
+use score::{Score, Stems};
+
+
+/// A `Standard` dictionary queried from its packed representation in a
+/// seekable source `R`, such as a file.
+#[derive(Debug)]
+pub struct OnDisk<R> {
+    pub language : Language,
+    /// The minimum number of `char`s from the start and end of a word where
+    /// breaks may not occur.
+    pub minima : (usize, usize),
+    /// The length in bytes beyond which words are left unhyphenated, which
+    /// is `MAX_LENGTH` unless set otherwise.
+    pub max_length : usize,
+    /// The normalization form of the patterns, if any, as packed.
+    pub normalization : Option<NormalizationForm>,
+    /// How the words to hyphenate are folded, as for `Standard`.
+    pub case_folding : CaseFolding,
+    /// The exceptions added at runtime, which take priority over those of the
+    /// packed table.
+    pub exceptions : Exceptions,
+    /// The words whose packed exceptions were removed at runtime.
+    suppressed : HashSet<String>,
+    /// Whether every packed exception was removed at runtime.
+    cleared : bool,
+    layout : Layout,
+    source : Mutex<R>
+}
+
+/// The positions of the sections of a packed dictionary within its source,
+/// along with the number of entries of each.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Layout {
+    transitions : u64,
+    stipe : u64,
+    next : u64,
+    output : u64,
+    finals : u64,
+    state_output : u64,
+    tallies : u64,
+    offsets : u64,
+    loci : u64,
+    loci_count : u64,
+    exceptions : u64,
+    exception_offsets : u64,
+    exception_data : u64,
+    end : u64
+}
+
+impl OnDisk<File> {
+    /// Open the packed dictionary at the given path, verifying that it
+    /// effectively belongs to the requested language.
+    pub fn from_path<P : AsRef<Path>>(lang : Language, path : P) -> Result<Self> {
+        Self::open(lang, File::open(path) ?)
+    }
+}
+
+impl<R> OnDisk<R> where R : Read + Seek {
+    /// Open a packed dictionary held by the given source, verifying that it
+    /// effectively belongs to the requested language.
+    pub fn open(lang : Language, source : R) -> Result<Self> {
+        let dict = Self::any_open(source) ?;
+        if dict.language != lang {
+            Err(Error::LanguageMismatch { expected : lang, found : dict.language })
+        } else { Ok(dict) }
+    }
+
+    /// Open a packed dictionary held by the given source.
+    ///
+    /// Only the header and the extent of each section are read, such that a
+    /// truncated dictionary is reported as `Error::Malformed`.
+    pub fn any_open(mut source : R) -> Result<Self> {
+        let truncated = |err : io::Error| match err.kind() {
+            io::ErrorKind::UnexpectedEof => Error::Malformed("the packed dictionary is truncated"),
+            io::ErrorKind::InvalidData => Error::Malformed("the packed dictionary holds invalid UTF-8"),
+            _ => Error::IO(err)
+        };
+        // Lengths read from the source are checked against its own before
+        // anything is allocated for them.
+        let length = source.seek(SeekFrom::End(0)) ?;
+        let mut magic = [0; 4];
+        source.seek(SeekFrom::Start(0)) ?;
+        source.read_exact(&mut magic).map_err(truncated) ?;
+        if magic[..] != *MAGIC {
+            return Err(Error::Malformed("not a packed dictionary"))
+        }
+        match read_u32(&mut source, 4).map_err(truncated) ? {
+            VERSION => (),
+            found => return Err(Error::VersionMismatch { expected : VERSION, found : Some(found) })
+        }
+        let code = read_str(&mut source, 8, length).map_err(truncated) ?;
+        let language = code.parse().unwrap_or_else(|_| Language::other(&code));
+        let n = 12 + code.len() as u64;
+        let minima = (read_u32(&mut source, n).map_err(truncated) ? as usize,
+                      read_u32(&mut source, n + 4).map_err(truncated) ? as usize);
+        let form = read_str(&mut source, n + 8, length).map_err(truncated) ?;
+        let n = n + 12 + form.len() as u64;
+        let normalization = packed::read_normalization(&form) ?;
+
+        let transitions = read_u32(&mut source, n).map_err(truncated) ? as u64;
+        let stipe = n + 4;
+        let next = stipe + 2 * transitions;
+        let output = next + 4 * transitions;
+        let finals = read_u32(&mut source, output + 2 * transitions).map_err(truncated) ? as u64;
+        let state_output = output + 2 * transitions + 4;
+        let tallies = read_u32(&mut source, state_output + 6 * finals).map_err(truncated) ? as u64;
+        let offsets = state_output + 6 * finals + 4;
+        let loci_count = read_u32(&mut source, offsets + 4 * tallies).map_err(truncated) ? as u64;
+        let loci = offsets + 4 * (tallies + 1);
+        let exceptions = read_u32(&mut source, loci + 2 * loci_count).map_err(truncated) ? as u64;
+        let exception_offsets = loci + 2 * loci_count + 4;
+        let data_length = read_u32(&mut source, exception_offsets + 4 * exceptions).map_err(truncated) ? as u64;
+        let exception_data = exception_offsets + 4 * (exceptions + 1);
+        let end = exception_data + data_length;
+
+        if transitions == 0 {
+            return Err(Error::Malformed("the packed dictionary is inconsistent"))
+        }
+        if length < end {
+            return Err(Error::Malformed("the packed dictionary is truncated"))
+        }
+
+        Ok(OnDisk {
+            language, minima, max_length : MAX_LENGTH, normalization, case_folding : CaseFolding::default(),
+            exceptions : Exceptions::default(), suppressed : HashSet::new(), cleared : false,
+            layout : Layout {
+                transitions, stipe, next, output, finals, state_output, tallies, offsets,
+                loci, loci_count, exceptions, exception_offsets, exception_data, end
+            },
+            source : Mutex::new(source)
+        })
+    }
+
+    /// Close the dictionary, returning its source.
+    pub fn into_inner(self) -> R {
+        self.source.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // A failed read leaves nothing to repair, even if it panicked.
+    fn source(&self) -> MutexGuard<'_, R> {
+        self.source.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The breaks of the exception which holds for the given word, whether
+    /// added at runtime or packed.
+    fn exception(&self, word : &str) -> Option<Vec<usize>> {
+        match self.exceptions.0.get(word) {
+            Some(breaks) => Some(breaks.clone()),
+            None if self.cleared || self.suppressed.contains(word) => None,
+            None => self.layout.exception(&mut *self.source(), word).unwrap_or(None)
+        }
+    }
+}
+
+fn read_at<R : Read + Seek>(source : &mut R, at : u64, buffer : &mut [u8]) -> io::Result<()> {
+    source.seek(SeekFrom::Start(at)) ?;
+    source.read_exact(buffer)
+}
+
+fn read_u32<R : Read + Seek>(source : &mut R, at : u64) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    read_at(source, at, &mut bytes) ?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_str<R : Read + Seek>(source : &mut R, at : u64, length : u64) -> io::Result<String> {
+    let n = read_u32(source, at) ? as u64;
+    if at + 4 + n > length {
+        return Err(io::ErrorKind::UnexpectedEof.into())
+    }
+    let mut bytes = vec![0; n as usize];
+    source.read_exact(&mut bytes) ?;
+    String::from_utf8(bytes).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid UTF-8"))
+}
+
+fn read_u16<R : Read + Seek>(source : &mut R, at : u64) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    read_at(source, at, &mut bytes) ?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn inconsistent() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "the packed dictionary is inconsistent")
+}
+
+impl Layout {
+    fn score<R : Read + Seek>(&self, source : &mut R, word : &str) -> io::Result<Vec<u8>> {
+        let match_str = [".", word, "."].concat();
+        let mut values : Vec<u8> = vec![0; word.len().saturating_sub(1)];
+        let root = self.root(source) ?;
+        for i in 0 .. match_str.len() - 1 {
+            if let Some(tally_id) = root {
+                self.tally(source, &mut values, i, tally_id) ?;
+            }
+            let (mut state, mut out) = (0u32, 0u16);
+            for &label in &match_str.as_bytes()[i ..] {
+                match self.step(source, state, out, label) ? {
+                    Some((next, output, tally_id)) => {
+                        state = next;
+                        out = output;
+                        if let Some(tally_id) = tally_id {
+                            self.tally(source, &mut values, i, tally_id) ?;
+                        }
+                    },
+                    None => break
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    // The root is final if the empty pattern is present.
+    fn root<R : Read + Seek>(&self, source : &mut R) -> io::Result<Option<u16>> {
+        let mut stipe = [0; 2];
+        read_at(source, self.stipe, &mut stipe) ?;
+        match stipe[1] {
+            EMPTY => Ok(Some(0)),
+            INNER => self.inner_output(source, 0),
+            _ => Ok(None)
+        }
+    }
+
+    fn step<R : Read + Seek>(&self, source : &mut R, state : u32, output : u16, label : u8)
+        -> io::Result<Option<(u32, u16, Option<u16>)>>
+    {
+        let e = state as u64 + 1 + label as u64;
+        if e >= self.transitions { return Ok(None) }
+        let mut stipe = [0; 2];
+        read_at(source, self.stipe + 2 * e, &mut stipe) ?;
+        if stipe[0] != label { return Ok(None) }
+        let output = output.wrapping_add(read_u16(source, self.output + 2 * e) ?);
+        let state = read_u32(source, self.next + 4 * e) ?;
+        let found = match stipe[1] {
+            EMPTY => Some(output),
+            INNER => self.inner_output(source, state) ?.map(|o| output.wrapping_add(o)),
+            _ => None
+        };
+        Ok(Some((state, output, found)))
+    }
+
+    /// The inner output of the given final state.
+    fn inner_output<R : Read + Seek>(&self, source : &mut R, state : u32) -> io::Result<Option<u16>> {
+        let (mut lo, mut hi) = (0, self.finals);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match read_u32(source, self.state_output + 6 * mid) ? {
+                s if s < state => lo = mid + 1,
+                s if s > state => hi = mid,
+                _ => return read_u16(source, self.state_output + 6 * mid + 4).map(Some)
+            }
+        }
+        Ok(None)
+    }
+
+    /// Apply the tally matched at index `i` of the word enclosed in dots.
+    fn tally<R : Read + Seek>(&self, source : &mut R, values : &mut [u8], i : usize, tally_id : u16)
+        -> io::Result<()>
+    {
+        let hyphenable_length = values.len() + 1;
+        let tally_id = tally_id as u64;
+        if tally_id >= self.tallies { return Ok(()) }
+        let start = read_u32(source, self.offsets + 4 * tally_id) ? as u64;
+        let end = read_u32(source, self.offsets + 4 * (tally_id + 1)) ? as u64;
+        if start > end || end > self.loci_count { return Err(inconsistent()) }
+        let mut loci = vec![0; 2 * (end - start) as usize];
+        read_at(source, self.loci + 2 * start, &mut loci) ?;
+        for locus in loci.chunks(2) {
+            let (index, value) = (locus[0], locus[1]);
+            let k = i + index as usize;
+            if k > 1 && k <= hyphenable_length && value > values[k - 2] {
+                values[k - 2] = value;
+            }
+        }
+        Ok(())
+    }
+
+    /// The position and word of the `i`th packed exception.
+    fn exception_word<R : Read + Seek>(&self, source : &mut R, i : u64) -> io::Result<(u64, Vec<u8>)> {
+        let at = self.exception_data + read_u32(source, self.exception_offsets + 4 * i) ? as u64;
+        let n = read_u16(source, at) ? as u64;
+        if at + 2 + n > self.end { return Err(inconsistent()) }
+        let mut word = vec![0; n as usize];
+        read_at(source, at + 2, &mut word) ?;
+        Ok((at + 2 + n, word))
+    }
+
+    /// The breaks of the packed exception for the given word, if any.
+    fn exception<R : Read + Seek>(&self, source : &mut R, word : &str) -> io::Result<Option<Vec<usize>>> {
+        let (mut lo, mut hi) = (0, self.exceptions);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let (at, candidate) = self.exception_word(source, mid) ?;
+            match candidate.as_slice().cmp(word.as_bytes()) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => {
+                    let mut count = [0];
+                    read_at(source, at, &mut count) ?;
+                    if at + 1 + 2 * count[0] as u64 > self.end { return Err(inconsistent()) }
+                    let mut breaks = vec![0; 2 * count[0] as usize];
+                    read_at(source, at + 1, &mut breaks) ?;
+                    return Ok(Some(breaks.chunks(2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize).collect()))
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'d, R> Score<'d> for OnDisk<R> where R : Read + Seek {
+    type Value = u8;
+
+    #[inline] fn denotes_opportunity(v : Self::Value) -> bool { v % 2 != 0 }
+
+    fn score(&'d self, word : &str) -> Vec<u8> {
+        self.layout.score(&mut *self.source(), word)
+            .unwrap_or_else(|_| vec![0; word.len().saturating_sub(1)])
+    }
+
+    #[inline]
+    fn score_with(&'d self, word : &str, _ : &Stems) -> Vec<u8> { self.score(word) }
+}
+
+impl<'h, R> Hyphenator<'h> for OnDisk<R> where R : Read + Seek {
+    type Opportunity = usize;
+    type Exact = usize;
+
+    fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, Self::Opportunity> {
+        hyphenator::hyphenate_prepared(self, word, |i| i, |i, prepared| prepared.realign(i))
+    }
+
+    fn opportunities_within(&'h self, word : &str, bounds : (usize, usize)) -> Vec<usize> {
+        hyphenator::scored(word, self.score(word), bounds, Self::denotes_opportunity, |i, _| i).collect()
+    }
+
+    #[inline]
+    fn exact_within(&'h self, w : &str, (l, r) : (usize, usize)) -> Option<Vec<Self::Opportunity>> {
+        self.exception(w).map(|v| v.into_iter().filter(|&i| i >= l && i <= r).collect())
+    }
+
+    fn add_exact(&mut self, w : String, ops : Vec<usize>) -> Option<Vec<usize>> {
+        let old = self.exception(&w);
+        self.exceptions.0.insert(w, ops);
+        old
+    }
+
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<usize>> {
+        let (w, ops) = hyphenator::unmark_for(marked, self);
+        self.add_exact(w, ops)
+    }
+
+    fn remove_exact(&mut self, w : &str) -> Option<Vec<usize>> {
+        let old = self.exception(w);
+        self.exceptions.0.remove(w);
+        let packed = self.layout.exception(&mut *self.source(), w).unwrap_or(None);
+        if !self.cleared && packed.is_some() {
+            self.suppressed.insert(w.to_owned());
+        }
+        old
+    }
+
+    fn clear_exceptions(&mut self) {
+        self.exceptions.0.clear();
+        self.suppressed.clear();
+        self.cleared = true;
+    }
+
+    #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.minima }
+
+    #[inline] fn max_length(&self) -> usize { self.max_length }
+
+    #[inline] fn normalization(&self) -> Option<NormalizationForm> { self.normalization }
+
+    #[inline] fn language(&self) -> Option<Language> { Some(self.language) }
+
+    #[inline] fn case_folding(&self) -> CaseFolding { self.case_folding }
+}
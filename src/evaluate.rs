@@ -0,0 +1,93 @@
+/*! Evaluating dictionaries against hyphenated words
+
+Tuning exceptions or custom patterns calls for a measure of their quality.
+Given a gold standard of words marked with hyphens (`-`) or equal signs (`=`)
+at their breaks, [`evaluate`] hyphenates each word with the dictionary and
+counts the breaks it found, those it wrongly found, and those it missed.
+
+```ignore
+use kl_hyphenate::evaluate::evaluate;
+
+let gold = fs::read_to_string("gold.hyp") ?;
+let evaluation = evaluate(&en_us, gold.lines());
+println!("precision {:.3}, recall {:.3}", evaluation.precision(), evaluation.recall());
+for word in &evaluation.mistaken { println!("{}", word) }
+```
+
+Breaks of the gold standard which lie within the minima of the dictionary
+count as missed, as the dictionary never finds them.
+
+[`evaluate`]: fn.evaluate.html
+*/
+
+use hyphenator::{self, Hyphenator};
+
+
+/// The agreement of a dictionary with a gold standard.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Evaluation {
+    /// The number of words evaluated.
+    pub words : usize,
+    /// The number of words hyphenated exactly as in the gold standard.
+    pub correct : usize,
+    /// The number of breaks found which the gold standard holds.
+    pub found : usize,
+    /// The number of breaks found which the gold standard does not hold.
+    pub spurious : usize,
+    /// The number of breaks of the gold standard which were not found.
+    pub missed : usize,
+    /// The words hyphenated otherwise than in the gold standard, as marked
+    /// therein.
+    pub mistaken : Vec<String>
+}
+
+impl Evaluation {
+    /// The share of breaks found which are correct, or 1 if none were found.
+    pub fn precision(&self) -> f64 {
+        ratio(self.found, self.found + self.spurious)
+    }
+
+    /// The share of correct breaks which were found, or 1 if there are none.
+    pub fn recall(&self) -> f64 {
+        ratio(self.found, self.found + self.missed)
+    }
+
+    /// The share of words hyphenated exactly as in the gold standard, or 1 if
+    /// there are none.
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.correct, self.words)
+    }
+}
+
+fn ratio(n : usize, total : usize) -> f64 {
+    if total == 0 { 1.0 } else { n as f64 / total as f64 }
+}
+
+/// Hyphenate each word of the gold standard, given marked at its breaks, and
+/// compare the breaks found with those marked.
+pub fn evaluate<'h, H, I, S>(dict : &'h H, gold : I) -> Evaluation
+where H : Hyphenator<'h, Opportunity = usize>
+    , I : IntoIterator<Item = S>
+    , S : AsRef<str>
+{
+    let mut evaluation = Evaluation::default();
+    for marked in gold {
+        let marked = marked.as_ref().trim();
+        if marked.is_empty() { continue }
+        // The dictionary folds the word as it hyphenates it.
+        let (word, expected) = hyphenator::unmark_with(marked, str::to_owned);
+        let breaks = dict.hyphenate(&word).breaks;
+        let found = breaks.iter().filter(|i| expected.contains(i)).count();
+
+        evaluation.words += 1;
+        evaluation.found += found;
+        evaluation.spurious += breaks.len() - found;
+        evaluation.missed += expected.len() - found;
+        if breaks == expected {
+            evaluation.correct += 1;
+        } else {
+            evaluation.mistaken.push(marked.to_owned());
+        }
+    }
+    evaluation
+}
@@ -0,0 +1,140 @@
+/*! Writing dictionaries back out as TeX patterns
+
+Dictionaries are built from the pattern files of the hyph-utf8 project, and
+may be written back out in the same notation, for instance to carry changes
+made to a dictionary over to TeX. The [`Export`] trait renders a dictionary
+either as a TeX file holding `\patterns{…}` and `\hyphenation{…}`, or as the
+plain `.pat.txt` and `.hyp.txt` files of hyph-utf8, from which the build
+script reads.
+
+```ignore
+use kl_hyphenate::export::Export;
+
+fs::write("hyph-en-us.pat.txt", en_us.to_pat()) ?;
+fs::write("hyph-en-us.hyp.txt", en_us.to_hyp()) ?;
+fs::write("hyph-en-us.tex", en_us.to_tex()) ?;
+```
+
+Extended patterns are written in the notation described by Németh, as found
+in the `.ext.txt` files of this crate. Patterns and exceptions are sorted,
+and comments are not preserved.
+
+[`Export`]: trait.Export.html
+*/
+
+use kl_hyphenate_commons::dictionary::{Locus, Standard};
+use kl_hyphenate_commons::dictionary::extended::{self as ext, Extended};
+
+
+/// Rendering of dictionaries in the notation of TeX patterns.
+pub trait Export {
+    /// The patterns of the dictionary in TeX notation, in order.
+    fn tex_patterns(&self) -> Vec<String>;
+
+    /// The exceptions of the dictionary in TeX notation, in order.
+    fn tex_exceptions(&self) -> Vec<String>;
+
+    /// The patterns, one per line, as in the `.pat.txt` files of hyph-utf8.
+    fn to_pat(&self) -> String { lines(self.tex_patterns()) }
+
+    /// The exceptions, one per line, as in the `.hyp.txt` files of hyph-utf8.
+    fn to_hyp(&self) -> String { lines(self.tex_exceptions()) }
+
+    /// A TeX file declaring the patterns and exceptions of the dictionary.
+    fn to_tex(&self) -> String {
+        format!("\\patterns{{\n{}}}\n\\hyphenation{{\n{}}}\n", self.to_pat(), self.to_hyp())
+    }
+}
+
+fn lines(items : Vec<String>) -> String {
+    items.into_iter().map(|item| item + "\n").collect()
+}
+
+/// The pattern of the given letters, with the values of the tally inserted at
+/// their loci.
+fn pattern(letters : &str, tally : &[Locus]) -> String {
+    let mut pattern = String::with_capacity(letters.len() + tally.len());
+    let mut loci = tally.iter().peekable();
+    for (i, c) in letters.char_indices() {
+        while let Some(locus) = loci.next_if(|locus| locus.index as usize <= i) {
+            pattern.push_str(&locus.value.to_string());
+        }
+        pattern.push(c);
+    }
+    for locus in loci {
+        pattern.push_str(&locus.value.to_string());
+    }
+    pattern
+}
+
+/// The word with a hyphen at each of the given breaks.
+fn exception(word : &str, breaks : &[usize]) -> String {
+    let mut hyphenated = String::with_capacity(word.len() + breaks.len());
+    let mut start = 0;
+    for &i in breaks {
+        hyphenated.push_str(&word[start .. i]);
+        hyphenated.push('-');
+        start = i;
+    }
+    hyphenated.push_str(&word[start ..]);
+    hyphenated
+}
+
+/// The extended pattern of the given letters, the subregion following the
+/// standard pattern as `/substitution,start,span`; the start of the subregion
+/// is counted in `char`s from the first letter, and its breakpoint marked by
+/// `=` within the substitution.
+fn extended_pattern(letters : &str, tally : &ext::Tally) -> String {
+    let standard = pattern(letters, &tally.standard);
+    match tally.subregion {
+        None => standard,
+        Some((locus, ref subregion)) => {
+            let index = locus.index as usize;
+            let start = index - subregion.left;
+            let span = letters[start .. index + subregion.right].chars().count();
+            let dots = if letters.starts_with('.') { 1 } else { 0 };
+            let offset = letters[.. start].chars().count() + 1 - dots;
+            let substitution = &subregion.substitution;
+            let (before, after) = substitution.split_at(subregion.breakpoint.min(substitution.len()));
+            format!("{}/{}={},{},{}", standard, before, after, offset, span)
+        }
+    }
+}
+
+impl Export for Standard {
+    fn tex_patterns(&self) -> Vec<String> {
+        self.patterns.entries().into_iter()
+            .map(|(letters, tally)| pattern(&letters, tally))
+            .collect()
+    }
+
+    fn tex_exceptions(&self) -> Vec<String> {
+        let mut exceptions : Vec<_> = self.exceptions.iter()
+            .map(|(word, breaks)| exception(word, breaks))
+            .collect();
+        exceptions.sort();
+        exceptions
+    }
+}
+
+impl Export for Extended {
+    fn tex_patterns(&self) -> Vec<String> {
+        self.patterns.entries().into_iter()
+            .map(|(letters, tally)| extended_pattern(&letters, tally))
+            .collect()
+    }
+
+    /// The exceptions of the dictionary in TeX notation, in order. TeX has
+    /// no notation for the substitutions of extended exceptions, which are
+    /// left out.
+    fn tex_exceptions(&self) -> Vec<String> {
+        let mut exceptions : Vec<_> = self.exceptions.iter()
+            .map(|(word, breaks)| {
+                let breaks : Vec<usize> = breaks.iter().map(|&(i, _)| i).collect();
+                exception(word, &breaks)
+            })
+            .collect();
+        exceptions.sort();
+        exceptions
+    }
+}
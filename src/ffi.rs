@@ -0,0 +1,251 @@
+/*! A C interface to standard dictionaries
+
+With the `ffi` feature, dictionaries may be loaded and used from C, C++ or
+any language that calls into C, through the functions of this module and the
+header `include/kl_hyphenate.h`. The library is built into a shared object
+with:
+
+```text
+cargo rustc --release --features ffi --crate-type cdylib
+```
+
+From C, a dictionary is loaded from a serialized file, applied to UTF-8
+words, and freed along with every string it returned:
+
+```c
+#include "kl_hyphenate.h"
+
+HyphenationDictionary *en_us = hyphenation_load("en-us", "en-us.standard.bincode");
+char *hyphenated = hyphenation_hyphenate(en_us, "anfractuous", "-");
+// "an-frac-tu-ous"
+hyphenation_free_string(hyphenated);
+hyphenation_free(en_us);
+```
+
+Bindings which would rather not marshal the result through a string may
+instead receive the breaks of a word as a [`HyphenationBreaks`] list, or as
+part of a [`HyphenationWord`] which borrows the text it was given. Words are
+then passed as a pointer and a length in bytes, and need not be
+null-terminated:
+
+```c
+const char *text = "anfractuous cosmopolitan";
+HyphenationWord word = hyphenation_hyphenate_word(en_us, text, 11);
+for (size_t i = 0; i < word.breaks.len; i++)
+    printf("%zu\n", word.breaks.indices[i]); // 2, 6, 8
+hyphenation_free_word(word);
+```
+
+Failures, whether of loading or of invalid UTF-8, are reported as null
+pointers; no panic unwinds into the caller. The header is generated by
+`cbindgen --config cbindgen.toml --output include/kl_hyphenate.h`.
+
+[`HyphenationBreaks`]: struct.HyphenationBreaks.html
+[`HyphenationWord`]: struct.HyphenationWord.html
+*/
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::str;
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::Standard;
+use hyphenator::Hyphenator;
+use load::Load;
+
+
+/// A standard dictionary, opaque to C.
+pub struct HyphenationDictionary(Standard);
+
+/// The breaks of a word, as byte indices into it, in ascending order.
+///
+/// The list is owned by the caller, and must be freed with
+/// `hyphenation_free_breaks`, unless it belongs to a `HyphenationWord`. A
+/// null `indices` denotes a failure, whereas a word without breaks has a
+/// non-null `indices` of length 0.
+#[repr(C)]
+#[derive(Debug)]
+pub struct HyphenationBreaks {
+    pub indices : *mut usize,
+    pub len : usize
+}
+
+/// A hyphenated word: the text given to `hyphenation_hyphenate_word`, and its
+/// breaks.
+///
+/// The text is borrowed from the caller, and is neither copied nor freed;
+/// it must outlive the word. The breaks are owned by the word, and freed
+/// along with it by `hyphenation_free_word`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct HyphenationWord {
+    pub text : *const c_char,
+    pub len : usize,
+    pub breaks : HyphenationBreaks
+}
+
+impl HyphenationBreaks {
+    fn failure() -> Self { HyphenationBreaks { indices : ptr::null_mut(), len : 0 } }
+
+    fn from_vec(breaks : Vec<usize>) -> Self {
+        let breaks = breaks.into_boxed_slice();
+        let len = breaks.len();
+        // The pointer of an empty slice is dangling, but never null.
+        HyphenationBreaks { indices : Box::into_raw(breaks) as *mut usize, len }
+    }
+}
+
+/// Borrow the UTF-8 string at the given pointer, if neither null nor invalid.
+unsafe fn utf8<'a>(s : *const c_char) -> Option<&'a str> {
+    if s.is_null() { None } else { CStr::from_ptr(s).to_str().ok() }
+}
+
+/// Borrow the UTF-8 string of `len` bytes at the given pointer, if neither
+/// null nor invalid.
+unsafe fn utf8_within<'a>(s : *const c_char, len : usize) -> Option<&'a str> {
+    if s.is_null() { None } else { str::from_utf8(slice::from_raw_parts(s as *const u8, len)).ok() }
+}
+
+/// Load the standard dictionary for the language of the given code from the
+/// serialized file at `path`, or return null if it cannot be loaded.
+///
+/// # Safety
+///
+/// `language` and `path` must be null or point to null-terminated strings.
+/// The dictionary must be freed with `hyphenation_free`.
+#[no_mangle]
+pub unsafe extern "C" fn hyphenation_load(language : *const c_char, path : *const c_char)
+    -> *mut HyphenationDictionary
+{
+    let (code, path) = match (utf8(language), utf8(path)) {
+        (Some(code), Some(path)) => (code, path),
+        _ => return ptr::null_mut()
+    };
+    let lang = code.parse().unwrap_or_else(|_| Language::other(code));
+    match panic::catch_unwind(|| Standard::from_path(lang, path)) {
+        Ok(Ok(dict)) => Box::into_raw(Box::new(HyphenationDictionary(dict))),
+        _ => ptr::null_mut()
+    }
+}
+
+/// Hyphenate the given word, returning its segments joined by `marker`, or
+/// the hyphen-minus if `marker` is null. Null is returned if the dictionary or
+/// the word is null, or if either string is not valid UTF-8.
+///
+/// # Safety
+///
+/// `dict` must be null or have been returned by `hyphenation_load`, and not
+/// yet freed. `word` and `marker` must be null or point to null-terminated
+/// strings. The result must be freed with `hyphenation_free_string`.
+#[no_mangle]
+pub unsafe extern "C" fn hyphenation_hyphenate(dict : *const HyphenationDictionary,
+                                               word : *const c_char, marker : *const c_char)
+    -> *mut c_char
+{
+    let marker = if marker.is_null() { Some("-") } else { utf8(marker) };
+    let (dict, word, marker) = match (dict.as_ref(), utf8(word), marker) {
+        (Some(dict), Some(word), Some(marker)) => (dict, word, marker),
+        _ => return ptr::null_mut()
+    };
+    let hyphenated = panic::catch_unwind(AssertUnwindSafe(|| {
+        dict.0.hyphenate(word).into_iter().segments().collect::<Vec<_>>().join(marker)
+    }));
+    match hyphenated.ok().and_then(|s| CString::new(s).ok()) {
+        Some(s) => s.into_raw(),
+        None => ptr::null_mut()
+    }
+}
+
+/// The breaks of the word of `len` bytes at `word`, which is not necessarily
+/// null-terminated. The list is null if the dictionary or the word is null,
+/// or if the word is not valid UTF-8.
+///
+/// # Safety
+///
+/// `dict` must be null or have been returned by `hyphenation_load`, and not
+/// yet freed. `word` must be null or point to `len` readable bytes. The
+/// result must be freed with `hyphenation_free_breaks`.
+#[no_mangle]
+pub unsafe extern "C" fn hyphenation_breaks(dict : *const HyphenationDictionary,
+                                            word : *const c_char, len : usize)
+    -> HyphenationBreaks
+{
+    let (dict, word) = match (dict.as_ref(), utf8_within(word, len)) {
+        (Some(dict), Some(word)) => (dict, word),
+        _ => return HyphenationBreaks::failure()
+    };
+    match panic::catch_unwind(AssertUnwindSafe(|| dict.0.hyphenate(word).breaks)) {
+        Ok(breaks) => HyphenationBreaks::from_vec(breaks),
+        Err(_) => HyphenationBreaks::failure()
+    }
+}
+
+/// Hyphenate the word of `len` bytes at `word`, as by `hyphenation_breaks`,
+/// returning it along with its breaks.
+///
+/// # Safety
+///
+/// As for `hyphenation_breaks`; `word` must moreover outlive the result,
+/// which must be freed with `hyphenation_free_word`.
+#[no_mangle]
+pub unsafe extern "C" fn hyphenation_hyphenate_word(dict : *const HyphenationDictionary,
+                                                    word : *const c_char, len : usize)
+    -> HyphenationWord
+{
+    HyphenationWord { text : word, len, breaks : hyphenation_breaks(dict, word, len) }
+}
+
+/// Free a dictionary returned by `hyphenation_load`. Null is ignored.
+///
+/// # Safety
+///
+/// `dict` must be null or have been returned by `hyphenation_load`, and not
+/// yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn hyphenation_free(dict : *mut HyphenationDictionary) {
+    if !dict.is_null() {
+        drop(Box::from_raw(dict));
+    }
+}
+
+/// Free a string returned by `hyphenation_hyphenate`. Null is ignored.
+///
+/// # Safety
+///
+/// `s` must be null or have been returned by `hyphenation_hyphenate`, and not
+/// yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn hyphenation_free_string(s : *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Free a list of breaks returned by `hyphenation_breaks`. A failure, whose
+/// `indices` is null, is ignored.
+///
+/// # Safety
+///
+/// `breaks` must have been returned by `hyphenation_breaks`, and not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn hyphenation_free_breaks(breaks : HyphenationBreaks) {
+    if !breaks.indices.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(breaks.indices, breaks.len)));
+    }
+}
+
+/// Free the breaks of a word returned by `hyphenation_hyphenate_word`, leaving
+/// its text to the caller.
+///
+/// # Safety
+///
+/// `word` must have been returned by `hyphenation_hyphenate_word`, and not
+/// yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn hyphenation_free_word(word : HyphenationWord) {
+    hyphenation_free_breaks(word.breaks)
+}
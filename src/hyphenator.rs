@@ -3,10 +3,15 @@ Methods for hyphenation dictionaries
 */
 
 use std::borrow::Cow;
+use std::io::{self, BufRead};
+use std::str;
+use std::sync::Arc;
 
+use kl_hyphenate_commons::Language;
 use kl_hyphenate_commons::dictionary::*;
 use kl_hyphenate_commons::dictionary::extended::*;
-use case_folding::{realign, refold, Shift};
+use kl_hyphenate_commons::normalization::{CaseFolding, NormalizationForm};
+use case_folding::{denormalize, fold_fully, Folded, normalize, realign, refold_in, refold_typography_in, within_expansion, Shift};
 use score::Score;
 
 
@@ -15,12 +20,101 @@ use score::Score;
 /// resorting to best-effort hyphenation.
 pub fn soft_hyphen_indices(word : &str) -> Option<Vec<usize>> {
     let shys : Vec<_> = word.match_indices('\u{00ad}').map(|(i, _)| i).collect();
-    if shys.len() > 0 {
+    if !shys.is_empty() {
         Some(shys)
     } else { None }
 }
 
 
+/// Unmark a word given with hyphens (`-`) or equal signs (`=`) at its
+/// breaks, such as "ta-ble", folding it for hyphenation. The breaks are
+/// returned as byte indices into the unmarked word.
+pub fn unmark(marked : &str) -> (String, Vec<usize>) {
+    unmark_as(marked, None)
+}
+
+/// Unmark a word as `unmark` does, normalizing it to the given form as well,
+/// as the dictionaries of that form expect their exceptions.
+pub fn unmark_as(marked : &str, form : Option<NormalizationForm>) -> (String, Vec<usize>) {
+    unmark_in(marked, None, form)
+}
+
+/// Unmark a word as `unmark_as` does, folding it by the case mappings of the
+/// given language, as the dictionaries of that language expect their
+/// exceptions.
+pub fn unmark_in(marked : &str, language : Option<Language>, form : Option<NormalizationForm>)
+    -> (String, Vec<usize>)
+{
+    unmark_with(marked, |segment| {
+        let folded = refold_in(segment, language).0;
+        match normalize(&folded, form) {
+            Some((normalized, _)) => normalized,
+            None => folded.into_owned()
+        }
+    })
+}
+
+/// Unmark a word, transforming each of its segments with `fold`.
+pub(crate) fn unmark_with<F>(marked : &str, fold : F) -> (String, Vec<usize>)
+where F : Fn(&str) -> String {
+    let mut word = String::with_capacity(marked.len());
+    let mut breaks = vec![];
+    for segment in marked.split(['-', '=']) {
+        if !word.is_empty() && breaks.last() != Some(&word.len()) {
+            breaks.push(word.len());
+        }
+        word.push_str(&fold(segment));
+    }
+    breaks.retain(|&i| i < word.len());
+    (word, breaks)
+}
+
+
+/// The outcome of importing a list of exceptions, see
+/// `Hyphenator::add_exceptions_from`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Import {
+    /// The number of exceptions added.
+    pub added : usize,
+    /// The lines which could not be imported, numbered from 1, and why.
+    pub errors : Vec<(usize, &'static str)>
+}
+
+
+/// A string stripped of its soft hyphens (U+00AD).
+///
+/// Indices into the stripped text, such as the breaks found by hyphenating
+/// it, can be projected back onto the original string with `realign`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stripped<'t> {
+    pub text : Cow<'t, str>,
+    shifts : Vec<Shift>
+}
+
+impl<'t> Stripped<'t> {
+    /// The index into the original string corresponding to `index` in the
+    /// stripped text.
+    ///
+    /// An index where soft hyphens were removed is mapped to the position
+    /// before them, just as soft hyphens are reported by `soft_hyphen_indices`.
+    pub fn realign(&self, index : usize) -> usize { realign(index, &self.shifts) }
+}
+
+/// Remove all soft hyphens (U+00AD) from the string.
+pub fn strip_soft_hyphens(text : &str) -> Stripped<'_> {
+    match soft_hyphen_indices(text) {
+        None => Stripped { text : Cow::Borrowed(text), shifts : vec![] },
+        Some(shys) => {
+            let width = '\u{00ad}'.len_utf8();
+            let shifts = shys.into_iter().enumerate().map(|(n, i)| {
+                Shift::new(i - n * width, -(((n + 1) * width) as isize))
+            }).collect();
+            Stripped { text : Cow::Owned(text.replace('\u{00ad}', "")), shifts }
+        }
+    }
+}
+
+
 /// A hyphenated word carrying valid breaks.
 ///
 /// The `Word` can be borrowed or moved for iteration with `iter()` and
@@ -32,6 +126,21 @@ pub struct Word<'t, Break> {
 }
 
 
+/// Opportunities which can be moved along the text where they were found.
+pub trait Offset {
+    /// This opportunity, shifted forward by `n` bytes.
+    fn offset(self, n : usize) -> Self;
+}
+
+impl Offset for usize {
+    #[inline] fn offset(self, n : usize) -> Self { self + n }
+}
+
+impl Offset for (usize, Option<&Subregion>) {
+    #[inline] fn offset(self, n : usize) -> Self { (self.0 + n, self.1) }
+}
+
+
 /// A dictionary capable of hyphenating individual words.
 ///
 /// For the purpose of hyphenation, a "word" should not be a compound in
@@ -53,10 +162,37 @@ pub trait Hyphenator<'h> {
     ///
     /// Soft hyphens take priority over dictionary hyphenation; if the word
     /// contains any, they will be returned as the only breaks available.
+    /// With the `graphemes` feature, no other break falls within an extended
+    /// grapheme cluster of the word.
+    ///
+    /// Punctuation before and after the word, as in "(word),", is set aside:
+    /// the word within is hyphenated, and its breaks offset into the token.
     ///
     /// This method is case-insensitive.
     fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, Self::Opportunity>;
 
+    /// Hyphenate a word as `hyphenate` does, writing its breaks into `breaks`,
+    /// which is cleared first. Reusing the same buffer from word to word
+    /// spares allocating the breaks of each.
+    fn hyphenate_into(&'h self, word : &str, breaks : &mut Vec<Self::Opportunity>) {
+        breaks.clear();
+        breaks.extend(self.hyphenate(word).breaks);
+    }
+
+    /// Hyphenate each of the given words as `hyphenate` does, in order. The
+    /// breaks of every word are found in a single scratch buffer, by
+    /// `hyphenate_into`, and copied out at their exact size.
+    fn hyphenate_batch<'t>(&'h self, words : &[&'t str]) -> Vec<Word<'t, Self::Opportunity>>
+    where Self::Opportunity : Clone {
+        let mut batch = Vec::with_capacity(words.len());
+        let mut scratch = vec![];
+        for &text in words {
+            self.hyphenate_into(text, &mut scratch);
+            batch.push(Word { text, breaks : scratch.to_vec() });
+        }
+        batch
+    }
+
     /// The hyphenation opportunities that our dictionary can find in the given
     /// word. The word should be lowercase.
     fn opportunities(&'h self, lowercase_word : &str) -> Vec<Self::Opportunity> {
@@ -89,32 +225,285 @@ pub trait Hyphenator<'h> {
     /// are returned.
     fn add_exact(&mut self, word : String, ops : Vec<Self::Exact>) -> Option<Vec<Self::Exact>>;
 
+    /// Specify the hyphenation of a word marked with hyphens (`-`) or equal
+    /// signs (`=`) at its breaks, such as "ta-ble", as by `add_exact`. The
+    /// word is folded like those given to `hyphenate`, and normalized to the
+    /// form of the dictionary, see `unmark_as`.
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<Self::Exact>>;
+
+    /// Forget the exact hyphenation of the given word, as it was specified to
+    /// `add_exact`, such that it is hyphenated from patterns again. The old
+    /// opportunities are returned, if any.
+    fn remove_exact(&mut self, word : &str) -> Option<Vec<Self::Exact>>;
+
+    /// Forget every exact hyphenation, including those shipped with the
+    /// dictionary.
+    fn clear_exceptions(&mut self);
+
+    /// Add the exceptions read from `reader`, one hyphen-marked word per line
+    /// as in the `.hyp.txt` files of hyph-utf8, by `add_exact_marked`. Blank
+    /// lines are skipped.
+    ///
+    /// Lines which do not hold a single word of valid UTF-8 are reported in
+    /// the returned `Import`, and the remaining lines imported regardless;
+    /// only a failure of the reader itself aborts the import.
+    fn add_exceptions_from<R>(&mut self, reader : R) -> io::Result<Import>
+    where R : BufRead, Self : Sized {
+        let mut import = Import::default();
+        for (n, line) in reader.split(b'\n').enumerate() {
+            let line = line ?;
+            let error = match str::from_utf8(&line).map(str::trim) {
+                Err(_) => "the line is not valid UTF-8",
+                Ok("") => continue,
+                Ok(word) if word.contains(char::is_whitespace) => "the line holds more than one word",
+                Ok(word) => {
+                    self.add_exact_marked(word);
+                    import.added += 1;
+                    continue
+                }
+            };
+            import.errors.push((n + 1, error));
+        }
+        Ok(import)
+    }
+
     /// The number of `char`s from the start and end of a word where breaks may
     /// not occur.
     fn unbreakable_chars(&self) -> (usize, usize);
 
+    /// The length in bytes beyond which words are left without opportunities,
+    /// bounding the work spent on pathological input such as a 100 kB token;
+    /// soft hyphens are still honored. Unbounded unless overridden.
+    fn max_length(&self) -> usize { usize::MAX }
+
+    /// The normalization form of the patterns, if any, to which words are
+    /// normalized before they are hyphenated. Words hyphenated by patterns of
+    /// no recorded form are composed into NFC. None unless overridden.
+    fn normalization(&self) -> Option<NormalizationForm> { None }
+
+    /// The language of the dictionary, by whose case mappings words are
+    /// folded before they are hyphenated, such as the dotless `ı` of Turkish.
+    /// None unless overridden.
+    fn language(&self) -> Option<Language> { None }
+
+    /// How words are folded before they are hyphenated. By their lowercase
+    /// unless overridden.
+    fn case_folding(&self) -> CaseFolding { CaseFolding::Lowercase }
+
     /// The byte indices delimiting the substring where breaks may occur, unless
-    /// the word is too short to be hyphenated.
+    /// the word is too short or too long to be hyphenated.
     fn boundaries(&self, word : &str) -> Option<(usize, usize)> {
+        if word.len() > self.max_length() {
+            return None
+        }
         let (l_min, r_min) = self.unbreakable_chars();
-        let length_min = l_min + r_min;
-        if word.chars().count() >= length_min {
-            ( word.char_indices().nth(l_min).unwrap().0
-            , word.char_indices().rev().nth(r_min.saturating_sub(1)).unwrap().0 ).into()
-        } else { None }
+        // Chars are counted and found by their leading bytes, rather than
+        // decoded; in ASCII words, every byte leads a char.
+        let ascii = word.is_ascii();
+        let starts = || word.bytes().enumerate().filter(|&(_, b)| b & 0xC0 != 0x80).map(|(i, _)| i);
+        let count = if ascii { word.len() } else { starts().count() };
+        if count < l_min + r_min {
+            return None
+        }
+        let nth = |n : usize| if ascii { Some(n).filter(|&n| n < count) } else { starts().nth(n) };
+        Some((nth(l_min) ?, nth(count.checked_sub(r_min.max(1)) ?) ?))
     }
 }
 
 
+/// A word folded, and normalized to the form of a dictionary, for
+/// hyphenation.
 #[derive(Debug, Clone)]
-struct Prepared<'t> {
-    word : Cow<'t, str>,
-    shifts : Vec<Shift>
+pub(crate) struct Prepared<'t> {
+    pub word : Cow<'t, str>,
+    shifts : Vec<Shift>,
+    expansions : Vec<(usize, usize)>,
+    alignment : Option<Vec<(usize, usize)>>,
+    clusters : Option<Vec<usize>>,
+    offset : usize,
+    end : usize
+}
+
+impl<'t> Prepared<'t> {
+    /// Whether opportunities found in the prepared word must be realigned to
+    /// be used in the original.
+    pub fn shifted(&self) -> bool {
+        !self.shifts.is_empty() || !self.expansions.is_empty() || self.alignment.is_some()
+            || self.clusters.is_some() || self.offset > 0
+    }
+
+    /// The opportunity `i` of the prepared word, index-corrected for use in
+    /// the original, unless folding or normalization left it without
+    /// counterpart there, or it falls within a grapheme cluster of the
+    /// original. Breaks are offset past the punctuation which the word was
+    /// trimmed of.
+    pub fn realign(&self, i : usize) -> Option<usize> {
+        let located = self.locate(i) ?;
+        match self.clusters {
+            Some(ref starts) if starts.binary_search(&(located - self.offset)).is_err() => None,
+            _ => Some(located)
+        }
+    }
+
+    /// The index `i` of the prepared word, located in the original as by
+    /// `realign`, whether or not it falls within a grapheme cluster.
+    fn locate(&self, i : usize) -> Option<usize> {
+        if i == self.word.len() {
+            return Some(self.end)
+        }
+        let i = match self.alignment {
+            Some(ref alignment) => denormalize(i, alignment) ?,
+            None => i
+        };
+        if within_expansion(i, &self.expansions) {
+            return None
+        }
+        Some(realign(i, &self.shifts) + self.offset)
+    }
+
+    /// The respelled opportunity `i` of the prepared word, index-corrected as
+    /// by `realign`, unless a shift or expansion falls within the bytes its
+    /// subregion replaces, which would then stand for other bytes of the
+    /// original.
+    pub fn realign_respelled(&self, i : usize, subregion : Option<&Subregion>) -> Option<usize> {
+        let realigned = self.realign(i) ?;
+        if let Some(subr) = subregion {
+            let span = subr.span(i) ?;
+            let (start, end) = (self.locate(span.start) ?, self.locate(span.end) ?);
+            if realigned - start != subr.left() || end - realigned != subr.right() {
+                return None
+            }
+        }
+        Some(realigned)
+    }
+}
+
+/// The word trimmed of the punctuation before and after it, as in "(word),",
+/// along with the byte offset of the word within the token.
+fn trim_affixes(token : &str) -> (usize, &str) {
+    let word = token.trim_start_matches(is_affix);
+    (token.len() - word.len(), word.trim_end_matches(is_affix))
 }
 
-fn prepare<'t>(text : &'t str) -> Prepared<'t> {
-    let (word, shifts) = refold(text);
-    Prepared { word, shifts }
+/// Whether the char may open or close a word without being part of it: ASCII
+/// chars other than letters and digits, and the punctuation, quotation marks
+/// and brackets of Latin-1, General Punctuation, Arabic, Devanagari and CJK.
+fn is_affix(c : char) -> bool {
+    match c {
+        c if c.is_ascii() => !c.is_ascii_alphanumeric(),
+        '\u{A1}' | '\u{A7}' | '\u{AB}' | '\u{B6}' | '\u{B7}' | '\u{BB}' | '\u{BF}' => true,
+        '\u{2010}' ..= '\u{2027}' | '\u{2030}' ..= '\u{205E}' => true,
+        '\u{60C}' | '\u{61B}' | '\u{61F}' | '\u{6D4}' | '\u{964}' | '\u{965}' => true,
+        '\u{3001}' ..= '\u{3003}' | '\u{3008}' ..= '\u{3011}' | '\u{3014}' ..= '\u{301F}' => true,
+        '\u{FF01}' ..= '\u{FF0F}' | '\u{FF1A}' ..= '\u{FF20}' | '\u{FF3B}' ..= '\u{FF40}' | '\u{FF5B}' ..= '\u{FF65}' => true,
+        _ => false
+    }
+}
+
+/// The starts of the extended grapheme clusters of the word, should any hold
+/// several chars, as a base and the combining marks which the patterns did
+/// not anticipate after it.
+#[cfg(feature = "graphemes")]
+fn clusters(text : &str) -> Option<Vec<usize>> {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    if text.is_ascii() {
+        return None
+    }
+    let starts : Vec<usize> = text.grapheme_indices(true).map(|(i, _)| i).collect();
+    if starts.len() < text.chars().count() { Some(starts) } else { None }
+}
+
+/// Without the `graphemes` feature, breaks are left where the patterns put
+/// them.
+#[cfg(not(feature = "graphemes"))]
+fn clusters(_ : &str) -> Option<Vec<usize>> { None }
+
+/// Fold the word as the dictionary expects, by its language and case folding,
+/// with its presentation ligatures and typographic apostrophes spelled plainly.
+fn fold<'t, 'h, H>(text : &'t str, dict : &H) -> Folded<'t>
+where H : Hyphenator<'h> + ?Sized {
+    match dict.case_folding() {
+        CaseFolding::Lowercase => match refold_typography_in(text, dict.language()) {
+            Some(folded) => folded,
+            None => {
+                let (word, shifts) = refold_in(text, dict.language());
+                (word, shifts, vec![])
+            }
+        },
+        CaseFolding::Full => fold_fully(text, dict.language())
+    }
+}
+
+/// The form to which words are normalized for the dictionary: that of its
+/// patterns, or else NFC, in which hyph-utf8 spells them, such that words
+/// given decomposed are matched as their composed spelling is.
+fn form<'h, H>(dict : &H) -> NormalizationForm
+where H : Hyphenator<'h> + ?Sized {
+    dict.normalization().unwrap_or(NormalizationForm::Nfc)
+}
+
+/// Trim the word of its punctuation, fold it, and normalize it to the form of
+/// the dictionary, for hyphenation.
+pub(crate) fn prepare<'t, 'h, H>(token : &'t str, dict : &H) -> Prepared<'t>
+where H : Hyphenator<'h> + ?Sized {
+    let (offset, text) = trim_affixes(token);
+    let (word, shifts, expansions) = fold(text, dict);
+    let (clusters, end) = (clusters(text), offset + text.len());
+    match normalize(&word, Some(form(dict))) {
+        Some((normalized, alignment)) => Prepared {
+            word : Cow::Owned(normalized), shifts, expansions, alignment : Some(alignment), clusters, offset, end
+        },
+        None => Prepared { word, shifts, expansions, alignment : None, clusters, offset, end }
+    }
+}
+
+/// Hyphenate a word as dictionaries do: at its soft hyphens, if any, each made
+/// an opportunity by `soft`, or else at the opportunities of the word prepared
+/// for the dictionary, each realigned onto the word by `realign`, unless it has
+/// no counterpart there.
+pub(crate) fn hyphenate_prepared<'t, 'h, H, S, F>(dict : &'h H, word : &'t str, soft : S, realign : F)
+    -> Word<'t, H::Opportunity>
+where H : Hyphenator<'h> + ?Sized
+    , S : Fn(usize) -> H::Opportunity
+    , F : Fn(H::Opportunity, &Prepared) -> Option<H::Opportunity>
+{
+    let breaks = match soft_hyphen_indices(word) {
+        Some(ops) => ops.into_iter().map(soft).collect(),
+        None => {
+            let prepared = prepare(word, dict);
+            let opportunities = dict.opportunities(&prepared.word);
+            if prepared.shifted() {
+                opportunities.into_iter().filter_map(|o| realign(o, &prepared)).collect()
+            } else { opportunities }
+        }
+    };
+
+    Word { breaks, text : word }
+}
+
+/// The opportunities of a word between the bounds `(l, r)`, given the scores
+/// of its inner indices: those at char boundaries whose score `denotes` one,
+/// each made an opportunity by `opportunity`.
+pub(crate) fn scored<'w, V, O, F>(word : &'w str, scores : Vec<V>, (l, r) : (usize, usize),
+                                  denotes : fn(V) -> bool, opportunity : F) -> impl Iterator<Item = O> + 'w
+where V : Copy + 'w, F : Fn(usize, V) -> O + 'w {
+    (1 .. word.len()).zip(scores)
+        .filter(move |&(i, v)| denotes(v) && i >= l && i <= r && word.is_char_boundary(i))
+        .map(move |(i, v)| opportunity(i, v))
+}
+
+/// Unmark a word as `unmark_in` does, as the given dictionary expects its
+/// exceptions folded and normalized.
+pub(crate) fn unmark_for<'h, H>(marked : &str, dict : &H) -> (String, Vec<usize>)
+where H : Hyphenator<'h> + ?Sized {
+    unmark_with(marked, |segment| {
+        let folded = fold(segment, dict).0;
+        match normalize(&folded, Some(form(dict))) {
+            Some((normalized, _)) => normalized,
+            None => folded.into_owned()
+        }
+    })
 }
 
 
@@ -123,29 +512,33 @@ impl<'h> Hyphenator<'h> for Standard {
     type Exact = usize;
 
     fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, Self::Opportunity> {
-        let breaks = match soft_hyphen_indices(word) {
-            Some(ops) => ops,
-            None => {
-                let Prepared { ref word, ref shifts } = prepare(word);
-                if shifts.len() > 0 {
-                    self.opportunities(word).into_iter()
-                        .map(move |o| realign(o, shifts)).collect()
-                } else { self.opportunities(word) }
-            }
-        };
+        hyphenate_prepared(self, word, |i| i, |i, prepared| prepared.realign(i))
+    }
 
-        Word { breaks, text : word }
+    fn hyphenate_into(&'h self, word : &str, breaks : &mut Vec<usize>) {
+        breaks.clear();
+        breaks.extend(word.match_indices('\u{00ad}').map(|(i, _)| i));
+        if !breaks.is_empty() {
+            return
+        }
+
+        let prepared = prepare(word, self);
+        let folded = &prepared.word;
+        let (l, r) = match self.boundaries(folded) {
+            Some(bounds) => bounds,
+            None => return
+        };
+        match self.exceptions.0.get(folded.as_ref()) {
+            Some(known) => breaks.extend(known.iter().filter(|&i| *i >= l && *i <= r)),
+            None => breaks.extend(scored(folded, self.score(folded), (l, r), Self::denotes_opportunity, |i, _| i))
+        }
+        if prepared.shifted() {
+            breaks.retain_mut(|i| prepared.realign(*i).map(|r| *i = r).is_some());
+        }
     }
 
-    fn opportunities_within(&'h self, word : &str, (l, r) : (usize, usize)) -> Vec<usize> {
-        (1 .. word.len())
-            .zip(self.score(word))
-            .filter(|&(i, v)| {
-                let valid = Self::denotes_opportunity(v);
-                let within_bounds = i >= l && i <= r;
-                let legal_index = word.is_char_boundary(i);
-                valid && within_bounds && legal_index
-            }).map(|(i, _)| i).collect()
+    fn opportunities_within(&'h self, word : &str, bounds : (usize, usize)) -> Vec<usize> {
+        scored(word, self.score(word), bounds, Self::denotes_opportunity, |i, _| i).collect()
     }
 
     #[inline]
@@ -155,10 +548,30 @@ impl<'h> Hyphenator<'h> for Standard {
 
     #[inline]
     fn add_exact(&mut self, w : String, ops : Vec<usize>) -> Option<Vec<usize>> {
-        self.exceptions.0.insert(w, ops)
+        Arc::make_mut(&mut self.exceptions).0.insert(w, ops)
+    }
+
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<usize>> {
+        let (w, ops) = unmark_for(marked, self);
+        self.add_exact(w, ops)
+    }
+
+    #[inline]
+    fn remove_exact(&mut self, w : &str) -> Option<Vec<usize>> {
+        Arc::make_mut(&mut self.exceptions).0.remove(w)
     }
 
+    #[inline] fn clear_exceptions(&mut self) { self.exceptions = Arc::default() }
+
     #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.minima }
+
+    #[inline] fn max_length(&self) -> usize { self.max_length }
+
+    #[inline] fn normalization(&self) -> Option<NormalizationForm> { self.normalization }
+
+    #[inline] fn language(&self) -> Option<Language> { Some(self.language) }
+
+    #[inline] fn case_folding(&self) -> CaseFolding { self.case_folding }
 }
 
 impl<'h> Hyphenator<'h> for Extended {
@@ -166,31 +579,39 @@ impl<'h> Hyphenator<'h> for Extended {
     type Exact = (usize, Option<Subregion>);
 
     fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, Self::Opportunity> {
-        let breaks = match soft_hyphen_indices(word) {
-            Some(ops) => ops.into_iter().map(|i| (i, None)).collect(),
-            None => {
-                let Prepared { ref word, ref shifts } = prepare(word);
-                if shifts.len() > 0 {
-                    self.opportunities(word).into_iter()
-                        .map(move |(i, subr)| (realign(i, shifts), subr)).collect()
-                } else { self.opportunities(word) }
-            }
-        };
+        hyphenate_prepared(self, word, |i| (i, None),
+            |(i, subr), prepared| prepared.realign_respelled(i, subr).map(|i| (i, subr)))
+    }
 
-        Word { breaks, text : word }
+    fn hyphenate_into(&'h self, word : &str, breaks : &mut Vec<Self::Opportunity>) {
+        breaks.clear();
+        breaks.extend(word.match_indices('\u{00ad}').map(|(i, _)| (i, None)));
+        if !breaks.is_empty() {
+            return
+        }
+
+        let prepared = prepare(word, self);
+        let folded = &prepared.word;
+        let (l, r) = match self.boundaries(folded) {
+            Some(bounds) => bounds,
+            None => return
+        };
+        match self.exceptions.0.get(folded.as_ref()) {
+            Some(known) => breaks.extend(known.iter()
+                .filter(|&&(i, _)| i >= l && i <= r)
+                .map(|&(i, ref subr)| (i, subr.as_ref()))),
+            None => breaks.extend(scored(folded, self.score(folded), (l, r), Self::denotes_opportunity,
+                                         |i, (_, subr)| (i, subr)))
+        }
+        if prepared.shifted() {
+            breaks.retain_mut(|&mut (ref mut i, subr)| prepared.realign_respelled(*i, subr).map(|r| *i = r).is_some());
+        }
     }
 
-    fn opportunities_within(&'h self, word : &str, (l, r) : (usize, usize))
+    fn opportunities_within(&'h self, word : &str, bounds : (usize, usize))
         -> Vec<Self::Opportunity>
     {
-        (1 .. word.len())
-            .zip(self.score(word))
-            .filter(|&(i, v)| {
-                let valid = Self::denotes_opportunity(v);
-                let within_bounds = i >= l && i <= r;
-                let legal_index = word.is_char_boundary(i);
-                valid && within_bounds && legal_index
-            }).map(|(i, (_, subr))| (i, subr)).collect()
+        scored(word, self.score(word), bounds, Self::denotes_opportunity, |i, (_, subr)| (i, subr)).collect()
     }
 
     #[inline]
@@ -201,8 +622,87 @@ impl<'h> Hyphenator<'h> for Extended {
 
     #[inline]
     fn add_exact(&mut self, w : String, ops : Vec<Self::Exact>) -> Option<Vec<Self::Exact>> {
-        self.exceptions.0.insert(w, ops)
+        Arc::make_mut(&mut self.exceptions).0.insert(w, ops)
+    }
+
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<Self::Exact>> {
+        let (w, ops) = unmark_for(marked, self);
+        self.add_exact(w, ops.into_iter().map(|i| (i, None)).collect())
+    }
+
+    #[inline]
+    fn remove_exact(&mut self, w : &str) -> Option<Vec<Self::Exact>> {
+        Arc::make_mut(&mut self.exceptions).0.remove(w)
     }
 
+    #[inline] fn clear_exceptions(&mut self) { self.exceptions = Arc::default() }
+
     #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.minima }
+
+    #[inline] fn max_length(&self) -> usize { self.max_length }
+
+    #[inline] fn normalization(&self) -> Option<NormalizationForm> { self.normalization }
+
+    #[inline] fn language(&self) -> Option<Language> { Some(self.language) }
+
+    #[inline] fn case_folding(&self) -> CaseFolding { self.case_folding }
+}
+
+
+/// A dictionary whose opportunities may be ranked by the level of the pattern
+/// which allowed them.
+pub trait Levels<'h> : Hyphenator<'h> {
+    /// Hyphenate a word as `hyphenate` does, pairing each opportunity with the
+    /// value of the pattern which allowed it, an odd level; the higher, the
+    /// more preferable the break. Opportunities not found by patterns, such as
+    /// soft hyphens and exact hyphenations, carry no level.
+    fn hyphenate_leveled<'t>(&'h self, word : &'t str) -> Word<'t, (Self::Opportunity, Option<u8>)>;
+}
+
+impl<'h> Levels<'h> for Standard {
+    fn hyphenate_leveled<'t>(&'h self, word : &'t str) -> Word<'t, (usize, Option<u8>)> {
+        if let Some(ops) = soft_hyphen_indices(word) {
+            return Word { breaks : ops.into_iter().map(|i| (i, None)).collect(), text : word }
+        }
+
+        let prepared = prepare(word, self);
+        let folded = &prepared.word;
+        let mut breaks : Vec<_> = match self.boundaries(folded) {
+            None => vec![],
+            Some(mins) => match self.exact_within(folded, mins) {
+                Some(known) => known.into_iter().map(|i| (i, None)).collect(),
+                None => scored(folded, self.score(folded), mins, Self::denotes_opportunity,
+                               |i, level| (i, Some(level))).collect()
+            }
+        };
+        if prepared.shifted() {
+            breaks.retain_mut(|&mut (ref mut i, _)| prepared.realign(*i).map(|r| *i = r).is_some());
+        }
+
+        Word { breaks, text : word }
+    }
+}
+
+impl<'h> Levels<'h> for Extended {
+    fn hyphenate_leveled<'t>(&'h self, word : &'t str) -> Word<'t, (Self::Opportunity, Option<u8>)> {
+        if let Some(ops) = soft_hyphen_indices(word) {
+            return Word { breaks : ops.into_iter().map(|i| ((i, None), None)).collect(), text : word }
+        }
+
+        let prepared = prepare(word, self);
+        let folded = &prepared.word;
+        let mut breaks : Vec<_> = match self.boundaries(folded) {
+            None => vec![],
+            Some(mins) => match self.exact_within(folded, mins) {
+                Some(known) => known.into_iter().map(|op| (op, None)).collect(),
+                None => scored(folded, self.score(folded), mins, Self::denotes_opportunity,
+                               |i, (level, subr)| ((i, subr), Some(level))).collect()
+            }
+        };
+        if prepared.shifted() {
+            breaks.retain_mut(|&mut ((ref mut i, subr), _)| prepared.realign_respelled(*i, subr).map(|r| *i = r).is_some());
+        }
+
+        Word { breaks, text : word }
+    }
 }
@@ -98,6 +98,53 @@ impl<'t> IntoIterator for Word<'t, (usize, Option<&'t Subregion>)> {
     }
 }
 
+impl<'t> Word<'t, usize> {
+    /// Break the word at the given opportunity, as a line breaker would: the
+    /// text before the break, ending with a hyphen, and the remainder.
+    pub fn apply(&self, opportunity : usize) -> (String, String) {
+        self.apply_marked(opportunity, "-")
+    }
+
+    /// Break the word at the given opportunity as `apply` does, ending the
+    /// text before the break with the given mark instead of a hyphen.
+    pub fn apply_marked(&self, opportunity : usize, mark : &str) -> (String, String) {
+        let (fore, aft) = self.text.split_at(opportunity);
+        ([fore, mark].concat(), aft.to_owned())
+    }
+}
+
+impl<'t> Word<'t, (usize, Option<&Subregion>)> {
+    /// Break the word at the given opportunity, as a line breaker would: the
+    /// text before the break, respelled as its subregion requires and ending
+    /// with a hyphen, and the remainder, likewise respelled.
+    pub fn apply(&self, opportunity : (usize, Option<&Subregion>)) -> (String, String) {
+        self.apply_marked(opportunity, "-")
+    }
+
+    /// Break the word at the given opportunity as `apply` does, ending the
+    /// text before the break with the given mark instead of a hyphen.
+    pub fn apply_marked(&self, opportunity : (usize, Option<&Subregion>), mark : &str) -> (String, String) {
+        let (fore, aft) = match opportunity {
+            (i, None) => (self.text[.. i].to_owned(), self.text[i ..].to_owned()),
+            (i, Some(subr)) => subr.split(self.text, i)
+                .unwrap_or_else(|| (self.text[.. i].to_owned(), self.text[i ..].to_owned()))
+        };
+        (fore + mark, aft)
+    }
+
+    /// The word as spelled when broken at each of its opportunities in turn,
+    /// before and after the break. Hungarian "asszonnyal", for one, is spelled
+    /// "asz" and "szonnyal" about its first break, to which a hyphen or other
+    /// mark may be appended.
+    pub fn spellings(&self) -> Vec<(Cow<'t, str>, Cow<'t, str>)> {
+        let text = self.text;
+        self.breaks.iter().map(|&(i, subregion)| match subregion.and_then(|subr| subr.split(text, i)) {
+            None => (Cow::Borrowed(&text[.. i]), Cow::Borrowed(&text[i ..])),
+            Some((fore, aft)) => (Cow::Owned(fore), Cow::Owned(aft))
+        }).collect()
+    }
+}
+
 
 /// An iterator over borrowed slices delimited by Standard hyphenation
 /// opportunities.
@@ -171,7 +218,7 @@ impl<'t, I> SegmentsExt<'t, I> {
     fn substitute(&mut self, text : &'t str) -> Cow<'t, str> {
         match self.queued.take() {
             None => Cow::Borrowed(text),
-            Some((skip, ref subst)) => Cow::Owned([subst, &text[skip ..]].concat())
+            Some((skip, subst)) => Cow::Owned([subst, &text[skip ..]].concat())
         }
     }
 }
@@ -190,14 +237,14 @@ where I : Iterator<Item = (usize, Option<&'t Subregion>)> {
                 self.start = Some(index);
                 Some(self.substitute(&self.text[start .. index]))
             },
-            Some((index, Some(ref subr))) => {
+            Some((index, Some(subr))) => {
                 let (start, end) = (self.start.unwrap(), index);
                 self.start = Some(index);
 
                 let (segment_start, fore) = self.queued.take().unwrap_or((start, ""));
                 let (segment_end, aft) = {
                     let (subst, queued) = subr.substitution.split_at(subr.breakpoint);
-                    if queued.len() > 0 {
+                    if !queued.is_empty() {
                         self.queued = Some((subr.right, queued));
                     }
                     (end - subr.left, subst)
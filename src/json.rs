@@ -0,0 +1,182 @@
+/*! Dictionaries in human-readable JSON
+
+Serialized dictionaries hold their patterns in an automaton, which is opaque
+to those who maintain them. With the `json` feature, the [`Json`] trait
+instead writes a dictionary as a JSON document listing its patterns, their tallies and its
+exceptions, in order, such that dictionaries can be inspected and compared
+across releases.
+
+```ignore
+use kl_hyphenate::json::Json;
+
+let json = en_us.to_json();
+fs::write("en-us.standard.json", &json) ?;
+
+// Later, perhaps edited:
+let en_us = Standard::from_json(&fs::read_to_string("en-us.standard.json") ?) ?;
+```
+
+A standard dictionary reads as follows, each locus of a tally giving the
+byte index within the pattern at which its value stands, and each exception
+the byte indices of its breaks:
+
+```json
+{
+  "language": "en-us",
+  "minima": [2, 3],
+  "provenance": {
+    "release": "tex-hyphen@49706f9cfa97f6ead26b473ec10d23d5a651318a",
+    "sources": ["hyph-en-us.pat.txt", "hyph-en-us.hyp.txt"],
+    ...
+  },
+  "normalization": null,
+  "patterns": {
+    "hyph": [{ "index": 2, "value": 3 }],
+    ...
+  },
+  "exceptions": {
+    "coassociative": [2, 6, 9],
+    ...
+  }
+}
+```
+
+[`Json`]: trait.Json.html
+*/
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use serde_json;
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::{Exceptions, Locus, Patterns, Standard, MAX_LENGTH};
+use kl_hyphenate_commons::dictionary::extended as ext;
+use kl_hyphenate_commons::normalization::{CaseFolding, NormalizationForm};
+use kl_hyphenate_commons::provenance::Provenance;
+use kl_hyphenate_commons::storage::{Automaton, Backend};
+use load::{Error, Result};
+
+
+/// Conversion of dictionaries from and to JSON.
+pub trait Json : Sized {
+    /// The dictionary as a pretty-printed JSON document.
+    fn to_json(&self) -> String;
+
+    /// Build a dictionary from a JSON document, as written by `to_json`.
+    fn from_json(json : &str) -> Result<Self>;
+}
+
+/// The JSON layout of a dictionary.
+#[derive(Serialize, Deserialize)]
+struct Document<T, E> {
+    language : String,
+    minima : (usize, usize),
+    #[serde(default)]
+    provenance : Provenance,
+    #[serde(default)]
+    normalization : Option<NormalizationForm>,
+    patterns : BTreeMap<String, T>,
+    exceptions : BTreeMap<String, E>
+}
+
+impl<T, E> Document<T, E> {
+    fn language(&self) -> Language {
+        self.language.parse().unwrap_or_else(|_| Language::other(&self.language))
+    }
+}
+
+/// Build the automaton of a set of patterns, sharing identical tallies.
+///
+/// Patterns refer to their tally by a `u16`, such that documents with more
+/// distinct tallies are reported as `Error::Malformed`.
+fn automaton<T>(patterns : BTreeMap<String, T>) -> Result<(Automaton, Vec<T>)>
+where T : Eq + Hash + Clone {
+    let mut ids = HashMap::new();
+    let mut tallies = vec![];
+    let mut pairs = Vec::with_capacity(patterns.len());
+    for (pattern, tally) in patterns {
+        let id = match ids.get(&tally) {
+            Some(&id) => id,
+            None => {
+                let id = u16::try_from(tallies.len())
+                    .map_err(|_| Error::Malformed("the document holds too many distinct tallies")) ?;
+                tallies.push(tally.clone());
+                ids.insert(tally, id);
+                id
+            }
+        };
+        pairs.push((pattern, id));
+    }
+    let automaton = Automaton::build(Backend::default(), pairs).map_err(codec) ?;
+    Ok((automaton, tallies))
+}
+
+fn codec<E>(err : E) -> Error where E : ::std::error::Error + Send + Sync + 'static {
+    Error::Codec(Box::new(err))
+}
+
+fn pretty<T, E>(document : &Document<T, E>) -> String
+where T : ::serde::Serialize, E : ::serde::Serialize {
+    serde_json::to_string_pretty(document).expect("dictionaries are always serializable as JSON")
+}
+
+impl Json for Standard {
+    fn to_json(&self) -> String {
+        pretty(&Document {
+            language : self.language.code().to_owned(),
+            minima : self.minima,
+            provenance : self.provenance.clone(),
+            normalization : self.normalization,
+            patterns : self.patterns.entries().into_iter().collect(),
+            exceptions : self.exceptions.0.iter().map(|(k, v)| (k.clone(), v)).collect()
+        })
+    }
+
+    fn from_json(json : &str) -> Result<Self> {
+        let document : Document<Vec<Locus>, Vec<usize>> = serde_json::from_str(json).map_err(codec) ?;
+        let language = document.language();
+        let (automaton, tallies) = automaton(document.patterns) ?;
+        Ok(Standard {
+            language,
+            patterns : Arc::new(Patterns { tallies, automaton }),
+            exceptions : Arc::new(Exceptions(document.exceptions.into_iter().collect())),
+            minima : document.minima,
+            provenance : document.provenance,
+            max_length : MAX_LENGTH,
+            normalization : document.normalization,
+            case_folding : CaseFolding::default()
+        })
+    }
+}
+
+impl Json for ext::Extended {
+    fn to_json(&self) -> String {
+        pretty(&Document {
+            language : self.language.code().to_owned(),
+            minima : self.minima,
+            provenance : self.provenance.clone(),
+            normalization : self.normalization,
+            patterns : self.patterns.entries().into_iter().collect(),
+            exceptions : self.exceptions.0.iter().map(|(k, v)| (k.clone(), v)).collect()
+        })
+    }
+
+    fn from_json(json : &str) -> Result<Self> {
+        let document : Document<ext::Tally, Vec<(usize, Option<ext::Subregion>)>> =
+            serde_json::from_str(json).map_err(codec) ?;
+        let language = document.language();
+        let (automaton, tallies) = automaton(document.patterns) ?;
+        Ok(ext::Extended {
+            language,
+            patterns : Arc::new(ext::Patterns { tallies, automaton }),
+            exceptions : Arc::new(ext::Exceptions(document.exceptions.into_iter().collect())),
+            minima : document.minima,
+            provenance : document.provenance,
+            max_length : MAX_LENGTH,
+            normalization : document.normalization,
+            case_folding : CaseFolding::default()
+        })
+    }
+}
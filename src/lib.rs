@@ -76,6 +76,17 @@ level of segmentation, as it affords greater control over the final result
 (such as where to break hyphen-joined compounds, or whether to set a leading
 hyphen on new lines).
 
+Hyphenation takes time loglinear in the length of a word, whatever its
+contents: patterns are matched in linear time, and breaks found within a
+word which folding shifted are realigned each by binary search. Dictionaries nonetheless leave words longer than `MAX_LENGTH`
+bytes (1 kB) unhyphenated, as no real word approaches it; services exposed
+to arbitrary input may lower the limit further.
+
+```ignore
+let en_us = en_us.with_max_length(128);
+assert!(en_us.hyphenate(&"a".repeat(100_000)).breaks.is_empty());
+```
+
 
 [`Hyphenator`]: hyphenator/trait.Hyphenator.html
 [`Standard`]: struct.Standard.html
@@ -89,17 +100,82 @@ hyphen on new lines).
 extern crate atlatl;
 extern crate bincode;
 extern crate kl_hyphenate_commons;
-
-
+#[cfg_attr(feature = "json", macro_use)]
+extern crate serde;
+extern crate xxhash_rust;
+#[cfg(feature = "textwrap")]
+extern crate textwrap;
+#[cfg(any(feature = "text", feature = "graphemes"))]
+extern crate unicode_segmentation;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "statics")]
+extern crate once_cell;
+#[cfg(feature = "memmap2")]
+extern crate memmap2;
+#[cfg(feature = "postcard")]
+extern crate postcard;
+#[cfg(feature = "cbor")]
+extern crate ciborium;
+#[cfg(feature = "compress")]
+extern crate zstd;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "detection")]
+extern crate whatlang;
+#[cfg(feature = "python")]
+extern crate pyo3;
+// The code generated by `pyo3` refers to `::core`, which the 2015 edition
+// resolves from the crate root.
+#[cfg(feature = "python")]
+extern crate core;
+
+
+pub mod archive;
+pub mod cache;
 mod case_folding;
+pub mod cjk;
+#[cfg(feature = "statics")]
+pub mod dictionaries;
+#[cfg(feature = "detection")]
+pub mod detect;
+pub mod diff;
+pub mod discretionary;
+pub mod disk;
+pub mod evaluate;
 pub mod hyphenator;
+pub mod export;
 pub mod extended;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod iter;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod load;
+pub mod negotiate;
+pub mod opportunity;
+pub mod overlay;
+pub mod packed;
+pub mod patgen;
+pub mod preprocess;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod registry;
 pub mod score;
-
-pub use kl_hyphenate_commons::Language;
+#[cfg(feature = "text")]
+pub mod stats;
+pub mod url;
+pub mod vocabulary;
+#[cfg(feature = "text")]
+pub mod text;
+#[cfg(feature = "textwrap")]
+pub mod wrap;
+
+pub use kl_hyphenate_commons::{Language, Script, Direction};
 pub use kl_hyphenate_commons::dictionary::Standard;
+pub use kl_hyphenate_commons::normalization::{CaseFolding, NormalizationForm};
 pub use hyphenator::Hyphenator;
 pub use iter::Iter;
 pub use load::Load;
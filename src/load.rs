@@ -31,57 +31,436 @@ let path = "dictionaries/en-us.standard.bincode";
 let en_us = Standard::from_path(Language::EnglishUS, path) ?;
 ```
 
+Dictionaries already in memory, such as those shipped among an application's
+own assets, are best loaded with [`from_bytes`].
+
+```norun
+let en_us = Standard::from_bytes(Language::EnglishUS, &asset_bytes) ?;
+```
+
+Dictionaries modified at runtime, for instance with exceptions of their own
+or adjusted minima, can be saved in turn with [`save`] or [`write_to`], and
+later loaded as any other.
+
+```norun
+en_us.add_exact_marked("ta-ble");
+en_us.save("dictionaries/en-us.standard.bincode") ?;
+```
+
+## Embedding
+
+With the `embed_all` feature, the dictionaries found in the `dictionaries`
+folder at build time, or those built into `OUT_DIR` by `build_dictionaries`,
+are compiled into the binary, and may be loaded without
+touching the file system. Applications which only need a few languages may
+instead enable their features, named after their codes, such as `embed-en-us`
+and `embed-de-1996`. Setting the `KL_HYPHENATE_EMBED` environment variable to
+a comma-separated list of language codes also restricts embedding to those
+languages.
+
+```norun
+let en_us = Standard::from_embedded(Language::EnglishUS) ?;
+```
+
+Since the set of embedded dictionaries is only known at build time, it can be
+enumerated at runtime with [`embedded`], for instance to disable options for
+the languages that are not supported.
+
+```norun
+let hyphenable = |lang| Standard::embedded().contains(&lang);
+```
+
+The `compress` feature embeds the dictionaries as zstd frames instead, which
+shrinks the binary several times over. Each dictionary is then decompressed
+only when it is loaded, and its decompressed bytes dropped once deserialized.
+
+## Formats
+
+Dictionaries are serialized with `bincode` by default. With the `postcard`
+and `cbor` features, they may also be stored as [postcard], which is more
+compact, or as self-describing CBOR, and converted with [`Format`]. Loading
+from a path or from bytes reads the format from the header of the dictionary.
+
+```norun
+use kl_hyphenate::load::Format;
+
+let compact = Format::Postcard.serialize(&en_us) ?;
+let en_us = Standard::from_bytes(Language::EnglishUS, &compact) ?;
+```
+
+Whatever their format, serialized dictionaries begin with a header of the
+[`MAGIC`] bytes, the [`VERSION`] of the format, a byte denoting the
+serializer and an xxh3 checksum of their contents. Dictionaries built for another version of the crate, whose layout
+may differ, are thus rejected with `Error::VersionMismatch` and should be
+rebuilt, while corrupted dictionaries are rejected with
+`Error::ChecksumMismatch`. The checksum of embedded dictionaries, which cannot
+be corrupted, is not verified.
+
+[`MAGIC`]: constant.MAGIC.html
+[`VERSION`]: constant.VERSION.html
+[postcard]: https://docs.rs/postcard
+[`Format`]: enum.Format.html
 [`Load`]: trait.Load.html
 [`from_path`]: trait.Load.html#method.from_path
+[`from_bytes`]: trait.Load.html#tymethod.from_bytes
+[`embedded`]: trait.Load.html#tymethod.embedded
+[`save`]: trait.Load.html#method.save
+[`write_to`]: trait.Load.html#tymethod.write_to
 */
 
 use bincode as bin;
+use bincode::Options;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use std::error;
 use std::fmt;
+use std::fs;
 use std::io;
+#[cfg(feature = "memmap2")]
 use std::fs::File;
 use std::path::Path;
 use std::result;
+use std::convert::TryInto;
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
 
 use kl_hyphenate_commons::Language;
 use kl_hyphenate_commons::dictionary::{Standard, Extended};
+pub use kl_hyphenate_commons::dictionary::{MAGIC, VERSION};
+use kl_hyphenate_commons::dictionary::BINCODE;
 
 /// Convenience methods for the retrieval of hyphenation dictionaries.
 pub trait Load : Sized {
     /// Read and deserialize the dictionary at the given path, verifying that it
     /// effectively belongs to the requested language.
+    ///
+    /// The format of the dictionary is detected as by `from_bytes`.
     fn from_path<P>(lang : Language, path : P) -> Result<Self>
+    where P : AsRef<Path> {
+        let bytes = fs::read(path) ?;
+        Self::from_bytes(lang, &bytes)
+    }
+
+    /// Map the dictionary at the given path into memory and deserialize it,
+    /// verifying that it effectively belongs to the requested language.
+    ///
+    /// Mapping spares the intermediate buffers of `from_path`, but the
+    /// dictionary is still deserialized into memory of its own, and the
+    /// mapping released once it is. Only serialized dictionaries are read:
+    /// those packed by `packed::pack` are rejected as `Error::Malformed`, and
+    /// should instead be mapped by `packed::Mapped`, which reads them in place
+    /// and leaves the OS to page them in lazily and share them between
+    /// processes.
+    ///
+    /// The file must not be modified while it is being loaded.
+    #[cfg(feature = "memmap2")]
+    fn from_mapped<P>(lang : Language, path : P) -> Result<Self>
     where P : AsRef<Path> {
         let file = File::open(path) ?;
-        Self::from_reader(lang, &mut io::BufReader::new(file))
+        // SAFETY: the mapping only lives for the duration of deserialization,
+        // during which the file is required not to change.
+        let map = unsafe { memmap2::Mmap::map(&file) } ?;
+        if map.starts_with(::packed::MAGIC) {
+            return Err(Error::Malformed("a packed dictionary, to be mapped by `packed::Mapped` instead"))
+        }
+        Self::from_bytes(lang, &map)
     }
 
-    /// Deserialize a dictionary from the provided reader, verifying that it
-    /// effectively belongs to the requested language.
+    /// Deserialize a `bincode` dictionary from the provided reader, verifying
+    /// that it effectively belongs to the requested language.
+    ///
+    /// Any reader will do, including trait objects such as the entries of an
+    /// archive or a decompressing stream. Since dictionaries are read in many
+    /// small pieces, unbuffered readers should be wrapped in an
+    /// `io::BufReader`.
     fn from_reader<R>(lang : Language, reader : &mut R) -> Result<Self>
-    where R : io::Read;
+    where R : io::Read + ?Sized;
 
-    /// Deserialize a dictionary from the provided reader.
+    /// Deserialize a `bincode` dictionary from the provided reader.
     fn any_from_reader<R>(reader : &mut R) -> Result<Self>
-    where R : io::Read;
+    where R : io::Read + ?Sized;
+
+    /// Deserialize a dictionary from a byte slice, such as an asset bundled by
+    /// the application, verifying that it effectively belongs to the requested
+    /// language.
+    ///
+    /// The bytes are read in place rather than through `io::Read`; only the
+    /// dictionary's own data is copied out. Their format is read from their
+    /// header with `Format::detect`.
+    fn from_bytes(lang : Language, bytes : &[u8]) -> Result<Self>;
+
+    /// Deserialize a dictionary from a byte slice as `from_bytes` does, but
+    /// without verifying its checksum. This spares hashing the dictionary
+    /// when its bytes are known to be intact, as are those embedded in the
+    /// binary.
+    fn from_trusted_bytes(lang : Language, bytes : &[u8]) -> Result<Self>;
+
+    /// Deserialize the dictionary for the given language from those embedded
+    /// in the binary, failing with `Error::NotEmbedded` if there is none.
+    fn from_embedded(lang : Language) -> Result<Self>;
+
+    /// The languages for which a dictionary of this type is embedded in the
+    /// binary, in order of their codes. Always empty unless `embed_all` or some
+    /// language's embedding feature is enabled.
+    fn embedded() -> Vec<Language>;
+
+    /// The name under which the dictionary for the given language is built,
+    /// such as `en-us.standard.bincode`.
+    fn file_name(lang : Language) -> String;
+
+    /// Serialize the dictionary as `bincode` to the provided writer, as it
+    /// stands, with any exceptions added and minima adjusted since it was
+    /// loaded.
+    fn write_to<W>(&self, writer : &mut W) -> Result<()>
+    where W : io::Write + ?Sized;
+
+    /// Serialize the dictionary as `write_to` does, to the file at the given
+    /// path, which is created or replaced.
+    fn save<P>(&self, path : P) -> Result<()>
+    where P : AsRef<Path> {
+        let mut bytes = vec![];
+        self.write_to(&mut bytes) ?;
+        fs::write(path, bytes) ?;
+        Ok(())
+    }
+}
+
+/// The folder into which the `build_dictionaries` feature writes the
+/// dictionaries it builds, within the `OUT_DIR` of this crate. Dictionaries
+/// are named as by [`Load::file_name`].
+///
+/// [`Load::file_name`]: trait.Load.html#method.file_name
+#[cfg(feature = "build_dictionaries")]
+pub const BUILT_DICTIONARIES : &str = concat!(env!("OUT_DIR"), "/dictionaries");
+
+/// The dictionaries embedded in the binary, by language and type suffix.
+#[cfg(feature = "embed")]
+static EMBEDDED : &[(Language, &str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/embedded.rs"));
+#[cfg(not(feature = "embed"))]
+static EMBEDDED : &[(Language, &str, &[u8])] = &[];
+
+/// The `bincode` configuration with which the bundled dictionaries are serialized.
+fn bincode_options() -> impl Options {
+    bin::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(5_000_000)
+}
+
+/// The formats in which dictionaries may be serialized, each denoted in the
+/// header of a dictionary by its discriminant.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum Format {
+    /// The format of the bundled dictionaries.
+    Bincode = BINCODE,
+    /// The compact `postcard` format, suited to embedded targets.
+    #[cfg(feature = "postcard")]
+    Postcard = 1,
+    /// The self-describing CBOR format, suited to external tooling.
+    #[cfg(feature = "cbor")]
+    Cbor = 2
+}
+
+impl Format {
+    /// The format of a serialized dictionary, as recorded by its header.
+    ///
+    /// Dictionaries without a header, or serialized in a format whose feature
+    /// is disabled, have none.
+    pub fn detect(bytes : &[u8]) -> Option<Format> {
+        if !bytes.starts_with(MAGIC) { return None }
+        match *bytes.get(8) ? {
+            BINCODE => Some(Format::Bincode),
+            #[cfg(feature = "postcard")]
+            1 => Some(Format::Postcard),
+            #[cfg(feature = "cbor")]
+            2 => Some(Format::Cbor),
+            _ => None
+        }
+    }
+
+    /// Serialize a dictionary in this format, behind a header bearing the
+    /// current format version and the checksum of the dictionary.
+    pub fn serialize<T : Serialize>(self, dict : &T) -> Result<Vec<u8>> {
+        let body = match self {
+            Format::Bincode => bincode_options().serialize(dict) ?,
+            #[cfg(feature = "postcard")]
+            Format::Postcard => postcard::to_allocvec(dict).map_err(|e| Error::Codec(Box::new(e))) ?,
+            #[cfg(feature = "cbor")]
+            Format::Cbor => {
+                let mut body = vec![];
+                ciborium::into_writer(dict, &mut body).map_err(|e| Error::Codec(Box::new(e))) ?;
+                body
+            }
+        };
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.push(self as u8);
+        bytes.extend_from_slice(&xxh3_64(&body).to_le_bytes());
+        bytes.extend_from_slice(&body);
+        Ok(bytes)
+    }
+
+    /// Deserialize a dictionary in this format, failing with
+    /// `Error::VersionMismatch` unless its header bears the current format
+    /// version, with `Error::Malformed` if it denotes another format, and
+    /// with `Error::ChecksumMismatch` if the dictionary was corrupted.
+    pub fn deserialize<T : DeserializeOwned>(self, bytes : &[u8]) -> Result<T> {
+        let expected = parse_header(bytes, self) ?;
+        verify_checksum(expected, xxh3_64(&bytes[HEADER_LEN ..])) ?;
+        self.deserialize_trusted(bytes)
+    }
+
+    /// Deserialize a dictionary in this format without verifying its
+    /// checksum, which is only sound for dictionaries known to be intact.
+    pub fn deserialize_trusted<T : DeserializeOwned>(self, bytes : &[u8]) -> Result<T> {
+        parse_header(bytes, self) ?;
+        let bytes = &bytes[HEADER_LEN ..];
+        match self {
+            Format::Bincode => Ok(bincode_options().deserialize(bytes) ?),
+            #[cfg(feature = "postcard")]
+            Format::Postcard => postcard::from_bytes(bytes).map_err(|e| Error::Codec(Box::new(e))),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::from_reader(bytes).map_err(|e| Error::Codec(Box::new(e)))
+        }
+    }
+}
+
+/// The length of the header of serialized dictionaries: the magic bytes, the
+/// format version, the serializer and the checksum.
+const HEADER_LEN : usize = 17;
+
+/// Ensure that the header of a serialized dictionary bears the current format
+/// version and denotes the given format, returning the checksum it records.
+fn parse_header(header : &[u8], format : Format) -> Result<u64> {
+    let found = match header.get(.. 8) {
+        Some(header) if header.starts_with(MAGIC) => Some(u32::from_le_bytes(header[4 ..].try_into().unwrap())),
+        _ => None
+    };
+    if found != Some(VERSION) {
+        return Err(Error::VersionMismatch { expected : VERSION, found })
+    }
+    match header.get(8) {
+        Some(&code) if code == format as u8 => (),
+        Some(_) => return Err(Error::Malformed("the dictionary is serialized in another format")),
+        None => return Err(Error::Malformed("the dictionary header is truncated"))
+    }
+    header.get(9 .. HEADER_LEN)
+        .map(|checksum| u64::from_le_bytes(checksum.try_into().unwrap()))
+        .ok_or(Error::Malformed("the dictionary header is truncated"))
+}
+
+/// Read the header of a `bincode` dictionary, ensuring that it bears the
+/// current format version, and return the checksum it records.
+fn read_header<R>(reader : &mut R) -> Result<u64> where R : io::Read + ?Sized {
+    use std::io::Read;
+
+    let mut header = vec![];
+    Read::take(reader, HEADER_LEN as u64).read_to_end(&mut header) ?;
+    parse_header(&header, Format::Bincode)
+}
+
+fn verify_checksum(expected : u64, found : u64) -> Result<()> {
+    if found != expected {
+        Err(Error::ChecksumMismatch { expected, found })
+    } else { Ok(()) }
+}
+
+/// A reader hashing the bytes read through it, such that the checksum of a
+/// dictionary may be verified as it is deserialized.
+struct Hashing<'r, R : 'r + ?Sized> {
+    reader : &'r mut R,
+    hasher : Xxh3
+}
+
+impl<'r, R> io::Read for Hashing<'r, R> where R : io::Read + ?Sized {
+    fn read(&mut self, buf : &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf) ?;
+        self.hasher.update(&buf[.. n]);
+        Ok(n)
+    }
+}
+
+/// Deserialize a `bincode` dictionary from a reader, verifying its header and
+/// checksum.
+///
+/// A corrupted dictionary may fail to deserialize before its checksum can be
+/// verified, in which case the rest of the reader is hashed, such that the
+/// corruption is reported as such.
+fn read_checked<D, R>(reader : &mut R) -> Result<D>
+where D : DeserializeOwned, R : io::Read + ?Sized {
+    let expected = read_header(reader) ?;
+    let mut hashing = Hashing { reader, hasher : Xxh3::new() };
+    match bincode_options().deserialize_from(&mut hashing) {
+        Ok(dict) => {
+            verify_checksum(expected, hashing.hasher.digest()) ?;
+            Ok(dict)
+        },
+        Err(err) => {
+            if io::copy(&mut hashing, &mut io::sink()).is_ok() {
+                verify_checksum(expected, hashing.hasher.digest()) ?;
+            }
+            Err(err.into())
+        }
+    }
+}
+
+/// Ensure that the dictionary belongs to the expected language.
+fn verify<D, F>(dict : D, expected : Language, language : F) -> Result<D>
+where F : Fn(&D) -> Language {
+    let found = language(&dict);
+    if found != expected {
+        Err(Error::LanguageMismatch { expected, found })
+    } else { Ok(dict) }
 }
 
 macro_rules! impl_load {
     ($dict:ty, $suffix:expr) => {
         impl Load for $dict {
             fn from_reader<R>(lang : Language, reader : &mut R) -> Result<Self>
-            where R : io::Read {
-                let dict : Self = bin::config().limit(5_000_000).deserialize_from(reader) ?;
-                let (found, expected) = (dict.language, lang);
-                if found != expected {
-                    Err(Error::LanguageMismatch { expected, found })
-                } else { Ok(dict) }
+            where R : io::Read + ?Sized {
+                let dict : Self = read_checked(reader) ?;
+                verify(dict, lang, |d| d.language)
             }
 
             fn any_from_reader<R>(reader : &mut R) -> Result<Self>
-            where R : io::Read {
-                let dict : Self = bin::config().limit(5_000_000).deserialize_from(reader) ?;
-                Ok(dict)
+            where R : io::Read + ?Sized {
+                read_checked(reader)
+            }
+
+            fn from_bytes(lang : Language, bytes : &[u8]) -> Result<Self> {
+                let dict : Self = Format::detect(bytes).unwrap_or(Format::Bincode).deserialize(bytes) ?;
+                verify(dict, lang, |d| d.language)
+            }
+
+            fn from_trusted_bytes(lang : Language, bytes : &[u8]) -> Result<Self> {
+                let dict : Self = Format::detect(bytes).unwrap_or(Format::Bincode).deserialize_trusted(bytes) ?;
+                verify(dict, lang, |d| d.language)
+            }
+
+            fn from_embedded(lang : Language) -> Result<Self> {
+                let &(_, _, bytes) = EMBEDDED.iter()
+                    .find(|&&(l, suffix, _)| l == lang && suffix == $suffix)
+                    .ok_or(Error::NotEmbedded(lang)) ?;
+                #[cfg(feature = "compress")]
+                let bytes = zstd::decode_all(bytes) ?;
+                Self::from_trusted_bytes(lang, &bytes)
+            }
+
+            fn embedded() -> Vec<Language> {
+                EMBEDDED.iter()
+                    .filter(|&&(_, suffix, _)| suffix == $suffix)
+                    .map(|&(lang, _, _)| lang)
+                    .collect()
+            }
+
+            fn file_name(lang : Language) -> String {
+                format!("{}.{}.bincode", lang.code(), $suffix)
+            }
+
+            fn write_to<W>(&self, writer : &mut W) -> Result<()>
+            where W : io::Write + ?Sized {
+                writer.write_all(&Format::Bincode.serialize(self) ?) ?;
+                Ok(())
             }
         }
     }
@@ -98,11 +477,24 @@ pub type Result<T> = result::Result<T, Error>;
 pub enum Error {
     /// The dictionary could not be deserialized.
     Deserialization(bin::Error),
+    /// The dictionary could not be serialized or deserialized in a format
+    /// other than `bincode`.
+    Codec(Box<dyn error::Error + Send + Sync>),
     /// The dictionary could not be read.
     IO(io::Error),
     /// The loaded dictionary is for the wrong language.
     LanguageMismatch { expected : Language, found : Language },
-    /// The embedded dictionary could not be retrieved.
+    /// The dictionary was serialized in another version of the format, or
+    /// before the format was versioned at all, and should be rebuilt.
+    VersionMismatch { expected : u32, found : Option<u32> },
+    /// The contents of the dictionary do not match the checksum recorded in
+    /// its header: it was corrupted, for instance by a faulty download.
+    ChecksumMismatch { expected : u64, found : u64 },
+    /// The packed dictionary is malformed, for the given reason.
+    Malformed(&'static str),
+    /// No dictionary of this type is embedded for the given language.
+    NotEmbedded(Language),
+    /// The dictionary could not be found in any of the sources searched.
     Resource
 }
 
@@ -110,6 +502,7 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match *self {
             Error::Deserialization(ref e) => Some(e),
+            Error::Codec(ref e) => Some(&**e),
             Error::IO(ref e) => Some(e),
             _ => None
         }
@@ -120,12 +513,28 @@ impl fmt::Display for Error {
     fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::Deserialization(ref e) => e.fmt(f),
+            Error::Codec(ref e) => e.fmt(f),
             Error::IO(ref e) => e.fmt(f),
             Error::LanguageMismatch { expected, found } =>
                 write!(f, "\
 Language mismatch: attempted to load a dictionary for `{}`, but found
 a dictionary for `{}` instead.", expected, found),
-            Error::Resource => f.write_str("the embedded dictionary could not be retrieved")
+            Error::VersionMismatch { expected, found : Some(found) } =>
+                write!(f, "\
+Version mismatch: the dictionary has format version {}, but this version of
+kl-hyphenate reads version {}. The dictionary should be rebuilt.", found, expected),
+            Error::VersionMismatch { expected, found : None } =>
+                write!(f, "\
+Version mismatch: the dictionary has no format header, as it predates format
+version {} or is not a dictionary at all. The dictionary should be rebuilt.", expected),
+            Error::ChecksumMismatch { expected, found } =>
+                write!(f, "\
+Checksum mismatch: the dictionary hashes to {:016x} instead of {:016x}, and
+was likely corrupted.", found, expected),
+            Error::Malformed(reason) => f.write_str(reason),
+            Error::NotEmbedded(lang) =>
+                write!(f, "no dictionary for `{}` is embedded; enable the `embed-{}` feature", lang, lang),
+            Error::Resource => f.write_str("the dictionary could not be retrieved")
         }
     }
 }
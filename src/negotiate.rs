@@ -0,0 +1,76 @@
+/*! Negotiating the language of HTTP content
+
+Web services hyphenating text on behalf of their clients generally learn of
+their preferred languages from the `Accept-Language` request header, which
+lists language ranges weighted by quality values:
+
+```text
+Accept-Language: fr-CH, fr;q=0.9, en;q=0.8, de;q=0.7, *;q=0.5
+```
+
+Given the header and the languages for which dictionaries are available, the
+[`negotiate`] function returns the best match.
+
+```ignore
+use kl_hyphenate::negotiate::negotiate;
+
+let available = Standard::embedded();
+let lang = negotiate("de-CH, en;q=0.8", &available).unwrap_or(Language::EnglishUS);
+```
+
+[`negotiate`]: fn.negotiate.html
+*/
+
+use std::cmp::Ordering;
+
+use kl_hyphenate_commons::Language;
+
+
+/// The available language which best satisfies the given `Accept-Language`
+/// header value, if any does.
+///
+/// Ranges are considered in order of decreasing quality, ties being resolved
+/// in favor of the range that appears first. Each range is mapped to the
+/// closest variant with `Language::from_tag`; should that variant be
+/// unavailable, another variant of the same language is accepted in its
+/// stead. The wildcard `*` accepts any available language. Ranges of quality
+/// zero mark languages as unacceptable, and malformed ranges are ignored.
+pub fn negotiate(accept_language : &str, available : &[Language]) -> Option<Language> {
+    let mut ranges : Vec<(&str, f32)> = accept_language.split(',').filter_map(range).collect();
+    ranges.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+
+    let excluded : Vec<Language> = ranges.iter()
+        .filter(|&&(_, q)| q == 0.0)
+        .filter_map(|&(tag, _)| Language::from_tag(tag))
+        .collect();
+    let acceptable = |lang : &Language| !excluded.contains(lang);
+
+    ranges.iter().filter(|&&(_, q)| q > 0.0).filter_map(|&(tag, _)| {
+        if tag == "*" {
+            return available.iter().cloned().find(|lang| acceptable(lang))
+        }
+
+        let exact = Language::from_tag(tag).filter(|lang| available.contains(lang));
+        exact.filter(|lang| acceptable(lang)).or_else(|| {
+            let primary = tag.split(&['-', '_'][..]).next().unwrap_or(tag).to_lowercase();
+            available.iter().cloned().filter(|lang| acceptable(lang)).find(|lang| {
+                lang.iso_639_1() == Some(primary.as_str()) || lang.iso_639_3() == Some(primary.as_str())
+            })
+        })
+    }).next()
+}
+
+/// Parse a language range and its quality value, which defaults to 1.
+fn range(item : &str) -> Option<(&str, f32)> {
+    let mut params = item.split(';').map(str::trim);
+    let tag = params.next().filter(|tag| !tag.is_empty()) ?;
+
+    let mut quality = 1.0;
+    for param in params {
+        let mut kv = param.splitn(2, '=').map(str::trim);
+        if let (Some("q"), Some(value)) = (kv.next(), kv.next()) {
+            quality = value.parse().ok().filter(|q| *q >= 0.0 && *q <= 1.0) ?;
+        }
+    }
+    Some((tag, quality))
+}
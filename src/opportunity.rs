@@ -0,0 +1,79 @@
+/*! Opportunities common to every kind of dictionary
+
+`Standard` dictionaries break words at byte indices, whereas `Extended`
+dictionaries pair each index with the subregion respelled about it; code
+which handles both would need two paths. An [`Opportunity`] holds either,
+along with the level of the pattern which allowed it, and is found alike by
+any dictionary whose opportunities are leveled, by [`hyphenate_unified`].
+
+```ignore
+use kl_hyphenate::opportunity::Unified;
+
+fn preferred<'h, H : Unified<'h>>(dict : &'h H, word : &str) -> Option<usize> {
+    let word = dict.hyphenate_unified(word);
+    word.breaks.iter().max_by_key(|op| op.level).map(|op| op.index)
+}
+
+let (english, hungarian) = (preferred(&en_us, "hyphenation"), preferred(&hu, "asszonnyal"));
+```
+
+[`Opportunity`]: struct.Opportunity.html
+[`hyphenate_unified`]: trait.Unified.html#method.hyphenate_unified
+*/
+
+use discretionary::{Discretionary, ToDiscretionary};
+use extended::Subregion;
+use hyphenator::{Levels, Offset, Word};
+
+
+/// An opportunity found by any kind of dictionary.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Opportunity<'d> {
+    /// The byte index of the break within the word.
+    pub index : usize,
+    /// The value of the pattern which allowed the break, if it was found by
+    /// patterns, as by `Levels`.
+    pub level : Option<u8>,
+    /// The respelling of the letters about the break, if any.
+    pub substitution : Option<&'d Subregion>
+}
+
+impl<'d> From<usize> for Opportunity<'d> {
+    fn from(index : usize) -> Self {
+        Opportunity { index, level : None, substitution : None }
+    }
+}
+
+impl<'d> From<(usize, Option<&'d Subregion>)> for Opportunity<'d> {
+    fn from((index, substitution) : (usize, Option<&'d Subregion>)) -> Self {
+        Opportunity { index, level : None, substitution }
+    }
+}
+
+impl<'d> Offset for Opportunity<'d> {
+    #[inline] fn offset(self, n : usize) -> Self { Opportunity { index : self.index + n, .. self } }
+}
+
+impl<'d> ToDiscretionary for Opportunity<'d> {
+    fn to_discretionary(&self, text : &str, mark : &str) -> Discretionary {
+        (self.index, self.substitution).to_discretionary(text, mark)
+    }
+}
+
+/// Dictionaries which find opportunities of the common type.
+pub trait Unified<'h> {
+    /// Hyphenate a word as `hyphenate` does, each opportunity found along with
+    /// its level.
+    fn hyphenate_unified<'t>(&'h self, word : &'t str) -> Word<'t, Opportunity<'h>>;
+}
+
+impl<'h, H> Unified<'h> for H
+where H : Levels<'h>, H::Opportunity : Into<Opportunity<'h>> {
+    fn hyphenate_unified<'t>(&'h self, word : &'t str) -> Word<'t, Opportunity<'h>> {
+        let leveled = self.hyphenate_leveled(word);
+        let breaks = leveled.breaks.into_iter()
+            .map(|(op, level)| Opportunity { level, .. op.into() })
+            .collect();
+        Word { breaks, text : leveled.text }
+    }
+}
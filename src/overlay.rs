@@ -0,0 +1,293 @@
+/*! Exceptions layered over a shared dictionary
+
+Servers handling many tenants typically share a single copy of each
+dictionary, which they cannot modify on behalf of any one tenant. An
+[`Overlay`] wraps such a shared dictionary, and keeps exceptions of its own
+in a separate layer: words are looked up in the layer first, and only then in
+the dictionary underneath.
+
+```ignore
+use kl_hyphenate::overlay::Overlay;
+
+let en_us : Arc<Standard> = registry.get(Language::EnglishUS) ?;
+let mut tenant = Overlay::new(en_us.clone());
+tenant.add_exact_marked("Kre-mer-Vos");
+tenant.remove_exact("associate");
+```
+
+Removing an exception which the dictionary underneath holds suppresses it,
+such that the word is hyphenated from patterns; the dictionary itself is
+left untouched.
+
+Modifying an `Overlay` still requires exclusive access to it. Exceptions
+which are added while other threads hyphenate, such as those entered by the
+users of an editor, are instead kept by a [`Concurrent`] overlay, whose layer
+sits behind a lock and is modified through a shared reference.
+
+```ignore
+use kl_hyphenate::overlay::Concurrent;
+
+let shared = Arc::new(Concurrent::new(en_us));
+let writer = shared.clone();
+thread::spawn(move || writer.insert_marked("Kre-mer-Vos"));
+let hyphenated = shared.hyphenate("Kremervos");
+```
+
+Only `Standard` dictionaries can underlie a `Concurrent` overlay, since the
+opportunities of extended dictionaries borrow their subregions, which a lock
+cannot lend.
+
+[`Overlay`]: struct.Overlay.html
+[`Concurrent`]: struct.Concurrent.html
+*/
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::ops::Deref;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::{Exceptions, Hasher, Standard};
+use kl_hyphenate_commons::dictionary::extended::{self as ext, Extended, Subregion};
+use kl_hyphenate_commons::normalization::{CaseFolding, NormalizationForm};
+use hyphenator::{self, hyphenate_prepared, Hyphenator, Prepared, Word};
+
+
+/// Dictionaries which may underlie an `Overlay`.
+pub trait Base {
+    /// The exceptions of the dictionary, which the overlay holds in turn.
+    type Exceptions : Clone + Debug + Default;
+}
+
+impl Base for Standard { type Exceptions = Exceptions; }
+impl Base for Extended { type Exceptions = ext::Exceptions; }
+
+/// A layer of exceptions over a dictionary `D`, held by `B`, which may be a
+/// reference, an `Arc` or any other pointer to it.
+#[derive(Clone, Debug)]
+pub struct Overlay<D : Base, B = Arc<D>> {
+    base : B,
+    /// The exceptions of the layer, which take priority over those of the
+    /// dictionary.
+    pub exceptions : D::Exceptions,
+    /// The words whose exceptions from the dictionary are suppressed.
+    suppressed : HashSet<String>,
+    /// Whether every exception from the dictionary is suppressed.
+    cleared : bool
+}
+
+impl<D, B> Overlay<D, B> where D : Base, B : Deref<Target = D> {
+    /// Lay an empty layer of exceptions over the given dictionary.
+    pub fn new(base : B) -> Self {
+        Overlay {
+            base,
+            exceptions : D::Exceptions::default(),
+            suppressed : HashSet::new(),
+            cleared : false
+        }
+    }
+
+    /// The dictionary underneath the layer.
+    pub fn base(&self) -> &D { &self.base }
+
+    /// Whether the exception which the dictionary holds for the given word, if
+    /// any, is suppressed by the layer.
+    pub fn suppresses(&self, word : &str) -> bool {
+        self.cleared || self.suppressed.contains(word)
+    }
+
+    /// Drop the layer, returning the dictionary underneath.
+    pub fn into_base(self) -> B { self.base }
+}
+
+macro_rules! impl_overlay {
+    ($dict:ty, $opportunity:ty, $exact:ty, $from_index:expr, $index:expr, $borrow:expr, $realign:expr) => {
+        impl<'h, B> Hyphenator<'h> for Overlay<$dict, B> where B : Deref<Target = $dict> {
+            type Opportunity = $opportunity;
+            type Exact = $exact;
+
+            fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, Self::Opportunity> {
+                hyphenate_prepared(self, word, $from_index, $realign)
+            }
+
+            #[inline]
+            fn opportunities_within(&'h self, word : &str, bounds : (usize, usize))
+                -> Vec<Self::Opportunity>
+            {
+                self.base.opportunities_within(word, bounds)
+            }
+
+            fn exact_within(&'h self, w : &str, (l, r) : (usize, usize))
+                -> Option<Vec<Self::Opportunity>>
+            {
+                match self.exceptions.0.get(w) {
+                    Some(ops) => Some(ops.iter()
+                        .filter(|&op| $index(op) >= l && $index(op) <= r)
+                        .map($borrow).collect()),
+                    None if self.suppresses(w) => None,
+                    None => self.base.exact_within(w, (l, r))
+                }
+            }
+
+            #[inline]
+            fn add_exact(&mut self, w : String, ops : Vec<Self::Exact>) -> Option<Vec<Self::Exact>> {
+                self.exceptions.0.insert(w, ops)
+            }
+
+            fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<Self::Exact>> {
+                let (w, ops) = hyphenator::unmark_for(marked, self);
+                self.add_exact(w, ops.into_iter().map($from_index).collect())
+            }
+
+            /// Forget the exact hyphenation of the given word, whether held by
+            /// the layer or by the dictionary underneath, which is suppressed.
+            fn remove_exact(&mut self, w : &str) -> Option<Vec<Self::Exact>> {
+                let removed = self.exceptions.0.remove(w);
+                let suppressed = if self.suppresses(w) { None } else {
+                    self.base.exceptions.0.get(w).cloned()
+                };
+                if !self.cleared {
+                    self.suppressed.insert(w.to_owned());
+                }
+                removed.or(suppressed)
+            }
+
+            /// Forget every exact hyphenation of the layer, and suppress those
+            /// of the dictionary underneath.
+            fn clear_exceptions(&mut self) {
+                self.exceptions.0.clear();
+                self.suppressed.clear();
+                self.cleared = true;
+            }
+
+            #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.base.unbreakable_chars() }
+
+            #[inline] fn max_length(&self) -> usize { self.base.max_length() }
+
+            #[inline] fn normalization(&self) -> Option<NormalizationForm> { self.base.normalization() }
+
+            #[inline] fn language(&self) -> Option<Language> { self.base.language() }
+
+            #[inline] fn case_folding(&self) -> CaseFolding { self.base.case_folding() }
+        }
+    }
+}
+
+impl_overlay! {
+    Standard, usize, usize,
+    |i| i, |&i : &usize| i, |&i : &usize| i,
+    |i, prepared : &Prepared| prepared.realign(i)
+}
+impl_overlay! {
+    Extended, (usize, Option<&'h Subregion>), (usize, Option<Subregion>),
+    |i| (i, None), |&(i, _) : &(usize, _)| i, |&(i, ref subr) : &'h (usize, Option<Subregion>)| (i, subr.as_ref()),
+    |(i, subr), prepared : &Prepared| prepared.realign_respelled(i, subr).map(|i| (i, subr))
+}
+
+
+/// A layer of exceptions over a `Standard` dictionary held by `B`, which may
+/// be modified through a shared reference while words are hyphenated.
+///
+/// The exceptions of the layer take priority over those of the dictionary;
+/// the dictionary itself is left untouched.
+#[derive(Debug)]
+pub struct Concurrent<B = Arc<Standard>> {
+    base : B,
+    exceptions : RwLock<Map>
+}
+
+type Map = HashMap<String, Vec<usize>, Hasher>;
+
+impl<B> Concurrent<B> where B : Deref<Target = Standard> {
+    /// Lay an empty layer of exceptions over the given dictionary.
+    pub fn new(base : B) -> Self {
+        Concurrent { base, exceptions : RwLock::new(Map::default()) }
+    }
+
+    /// The dictionary underneath the layer.
+    pub fn base(&self) -> &Standard { &self.base }
+
+    /// Specify the hyphenation of the given word, as by `add_exact`, through a
+    /// shared reference.
+    pub fn insert(&self, word : String, ops : Vec<usize>) -> Option<Vec<usize>> {
+        self.write().insert(word, ops)
+    }
+
+    /// Specify the hyphenation of a hyphen-marked word, as by
+    /// `add_exact_marked`, through a shared reference.
+    pub fn insert_marked(&self, marked : &str) -> Option<Vec<usize>> {
+        let (word, ops) = hyphenator::unmark_for(marked, &*self.base);
+        self.insert(word, ops)
+    }
+
+    /// Forget the exact hyphenation which the layer holds for the given word,
+    /// through a shared reference.
+    pub fn remove(&self, word : &str) -> Option<Vec<usize>> {
+        self.write().remove(word)
+    }
+
+    /// A copy of the exceptions of the layer.
+    pub fn exceptions(&self) -> Exceptions {
+        Exceptions(self.read().clone())
+    }
+
+    // The layer is left consistent by every operation, even one that panics.
+    fn read(&self) -> RwLockReadGuard<'_, Map> {
+        self.exceptions.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Map> {
+        self.exceptions.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<'h, B> Hyphenator<'h> for Concurrent<B> where B : Deref<Target = Standard> {
+    type Opportunity = usize;
+    type Exact = usize;
+
+    fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, usize> {
+        hyphenate_prepared(self, word, |i| i, |i, prepared| prepared.realign(i))
+    }
+
+    #[inline]
+    fn opportunities_within(&'h self, word : &str, bounds : (usize, usize)) -> Vec<usize> {
+        self.base.opportunities_within(word, bounds)
+    }
+
+    fn exact_within(&'h self, w : &str, (l, r) : (usize, usize)) -> Option<Vec<usize>> {
+        match self.read().get(w) {
+            Some(v) => Some(v.iter().filter(|&i| *i >= l && *i <= r).cloned().collect()),
+            None => self.base.exact_within(w, (l, r))
+        }
+    }
+
+    #[inline]
+    fn add_exact(&mut self, w : String, ops : Vec<usize>) -> Option<Vec<usize>> {
+        self.insert(w, ops)
+    }
+
+    #[inline]
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<usize>> {
+        self.insert_marked(marked)
+    }
+
+    /// Forget the exact hyphenation which the layer holds for the given word.
+    /// Those of the dictionary underneath cannot be removed.
+    #[inline]
+    fn remove_exact(&mut self, w : &str) -> Option<Vec<usize>> {
+        self.remove(w)
+    }
+
+    /// Forget every exact hyphenation of the layer.
+    #[inline] fn clear_exceptions(&mut self) { self.write().clear() }
+
+    #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.base.unbreakable_chars() }
+
+    #[inline] fn max_length(&self) -> usize { self.base.max_length() }
+
+    #[inline] fn normalization(&self) -> Option<NormalizationForm> { self.base.normalization() }
+
+    #[inline] fn language(&self) -> Option<Language> { self.base.language() }
+
+    #[inline] fn case_folding(&self) -> CaseFolding { self.base.case_folding() }
+}
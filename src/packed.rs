@@ -0,0 +1,560 @@
+/*! Dictionaries used in place, without deserialization
+
+Deserializing a dictionary rebuilds its automaton and tallies in memory,
+which dominates the time taken to load large dictionaries. A dictionary may
+instead be [`pack`]ed into a flat layout, from which a [`Packed`] dictionary
+reads its patterns directly: loading it only validates the layout, and the
+bytes may come from a memory-mapped file, an embedded asset or any other
+slice.
+
+```ignore
+use kl_hyphenate::packed::{self, Packed};
+
+let bytes = packed::pack(&en_us);
+fs::write("en-us.standard.packed", &bytes) ?;
+
+// Later, or elsewhere:
+let bytes = fs::read("en-us.standard.packed") ?;
+let en_us = Packed::from_bytes(Language::EnglishUS, &bytes) ?;
+let hyphenated = en_us.hyphenate("anfractuous");
+```
+
+Exceptions are likewise read in place, from a table sorted by word, rather
+than held in a `HashMap`; languages whose `.hyp` files list thousands of
+words thus spare the memory of their map. Exceptions added at runtime are
+kept apart, and take priority over those of the table. Only `Standard`
+dictionaries can be packed, and their provenance is left out, though their
+normalization form is kept; their case folding, never serialized, is left to
+be set anew. The layout is versioned, such
+that dictionaries packed by another version of the crate are rejected with
+`Error::VersionMismatch` rather than misread. Where even the packed layout
+cannot be held in memory, it may be queried from a file with an [`OnDisk`] dictionary.
+
+With the `memmap2` feature, a packed file may be mapped into memory as a
+[`Mapped`] dictionary, whose pages the OS reads in as patterns are looked up,
+and shares between the processes which map the same file; applications
+bundling dozens of languages thus only pay for the pages they touch.
+
+```ignore
+let mapped = Mapped::from_path(Language::EnglishUS, "en-us.standard.packed") ?;
+let en_us = mapped.packed();
+```
+
+[`pack`]: fn.pack.html
+[`Packed`]: struct.Packed.html
+[`Mapped`]: struct.Mapped.html
+[`OnDisk`]: ../disk/struct.OnDisk.html
+*/
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::convert::TryInto;
+#[cfg(feature = "memmap2")]
+use std::fs::File;
+#[cfg(feature = "memmap2")]
+use std::path::Path;
+
+use atlatl::fst::Terminal;
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::{Exceptions, Standard, MAX_LENGTH};
+use kl_hyphenate_commons::normalization::{CaseFolding, NormalizationForm};
+use kl_hyphenate_commons::storage::{Automaton, Backend, Storage};
+use hyphenator::{self, Hyphenator, Word};
+use load::{Error, Result};
+use score::{Score, Stems, Walk};
+
+
+/// The leading bytes of every packed dictionary, followed by the version of
+/// its layout as a little-endian `u32`.
+pub(crate) const MAGIC : &[u8] = b"KLHP";
+
+/// The version of the packed layout, raised whenever older packed
+/// dictionaries cease to be readable.
+pub const VERSION : u32 = 2;
+
+// The finality of transitions, as packed.
+const NOT : u8 = 0;
+pub(crate) const EMPTY : u8 = 1;
+pub(crate) const INNER : u8 = 2;
+
+/// A `Standard` dictionary read in place from its packed representation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Packed<'a> {
+    pub language : Language,
+    /// The minimum number of `char`s from the start and end of a word where
+    /// breaks may not occur.
+    pub minima : (usize, usize),
+    /// The length in bytes beyond which words are left unhyphenated, which
+    /// is `MAX_LENGTH` unless set otherwise.
+    pub max_length : usize,
+    /// The normalization form of the patterns, if any, as packed.
+    pub normalization : Option<NormalizationForm>,
+    /// How the words to hyphenate are folded, as for `Standard`.
+    pub case_folding : CaseFolding,
+    /// The exceptions added at runtime, which take priority over those of the
+    /// packed table.
+    pub exceptions : Exceptions,
+    /// The words whose packed exceptions were removed at runtime.
+    suppressed : HashSet<String>,
+    /// Whether every packed exception was removed at runtime.
+    cleared : bool,
+    /// The labels and finality of each transition, two bytes apiece.
+    stipe : &'a [u8],
+    /// The destination state of each transition, as a `u32`.
+    next : &'a [u8],
+    /// The output of each transition, as a `u16`.
+    output : &'a [u8],
+    /// The inner output of final states, as `u32` states and `u16` outputs,
+    /// sorted by state.
+    state_output : &'a [u8],
+    /// The offsets of each tally into `loci`, as `u32`s.
+    offsets : &'a [u8],
+    /// The loci of all tallies, two bytes apiece.
+    loci : &'a [u8],
+    /// The offsets of each exception into `exception_data`, as `u32`s, the
+    /// exceptions being sorted by word.
+    exception_offsets : &'a [u8],
+    /// The exceptions, each as the `u16` length of its word, the word, the
+    /// number of its breaks as a `u8` and the breaks as `u16`s.
+    exception_data : &'a [u8]
+}
+
+/// Write the dictionary in the packed layout read by `Packed`.
+pub fn pack(dict : &Standard) -> Vec<u8> {
+    let converted;
+    let fst = match dict.patterns.automaton {
+        Automaton::DoubleArray(ref fst) => fst,
+        Automaton::HashTable(_) => {
+            converted = Automaton::build(Backend::DoubleArray, dict.patterns.automaton.entries())
+                .expect("the patterns of a dictionary always build into a trie");
+            match converted { Automaton::DoubleArray(ref fst) => fst, _ => unreachable!() }
+        }
+    };
+    let mut bytes = MAGIC.to_vec();
+    put_u32(&mut bytes, VERSION);
+    put_str(&mut bytes, dict.language.code());
+    put_u32(&mut bytes, dict.minima.0 as u32);
+    put_u32(&mut bytes, dict.minima.1 as u32);
+    put_str(&mut bytes, dict.normalization.map_or("", NormalizationForm::name));
+
+    put_u32(&mut bytes, fst.da.stipe.len() as u32);
+    for stipe in &fst.da.stipe {
+        let terminal = match stipe.terminal {
+            Terminal::Not => NOT,
+            Terminal::Empty => EMPTY,
+            Terminal::Inner => INNER
+        };
+        bytes.extend_from_slice(&[stipe.check, terminal]);
+    }
+    for &next in &fst.da.next { put_u32(&mut bytes, next) }
+    for &output in &fst.da.output { bytes.extend_from_slice(&output.to_le_bytes()) }
+
+    let mut state_output : Vec<_> = fst.state_output.iter().collect();
+    state_output.sort();
+    put_u32(&mut bytes, state_output.len() as u32);
+    for (&state, &output) in state_output {
+        put_u32(&mut bytes, state);
+        bytes.extend_from_slice(&output.to_le_bytes());
+    }
+
+    let tallies = &dict.patterns.tallies;
+    put_u32(&mut bytes, tallies.len() as u32);
+    let mut offset = 0;
+    put_u32(&mut bytes, offset);
+    for tally in tallies {
+        offset += tally.len() as u32;
+        put_u32(&mut bytes, offset);
+    }
+    for locus in tallies.iter().flat_map(|tally| tally.iter()) {
+        bytes.extend_from_slice(&[locus.index, locus.value]);
+    }
+
+    // Exceptions for words too long to be packed, which do not occur in
+    // practice, are left out.
+    let mut exceptions : Vec<_> = dict.exceptions.iter()
+        .filter(|&(word, breaks)| word.len() <= u16::MAX as usize && breaks.len() <= u8::MAX as usize)
+        .collect();
+    exceptions.sort();
+    let mut data = vec![];
+    put_u32(&mut bytes, exceptions.len() as u32);
+    put_u32(&mut bytes, 0);
+    for (word, breaks) in exceptions {
+        data.extend_from_slice(&(word.len() as u16).to_le_bytes());
+        data.extend_from_slice(word.as_bytes());
+        data.push(breaks.len() as u8);
+        for &i in breaks { data.extend_from_slice(&(i as u16).to_le_bytes()) }
+        put_u32(&mut bytes, data.len() as u32);
+    }
+    bytes.extend_from_slice(&data);
+    bytes
+}
+
+/// The normalization form of a packed dictionary, given by name unless empty.
+pub(crate) fn read_normalization(name : &str) -> Result<Option<NormalizationForm>> {
+    match name {
+        "" => Ok(None),
+        name => name.parse().map(Some).map_err(|_| Error::Malformed("the packed dictionary is inconsistent"))
+    }
+}
+
+fn put_u32(bytes : &mut Vec<u8>, n : u32) {
+    bytes.extend_from_slice(&n.to_le_bytes());
+}
+
+fn put_str(bytes : &mut Vec<u8>, s : &str) {
+    put_u32(bytes, s.len() as u32);
+    bytes.extend_from_slice(s.as_bytes());
+}
+
+/// A packed dictionary mapped into memory from a file, from which `Packed`
+/// dictionaries are read in place.
+#[cfg(feature = "memmap2")]
+#[derive(Debug)]
+pub struct Mapped {
+    map : memmap2::Mmap
+}
+
+#[cfg(feature = "memmap2")]
+impl Mapped {
+    /// Map the packed dictionary at the given path, verifying that it
+    /// effectively belongs to the requested language.
+    ///
+    /// The layout is validated once, as it is mapped. The file must not be
+    /// modified as long as it is mapped.
+    pub fn from_path<P : AsRef<Path>>(lang : Language, path : P) -> Result<Self> {
+        let file = File::open(path) ?;
+        // SAFETY: the file is required not to change while it is mapped.
+        let map = unsafe { memmap2::Mmap::map(&file) } ?;
+        Packed::from_bytes(lang, &map) ?;
+        Ok(Mapped { map })
+    }
+
+    /// The dictionary, read in place from the mapping. Exceptions added at
+    /// runtime belong to each `Packed` dictionary, not to the mapping.
+    pub fn packed(&self) -> Packed<'_> {
+        Packed::read(&self.map).expect("the mapped layout was validated")
+    }
+}
+
+/// Successive reads from a packed dictionary.
+struct Fields<'a>(&'a [u8]);
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, n : usize) -> Result<&'a [u8]> {
+        if n > self.0.len() {
+            return Err(Error::Malformed("the packed dictionary is truncated"))
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn array(&mut self, count : usize, width : usize) -> Result<&'a [u8]> {
+        let n = count.checked_mul(width).ok_or(Error::Malformed("the packed dictionary is truncated")) ?;
+        self.take(n)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        self.take(4).map(|b| u32_at(b, 0))
+    }
+
+    fn str(&mut self) -> Result<&'a str> {
+        let n = self.u32() ? as usize;
+        ::std::str::from_utf8(self.take(n) ?)
+            .map_err(|_| Error::Malformed("the packed dictionary holds invalid UTF-8"))
+    }
+}
+
+#[inline]
+pub(crate) fn u32_at(bytes : &[u8], i : usize) -> u32 {
+    u32::from_le_bytes(bytes[i * 4 .. i * 4 + 4].try_into().unwrap())
+}
+
+#[inline]
+fn u16_at(bytes : &[u8], i : usize) -> u16 {
+    u16::from_le_bytes(bytes[i * 2 .. i * 2 + 2].try_into().unwrap())
+}
+
+impl<'a> Packed<'a> {
+    /// Read a packed dictionary, verifying that it effectively belongs to the
+    /// requested language.
+    pub fn from_bytes(lang : Language, bytes : &'a [u8]) -> Result<Self> {
+        let dict = Self::any_from_bytes(bytes) ?;
+        if dict.language != lang {
+            Err(Error::LanguageMismatch { expected : lang, found : dict.language })
+        } else { Ok(dict) }
+    }
+
+    /// Read a packed dictionary.
+    ///
+    /// The layout is validated, such that a malformed dictionary is reported
+    /// as `Error::Malformed` rather than mishandled during hyphenation.
+    pub fn any_from_bytes(bytes : &'a [u8]) -> Result<Self> {
+        let dict = Self::read(bytes) ?;
+        dict.validate().map(|_| dict)
+    }
+
+    /// Read the sections of a packed dictionary, without validating them.
+    fn read(bytes : &'a [u8]) -> Result<Self> {
+        let mut fields = Fields(bytes);
+        if fields.take(MAGIC.len()).ok() != Some(MAGIC) {
+            return Err(Error::Malformed("not a packed dictionary"))
+        }
+        match fields.u32() ? {
+            VERSION => (),
+            found => return Err(Error::VersionMismatch { expected : VERSION, found : Some(found) })
+        }
+        let code = fields.str() ?;
+        let language = code.parse().unwrap_or_else(|_| Language::other(code));
+        let minima = (fields.u32() ? as usize, fields.u32() ? as usize);
+        let normalization = read_normalization(fields.str() ?) ?;
+
+        let n = fields.u32() ? as usize;
+        let stipe = fields.array(n, 2) ?;
+        let next = fields.array(n, 4) ?;
+        let output = fields.array(n, 2) ?;
+        let m = fields.u32() ? as usize;
+        let state_output = fields.array(m, 6) ?;
+        let t = fields.u32() ? as usize;
+        let offsets = fields.array(t + 1, 4) ?;
+        let loci = fields.array(u32_at(offsets, t) as usize, 2) ?;
+
+        let e = fields.u32() ? as usize;
+        let exception_offsets = fields.array(e + 1, 4) ?;
+        let exception_data = fields.take(u32_at(exception_offsets, e) as usize) ?;
+
+        Ok(Packed {
+            language, minima, max_length : MAX_LENGTH, normalization, case_folding : CaseFolding::default(),
+            exceptions : Exceptions::default(), suppressed : HashSet::new(), cleared : false,
+            stipe, next, output, state_output, offsets, loci, exception_offsets, exception_data
+        })
+    }
+
+    /// Ensure that the layout can be traversed safely. Transitions leading
+    /// out of the automaton and unknown tallies are instead ignored as they
+    /// are met.
+    fn validate(&self) -> Result<()> {
+        let n = self.stipe.len() / 2;
+        let (t, k) = (self.offsets.len() / 4 - 1, self.loci.len() / 2);
+        let malformed = Err(Error::Malformed("the packed dictionary is inconsistent"));
+
+        if n == 0 { return malformed }
+        if (0 .. n).any(|i| self.stipe[2 * i + 1] > INNER) { return malformed }
+        for i in 0 .. t {
+            let (start, end) = (u32_at(self.offsets, i), u32_at(self.offsets, i + 1));
+            if start > end || end as usize > k { return malformed }
+        }
+        for i in 1 .. self.state_output.len() / 6 {
+            if self.state(i - 1) >= self.state(i) { return malformed }
+        }
+
+        let e = self.exception_offsets.len() / 4 - 1;
+        for i in 0 .. e {
+            let (start, end) = (u32_at(self.exception_offsets, i), u32_at(self.exception_offsets, i + 1));
+            if start > end { return malformed }
+            let entry = &self.exception_data[start as usize .. end as usize];
+            let n = match entry.get(.. 2) { Some(n) => u16_at(n, 0) as usize, None => return malformed };
+            match entry.get(2 + n) {
+                Some(&count) if entry.len() == 3 + n + 2 * count as usize => (),
+                _ => return malformed
+            }
+            if i > 0 && self.exception_word(i - 1) >= self.exception_word(i) { return malformed }
+        }
+        Ok(())
+    }
+
+    /// The word of the `i`th packed exception.
+    fn exception_word(&self, i : usize) -> &'a [u8] {
+        let entry = &self.exception_data[u32_at(self.exception_offsets, i) as usize ..];
+        &entry[2 .. 2 + u16_at(entry, 0) as usize]
+    }
+
+    /// The breaks of the packed exception for the given word, if any.
+    fn packed_exception(&self, word : &str) -> Option<impl Iterator<Item = usize> + 'a> {
+        let (mut lo, mut hi) = (0, self.exception_offsets.len() / 4 - 1);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.exception_word(mid).cmp(word.as_bytes()) {
+                Ordering::Less => lo = mid + 1,
+                Ordering::Greater => hi = mid,
+                Ordering::Equal => {
+                    let entry = &self.exception_data[u32_at(self.exception_offsets, mid) as usize ..];
+                    let n = 2 + u16_at(entry, 0) as usize;
+                    let breaks = &entry[n + 1 .. n + 1 + 2 * entry[n] as usize];
+                    return Some((0 .. breaks.len() / 2).map(move |i| u16_at(breaks, i) as usize))
+                }
+            }
+        }
+        None
+    }
+
+    /// The breaks of the exception which holds for the given word, whether
+    /// added at runtime or packed.
+    fn exception(&self, word : &str) -> Option<Vec<usize>> {
+        match self.exceptions.0.get(word) {
+            Some(breaks) => Some(breaks.clone()),
+            None if self.cleared || self.suppressed.contains(word) => None,
+            None => self.packed_exception(word).map(|breaks| breaks.collect())
+        }
+    }
+
+    #[inline]
+    fn state(&self, i : usize) -> u32 {
+        u32_at(&self.state_output[i * 6 ..], 0)
+    }
+
+    /// The inner output of the given final state.
+    fn inner_output(&self, state : u32) -> Option<u16> {
+        let m = self.state_output.len() / 6;
+        let (mut lo, mut hi) = (0, m);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.state(mid) {
+                s if s < state => lo = mid + 1,
+                s if s > state => hi = mid,
+                _ => return Some(u16_at(&self.state_output[mid * 6 + 4 ..], 0))
+            }
+        }
+        None
+    }
+
+    /// The tallies of all patterns that match a prefix of the query, traversed
+    /// as by the automaton's `reap`.
+    fn reap(&self, query : &[u8], mut found : impl FnMut(u16)) {
+        if let Some(tally_id) = self.root() {
+            found(tally_id);
+        }
+
+        let (mut state, mut out) = (0u32, 0u16);
+        for &label in query {
+            match self.step(state, out, label) {
+                Some((next, output, tally_id)) => {
+                    state = next;
+                    out = output;
+                    if let Some(tally_id) = tally_id {
+                        found(tally_id);
+                    }
+                },
+                None => return
+            }
+        }
+    }
+
+    /// Apply the tally matched at index `i` of the word enclosed in dots.
+    fn tally(&self, values : &mut [u8], i : usize, tally_id : u16) {
+        let hyphenable_length = values.len() + 1;
+        let tally_id = tally_id as usize;
+        if tally_id >= self.offsets.len() / 4 - 1 { return }
+        let (start, end) = (u32_at(self.offsets, tally_id), u32_at(self.offsets, tally_id + 1));
+        for locus in self.loci[2 * start as usize .. 2 * end as usize].chunks(2) {
+            let (index, value) = (locus[0], locus[1]);
+            let k = i + index as usize;
+            if k > 1 && k <= hyphenable_length && value > values[k - 2] {
+                values[k - 2] = value;
+            }
+        }
+    }
+}
+
+impl<'a> Walk for Packed<'a> {
+    // The root is final if the empty pattern is present.
+    fn root(&self) -> Option<u16> {
+        match self.stipe[1] {
+            EMPTY => Some(0),
+            INNER => self.inner_output(0),
+            _ => None
+        }
+    }
+
+    #[inline]
+    fn step(&self, state : u32, output : u16, label : u8) -> Option<(u32, u16, Option<u16>)> {
+        let e = state as usize + 1 + label as usize;
+        if e >= self.stipe.len() / 2 || self.stipe[2 * e] != label { return None }
+        let output = output.wrapping_add(u16_at(self.output, e));
+        let state = u32_at(self.next, e);
+        let found = match self.stipe[2 * e + 1] {
+            EMPTY => Some(output),
+            INNER => self.inner_output(state).map(|o| output.wrapping_add(o)),
+            _ => None
+        };
+        Some((state, output, found))
+    }
+}
+
+impl<'d, 'a> Score<'d> for Packed<'a> {
+    type Value = u8;
+
+    #[inline] fn denotes_opportunity(v : Self::Value) -> bool { v % 2 != 0 }
+
+    fn score(&'d self, word : &str) -> Vec<u8> {
+        let match_str = [".", word, "."].concat();
+        let mut values : Vec<u8> = vec![0; word.len().saturating_sub(1)];
+        for i in 0 .. match_str.len() - 1 {
+            let substring = &match_str.as_bytes()[i ..];
+            self.reap(substring, |tally_id| self.tally(&mut values, i, tally_id));
+        }
+        values
+    }
+
+    fn score_with(&'d self, word : &str, stems : &Stems) -> Vec<u8> {
+        let mut values : Vec<u8> = vec![0; word.len().saturating_sub(1)];
+        for (i, tally_id) in stems.matches(self, word, true) {
+            self.tally(&mut values, i, tally_id);
+        }
+        values
+    }
+}
+
+impl<'h, 'a> Hyphenator<'h> for Packed<'a> {
+    type Opportunity = usize;
+    type Exact = usize;
+
+    fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, Self::Opportunity> {
+        hyphenator::hyphenate_prepared(self, word, |i| i, |i, prepared| prepared.realign(i))
+    }
+
+    fn opportunities_within(&'h self, word : &str, bounds : (usize, usize)) -> Vec<usize> {
+        hyphenator::scored(word, self.score(word), bounds, Self::denotes_opportunity, |i, _| i).collect()
+    }
+
+    #[inline]
+    fn exact_within(&'h self, w : &str, (l, r) : (usize, usize)) -> Option<Vec<Self::Opportunity>> {
+        self.exception(w).map(|v| v.into_iter().filter(|&i| i >= l && i <= r).collect())
+    }
+
+    fn add_exact(&mut self, w : String, ops : Vec<usize>) -> Option<Vec<usize>> {
+        let old = self.exception(&w);
+        self.exceptions.0.insert(w, ops);
+        old
+    }
+
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<usize>> {
+        let (w, ops) = hyphenator::unmark_for(marked, self);
+        self.add_exact(w, ops)
+    }
+
+    fn remove_exact(&mut self, w : &str) -> Option<Vec<usize>> {
+        let old = self.exception(w);
+        self.exceptions.0.remove(w);
+        if !self.cleared && self.packed_exception(w).is_some() {
+            self.suppressed.insert(w.to_owned());
+        }
+        old
+    }
+
+    fn clear_exceptions(&mut self) {
+        self.exceptions.0.clear();
+        self.suppressed.clear();
+        self.cleared = true;
+    }
+
+    #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.minima }
+
+    #[inline] fn max_length(&self) -> usize { self.max_length }
+
+    #[inline] fn normalization(&self) -> Option<NormalizationForm> { self.normalization }
+
+    #[inline] fn language(&self) -> Option<Language> { Some(self.language) }
+
+    #[inline] fn case_folding(&self) -> CaseFolding { self.case_folding }
+}
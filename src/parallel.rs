@@ -0,0 +1,62 @@
+/*! Parallel hyphenation
+
+Hyphenation is embarrassingly parallel: dictionaries are only ever read
+while hyphenating, and can be shared across threads without contention.
+With the `rayon` feature enabled, the [`ParHyphenate`] trait hyphenates
+slices of words – or, with the `text` feature, of whole paragraphs – on
+rayon's global thread pool.
+
+```ignore
+use kl_hyphenate::parallel::ParHyphenate;
+
+let words = vec!["anfractuous", "inescapable", "sulphur"];
+let hyphenated = en_us.par_hyphenate(&words);
+```
+
+Results are returned in the order of their inputs. Large batches are better
+served by `par_hyphenate_batch`, which hands each thread a run of words to
+hyphenate by `Hyphenator::hyphenate_batch`.
+
+[`ParHyphenate`]: trait.ParHyphenate.html
+*/
+
+use rayon::prelude::*;
+
+use hyphenator::{Hyphenator, Offset, Word};
+#[cfg(feature = "text")]
+use text::Text;
+
+
+/// The number of words hyphenated by each task of `par_hyphenate_batch`.
+const BATCH : usize = 256;
+
+/// Hyphenation of many words or texts in parallel.
+pub trait ParHyphenate<'h> : Hyphenator<'h> + Sync {
+    /// Hyphenate each word in parallel, as by `hyphenate`.
+    fn par_hyphenate<'t>(&'h self, words : &[&'t str]) -> Vec<Word<'t, Self::Opportunity>>;
+
+    /// Hyphenate runs of words in parallel, each as by `hyphenate_batch`.
+    fn par_hyphenate_batch<'t>(&'h self, words : &[&'t str]) -> Vec<Word<'t, Self::Opportunity>>;
+
+    /// Hyphenate each text in parallel, as by `hyphenate_text`.
+    #[cfg(feature = "text")]
+    fn par_hyphenate_text<'t>(&'h self, texts : &[&'t str]) -> Vec<Word<'t, Self::Opportunity>>;
+}
+
+impl<'h, H> ParHyphenate<'h> for H
+where H : Hyphenator<'h> + Sync
+    , H::Opportunity : Send + Offset + Clone
+{
+    fn par_hyphenate<'t>(&'h self, words : &[&'t str]) -> Vec<Word<'t, Self::Opportunity>> {
+        words.par_iter().map(|word| self.hyphenate(word)).collect()
+    }
+
+    fn par_hyphenate_batch<'t>(&'h self, words : &[&'t str]) -> Vec<Word<'t, Self::Opportunity>> {
+        words.par_chunks(BATCH).flat_map(|run| self.hyphenate_batch(run)).collect()
+    }
+
+    #[cfg(feature = "text")]
+    fn par_hyphenate_text<'t>(&'h self, texts : &[&'t str]) -> Vec<Word<'t, Self::Opportunity>> {
+        texts.par_iter().map(|text| self.hyphenate_text(text)).collect()
+    }
+}
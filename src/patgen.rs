@@ -0,0 +1,199 @@
+/*! Generating patterns from hyphenated words
+
+Languages and jargons without TeX patterns may have theirs generated from a
+corpus of correctly hyphenated words, by the method of Liang's `patgen`. A
+[`Generator`] proceeds by levels: odd levels find patterns which allow breaks,
+even levels find patterns which inhibit the breaks wrongly allowed by those
+before them. At each level, patterns of every length in the given range are
+tallied over the corpus, and kept if they fix enough breaks, and spoil few
+enough, by the weights and threshold of the level.
+
+```ignore
+use kl_hyphenate::patgen::{Generator, Level};
+
+let words = fs::read_to_string("jargon.hyp") ?;
+let generator = Generator::new(vec![Level::new((1, 3), 1, 1, 1), Level::new((1, 4), 1, 1, 1)]);
+let jargon = generator.standard(Language::EnglishUS, words.lines()) ?;
+```
+
+Words are given marked with hyphens (`-`) or equal signs (`=`) at their
+breaks, as by `Hyphenator::add_exact_marked`. The default levels are those
+with which Liang generated the patterns of American English; they suit
+corpora of tens of thousands of words, whereas small corpora call for lower
+thresholds.
+
+[`Generator`]: struct.Generator.html
+*/
+
+use std::cmp;
+use std::collections::HashMap;
+use std::io;
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::Standard;
+use hyphenator;
+
+
+/// The parameters of a level of generation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Level {
+    /// The shortest and longest patterns considered, in chars, including the
+    /// dots which mark the boundaries of a word.
+    pub lengths : (usize, usize),
+    /// The weight of each break a pattern fixes.
+    pub good_weight : usize,
+    /// The weight of each break a pattern spoils.
+    pub bad_weight : usize,
+    /// The least weighted difference between fixed and spoiled breaks for
+    /// which a pattern is kept.
+    pub threshold : usize
+}
+
+impl Level {
+    pub fn new(lengths : (usize, usize), good_weight : usize, bad_weight : usize, threshold : usize) -> Self {
+        Level { lengths, good_weight, bad_weight, threshold }
+    }
+
+    fn keeps(&self, (good, bad) : (usize, usize)) -> bool {
+        good > 0 && good * self.good_weight >= bad * self.bad_weight + self.threshold
+    }
+}
+
+/// A generator of patterns, by successive levels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Generator {
+    /// The levels of generation, the first of which allows breaks.
+    pub levels : Vec<Level>,
+    /// The minimum number of chars from the start and end of a word where
+    /// breaks are neither sought nor tallied.
+    pub minima : (usize, usize)
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Generator::new(vec![
+            Level::new((2, 5), 1, 2, 20),
+            Level::new((2, 5), 2, 1, 8),
+            Level::new((2, 6), 1, 4, 7),
+            Level::new((2, 7), 1, 4, 1)
+        ])
+    }
+}
+
+/// A word of the corpus, with its breaks as positions between the chars of
+/// the dotted word.
+struct Sample {
+    dotted : Vec<char>,
+    breaks : Vec<bool>
+}
+
+/// Patterns by their letters, with their values between letters.
+type Table = HashMap<Vec<char>, Vec<u8>>;
+
+impl Generator {
+    /// A generator of the given levels, with the minima of English.
+    pub fn new(levels : Vec<Level>) -> Self {
+        Generator { levels, minima : (2, 3) }
+    }
+
+    /// The same generator, with the given minima in place of its own.
+    pub fn with_minima(self, minima : (usize, usize)) -> Self {
+        Generator { minima, .. self }
+    }
+
+    /// Generate patterns from the given hyphenated words, in TeX notation,
+    /// such as `"hy3ph"`, and sorted by their letters.
+    pub fn patterns<I, S>(&self, words : I) -> Vec<String>
+    where I : IntoIterator<Item = S>, S : AsRef<str> {
+        let samples : Vec<Sample> = words.into_iter().map(|marked| {
+            let (word, breaks) = hyphenator::unmark(marked.as_ref());
+            let mut sample = Sample { dotted : vec!['.'], breaks : vec![] };
+            for (i, c) in word.char_indices() {
+                sample.breaks.push(breaks.contains(&i));
+                sample.dotted.push(c);
+            }
+            sample.dotted.push('.');
+            sample.breaks.push(false);
+            sample
+        }).collect();
+
+        let mut table = Table::new();
+        for (n, level) in self.levels.iter().enumerate() {
+            let value = (n + 1) as u8;
+            for length in level.lengths.0 .. level.lengths.1 + 1 {
+                let tallies = self.tally(&table, &samples, value, length);
+                for ((letters, dot), tally) in tallies {
+                    if level.keeps(tally) {
+                        let values = table.entry(letters).or_insert_with(|| vec![0; length + 1]);
+                        values[dot] = cmp::max(values[dot], value);
+                    }
+                }
+            }
+        }
+
+        let mut patterns : Vec<_> = table.into_iter().collect();
+        patterns.sort();
+        patterns.into_iter().map(|(letters, values)| {
+            let mut pattern = String::new();
+            for (i, &v) in values.iter().enumerate() {
+                if v > 0 { pattern.push((b'0' + v) as char) }
+                if let Some(&c) = letters.get(i) { pattern.push(c) }
+            }
+            pattern
+        }).collect()
+    }
+
+    /// Generate a dictionary, without exceptions, from the given hyphenated
+    /// words. Its minima are those of the generator.
+    pub fn standard<I, S>(&self, language : Language, words : I) -> io::Result<Standard>
+    where I : IntoIterator<Item = S>, S : AsRef<str> {
+        let patterns = self.patterns(words);
+        Ok(Standard::from_pattern_strings(language, &patterns) ?.with_minima(self.minima))
+    }
+
+    /// Tally the breaks fixed and spoiled by each candidate pattern of the
+    /// given length and value, keyed by its letters and the position of its
+    /// value among them.
+    fn tally(&self, table : &Table, samples : &[Sample], value : u8, length : usize)
+        -> HashMap<(Vec<char>, usize), (usize, usize)>
+    {
+        let (left, right) = self.minima;
+        let longest = table.keys().map(Vec::len).max().unwrap_or(0);
+        let mut tallies : HashMap<(Vec<char>, usize), (usize, usize)> = HashMap::new();
+        for sample in samples {
+            let values = score(table, longest, &sample.dotted);
+            let chars = sample.dotted.len() - 2;
+            // A break before the `j`th char lies at position `j + 1` of the
+            // dotted word.
+            let (first, last) = (cmp::max(left, 1) + 1, chars.saturating_sub(right) + 1);
+            for (k, &current) in values.iter().enumerate().take(last + 1).skip(first) {
+                if current >= value || current % 2 == value % 2 { continue }
+                let good = sample.breaks[k - 1] == (value % 2 == 1);
+                for dot in 0 .. cmp::min(k, length) + 1 {
+                    let start = k - dot;
+                    if start + length > sample.dotted.len() { continue }
+                    let letters = sample.dotted[start .. start + length].to_vec();
+                    let tally = tallies.entry((letters, dot)).or_insert((0, 0));
+                    if good { tally.0 += 1 } else { tally.1 += 1 }
+                }
+            }
+        }
+        tallies
+    }
+}
+
+/// The values of the given patterns, none longer than `longest`, at each
+/// position of a dotted word, the position `k` lying before its `k`th char.
+fn score(table : &Table, longest : usize, dotted : &[char]) -> Vec<u8> {
+    let mut values = vec![0; dotted.len() + 1];
+    for start in 0 .. dotted.len() {
+        for end in start + 1 .. cmp::min(start + longest, dotted.len()) + 1 {
+            if let Some(pattern) = table.get(&dotted[start .. end]) {
+                for (i, &v) in pattern.iter().enumerate() {
+                    values[start + i] = cmp::max(values[start + i], v);
+                }
+            }
+        }
+    }
+    values
+}
@@ -0,0 +1,188 @@
+/*! Preparation of words by the caller
+
+Dictionaries fold and normalize the words they hyphenate as their patterns
+expect, but specialized pipelines need preparation of their own: the output
+of OCR may spell the long `ſ` of old prints, or confuse `rn` with `m`, and
+transliterated text must be respelled before any pattern matches it. A
+[`Preprocessed`] dictionary transforms every word by a [`Preprocessor`] of
+the caller's before the dictionary underneath hyphenates it, and realigns the
+breaks found onto the word as it was given, by the shifts which the
+transformation reports.
+
+```ignore
+use kl_hyphenate::preprocess::{self, Preprocessed};
+
+let modern = Preprocessed::new(en_gb, |word : &str| preprocess::map_chars(word, |c, mapped| match c {
+    'ſ' => mapped.push('s'),
+    c => mapped.push(c)
+}));
+let word = modern.hyphenate("poſſeſſion");
+```
+
+Transformations which turn single chars into several, or several into one,
+are best written by [`map_chars`], which reports the shifts they induce and
+the bounds of the chars expanded to several. Breaks found within such chars,
+or which the shifts realign within a char of the original, or past either
+end of it, are dropped, as are respellings whose subregion spans a shift.
+
+[`Preprocessed`]: struct.Preprocessed.html
+[`Preprocessor`]: trait.Preprocessor.html
+[`map_chars`]: fn.map_chars.html
+*/
+
+use std::borrow::Cow;
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::normalization::{CaseFolding, NormalizationForm};
+use case_folding::{self, realign, within_expansion};
+use extended::Subregion;
+use hyphenator::{Hyphenator, Word};
+
+pub use case_folding::Shift;
+
+
+/// A transformation of the words to hyphenate, such as the cleanup of OCR
+/// output, or a transliteration.
+pub trait Preprocessor {
+    /// The word transformed for hyphenation, along with the shifts induced by
+    /// the transformation, ordered by index into the transformed word, and
+    /// the bounds of the chars of the transformed word which stand for a
+    /// single char of the original, in order.
+    fn preprocess<'t>(&self, word : &'t str) -> (Cow<'t, str>, Vec<Shift>, Vec<(usize, usize)>);
+}
+
+impl<F> Preprocessor for F where F : Fn(&str) -> (String, Vec<Shift>, Vec<(usize, usize)>) {
+    fn preprocess<'t>(&self, word : &'t str) -> (Cow<'t, str>, Vec<Shift>, Vec<(usize, usize)>) {
+        let (transformed, shifts, expansions) = self(word);
+        (Cow::Owned(transformed), shifts, expansions)
+    }
+}
+
+/// The word transformed one char at a time, each of its chars pushed onto the
+/// transformed word by `map`, along with the shifts induced by those whose
+/// transformation differs in length, and the bounds of those transformed
+/// into several chars.
+pub fn map_chars<F>(word : &str, map : F) -> (String, Vec<Shift>, Vec<(usize, usize)>)
+where F : Fn(char, &mut String) {
+    case_folding::fold_chars(word, map)
+}
+
+/// Opportunities which can be moved from a transformed word back onto the
+/// original.
+pub trait Realign : Sized {
+    /// This opportunity of the transformed word, moved onto the original by
+    /// the given shifts, unless it falls within one of the given expansions.
+    fn realign(self, shifts : &[Shift], expansions : &[(usize, usize)]) -> Option<Self>;
+
+    /// The byte index of the opportunity.
+    fn index(&self) -> usize;
+}
+
+impl Realign for usize {
+    fn realign(self, shifts : &[Shift], expansions : &[(usize, usize)]) -> Option<Self> {
+        if within_expansion(self, expansions) { None } else { Some(realign(self, shifts)) }
+    }
+
+    #[inline] fn index(&self) -> usize { *self }
+}
+
+/// Respelled opportunities are dropped unless their subregion is realigned
+/// as a whole, with neither end within an expansion nor any shift within.
+impl Realign for (usize, Option<&Subregion>) {
+    fn realign(self, shifts : &[Shift], expansions : &[(usize, usize)]) -> Option<Self> {
+        let (i, subregion) = self;
+        let realigned = i.realign(shifts, expansions) ?;
+        if let Some(subr) = subregion {
+            let span = subr.span(i) ?;
+            let start = span.start.realign(shifts, expansions) ?;
+            let end = span.end.realign(shifts, expansions) ?;
+            if realigned.checked_sub(start) != Some(subr.left) || end.checked_sub(realigned) != Some(subr.right) {
+                return None
+            }
+        }
+        Some((realigned, subregion))
+    }
+
+    #[inline] fn index(&self) -> usize { self.0 }
+}
+
+
+/// A dictionary which hyphenates the words it is given as transformed by a
+/// preprocessor.
+#[derive(Clone, Debug)]
+pub struct Preprocessed<H, P> {
+    inner : H,
+    preprocessor : P
+}
+
+impl<H, P> Preprocessed<H, P> where P : Preprocessor {
+    /// Transform the words hyphenated by `inner` by the given preprocessor.
+    pub fn new(inner : H, preprocessor : P) -> Self {
+        Preprocessed { inner, preprocessor }
+    }
+
+    /// The dictionary underneath the preprocessor.
+    pub fn inner(&self) -> &H { &self.inner }
+
+    /// Drop the preprocessor, returning the dictionary underneath.
+    pub fn into_inner(self) -> H { self.inner }
+}
+
+impl<'h, H, P> Hyphenator<'h> for Preprocessed<H, P>
+where H : Hyphenator<'h>, H::Opportunity : Realign, P : Preprocessor {
+    type Opportunity = H::Opportunity;
+    type Exact = H::Exact;
+
+    fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, Self::Opportunity> {
+        let (transformed, shifts, expansions) = self.preprocessor.preprocess(word);
+        let breaks = self.inner.hyphenate(&transformed).breaks.into_iter()
+            .filter_map(|o| o.realign(&shifts, &expansions))
+            .filter(|o| o.index() > 0 && o.index() < word.len() && word.is_char_boundary(o.index()))
+            .collect();
+
+        Word { breaks, text : word }
+    }
+
+    #[inline]
+    fn opportunities_within(&'h self, word : &str, bounds : (usize, usize)) -> Vec<Self::Opportunity> {
+        self.inner.opportunities_within(word, bounds)
+    }
+
+    #[inline]
+    fn exact_within(&'h self, word : &str, bounds : (usize, usize)) -> Option<Vec<Self::Opportunity>> {
+        self.inner.exact_within(word, bounds)
+    }
+
+    #[inline]
+    fn add_exact(&mut self, word : String, ops : Vec<Self::Exact>) -> Option<Vec<Self::Exact>> {
+        self.inner.add_exact(word, ops)
+    }
+
+    /// Specify the hyphenation of a marked word as the dictionary underneath
+    /// does, once transformed by the preprocessor, as the words it stands for
+    /// are.
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<Self::Exact>> {
+        let (transformed, _, _) = self.preprocessor.preprocess(marked);
+        self.inner.add_exact_marked(&transformed)
+    }
+
+    #[inline]
+    fn remove_exact(&mut self, word : &str) -> Option<Vec<Self::Exact>> {
+        self.inner.remove_exact(word)
+    }
+
+    #[inline]
+    fn clear_exceptions(&mut self) {
+        self.inner.clear_exceptions()
+    }
+
+    #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.inner.unbreakable_chars() }
+
+    #[inline] fn max_length(&self) -> usize { self.inner.max_length() }
+
+    #[inline] fn normalization(&self) -> Option<NormalizationForm> { self.inner.normalization() }
+
+    #[inline] fn language(&self) -> Option<Language> { self.inner.language() }
+
+    #[inline] fn case_folding(&self) -> CaseFolding { self.inner.case_folding() }
+}
@@ -0,0 +1,103 @@
+/*! Python bindings
+
+With the `python` feature, the crate doubles as the Python extension module
+`hyphenation_py`, which shares the dictionaries and the behavior of the Rust
+API. The module is built as a shared object, under the name Python expects:
+
+```text
+cargo rustc --release --features python,pyo3/extension-module --crate-type cdylib
+cp target/release/libkl_hyphenate.so hyphenation_py.so
+```
+
+A [`Dictionary`] is loaded from a serialized standard dictionary, and
+hyphenates words or wraps whole paragraphs:
+
+```python
+from hyphenation_py import Dictionary
+
+en_us = Dictionary("en-us", "dictionaries/en-us.standard.bincode")
+en_us.hyphenate("anfractuous")        # "an-frac-tu-ous"
+en_us.hyphenate("anfractuous", "\xad") # with soft hyphens
+en_us.segments("anfractuous")         # ["an", "frac", "tu", "ous"]
+en_us.breaks("anfractuous")           # [2, 6, 8]
+en_us.wrap("Hyphenation is accomplished by the dictionary", 18)
+```
+
+Unlike those of the Rust API, breaks are given as indices of code points,
+such that they index Python strings directly.
+
+[`Dictionary`]: struct.Dictionary.html
+*/
+
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use textwrap::{self, Options};
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::Standard;
+use hyphenator::Hyphenator;
+use load::{Error, Load};
+use wrap::Splitter;
+
+
+/// A standard dictionary, as a Python class.
+#[pyclass(frozen, module = "hyphenation_py")]
+#[derive(Clone, Debug)]
+pub struct Dictionary(pub Standard);
+
+fn py_error(err : Error) -> PyErr {
+    match err {
+        Error::IO(err) => PyIOError::new_err(err.to_string()),
+        err => PyValueError::new_err(err.to_string())
+    }
+}
+
+#[pymethods]
+impl Dictionary {
+    /// Load the standard dictionary for the language of the given code from
+    /// the serialized file at `path`.
+    #[new]
+    fn new(language : &str, path : &str) -> PyResult<Self> {
+        let lang = language.parse().unwrap_or_else(|_| Language::other(language));
+        Standard::from_path(lang, path).map(Dictionary).map_err(py_error)
+    }
+
+    /// The code of the dictionary's language.
+    #[getter]
+    fn language(&self) -> &str { self.0.language.code() }
+
+    /// The breaks of the word, as indices of code points.
+    fn breaks(&self, word : &str) -> Vec<usize> {
+        let breaks = self.0.hyphenate(word).breaks;
+        let mut chars = word.char_indices().enumerate();
+        breaks.into_iter().filter_map(|i| chars.find(|&(_, (j, _))| j == i).map(|(n, _)| n)).collect()
+    }
+
+    /// The segments of the hyphenated word.
+    fn segments(&self, word : &str) -> Vec<String> {
+        self.0.hyphenate(word).into_iter().segments().map(str::to_owned).collect()
+    }
+
+    /// The word, its segments joined by `marker`.
+    #[pyo3(signature = (word, marker = "-"))]
+    fn hyphenate(&self, word : &str, marker : &str) -> String {
+        self.0.hyphenate(word).into_iter().segments().collect::<Vec<_>>().join(marker)
+    }
+
+    /// The lines of the text wrapped at the given width, hyphenating words
+    /// which overflow it.
+    fn wrap(&self, text : &str, width : usize) -> Vec<String> {
+        let options = Options::new(width).word_splitter(Splitter(self.0.clone()));
+        textwrap::wrap(text, &options).into_iter().map(|line| line.into_owned()).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Dictionary({:?})", self.0.language.code())
+    }
+}
+
+/// The `hyphenation_py` extension module.
+#[pymodule]
+fn hyphenation_py(module : &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Dictionary>()
+}
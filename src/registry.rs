@@ -0,0 +1,340 @@
+/*! Loading dictionaries on demand
+
+Applications handling many languages, such as long-running servers, need not
+load every dictionary up front. A [`Registry`] resolves languages to their
+dictionaries the first time they are requested, and caches the result for
+subsequent requests. Dictionaries are shared behind an `Arc`, so that a
+single registry may serve several threads.
+
+```ignore
+use kl_hyphenate::registry::{Registry, Source};
+
+let registry : Registry<Standard> = Registry::new()
+    .source(Source::Embedded)
+    .source(Source::Dir("/usr/share/kl-hyphenate".into()));
+
+let en_us = registry.get(Language::EnglishUS) ?;
+let hyphenated = en_us.hyphenate("anfractuous");
+```
+
+With the `async` feature, dictionaries may also be fetched asynchronously,
+for instance from object storage, without blocking the executor. The
+returned [`Fetch`] future is not tied to any particular runtime. Concurrent
+requests for the same language share a single fetch.
+
+```ignore
+let en_us = registry.fetch(Language::EnglishUS, |lang| {
+    bucket.get(format!("dictionaries/{}", Standard::file_name(lang)))
+}).await ?;
+```
+
+[`Registry`]: struct.Registry.html
+[`Fetch`]: struct.Fetch.html
+*/
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll, Waker};
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::Standard;
+use load::{self, Load, Error};
+
+
+/// A place where dictionaries may be found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// The dictionaries embedded in the binary, see `load`.
+    Embedded,
+    /// A directory of dictionaries, named after their language code and type
+    /// as they are built, e.g. `en-us.standard.bincode`.
+    Dir(PathBuf)
+}
+
+/// A cache of dictionaries, loaded on demand from a list of sources.
+#[derive(Debug)]
+pub struct Registry<D = Standard> {
+    sources : Vec<Source>,
+    cache : Mutex<HashMap<Language, Arc<D>>>,
+    #[cfg(feature = "async")]
+    pending : Mutex<HashMap<Language, Arc<Pending<D>>>>
+}
+
+impl<D> Default for Registry<D> {
+    fn default() -> Self {
+        Registry {
+            sources : vec![],
+            cache : Mutex::new(HashMap::new()),
+            #[cfg(feature = "async")]
+            pending : Mutex::new(HashMap::new())
+        }
+    }
+}
+
+impl<D> Registry<D> where D : Load {
+    /// Build a registry without any source.
+    pub fn new() -> Self { Self::default() }
+
+    /// Add a source to the registry. Sources are searched in the order in which
+    /// they were added.
+    pub fn source(mut self, source : Source) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// The dictionary for the given language, loading it from the first source
+    /// that provides it unless it was loaded already.
+    ///
+    /// Failures are not cached: a subsequent request for the same language will
+    /// search the sources anew. Should no source provide the dictionary, this
+    /// fails with `Error::Resource`.
+    pub fn get(&self, lang : Language) -> load::Result<Arc<D>> {
+        if let Some(dict) = self.lock().get(&lang) {
+            return Ok(dict.clone())
+        }
+
+        // Dictionaries are loaded without holding the lock, so that requests
+        // for other languages are not held up in the meantime.
+        let dict = Arc::new(self.load(lang) ?);
+        Ok(self.lock().entry(lang).or_insert(dict).clone())
+    }
+
+    /// The languages for which some source provides a dictionary, in order.
+    pub fn available(&self) -> Vec<Language> {
+        let mut languages = vec![];
+        for source in &self.sources {
+            match *source {
+                Source::Embedded => languages.extend(D::embedded()),
+                Source::Dir(ref dir) => {
+                    let names = fs::read_dir(dir).into_iter().flatten().flatten()
+                        .filter_map(|entry| entry.file_name().into_string().ok());
+                    languages.extend(names.filter_map(|name| {
+                        let code = name.split('.').next() ?;
+                        code.parse().ok().filter(|&lang| D::file_name(lang) == name)
+                    }));
+                }
+            }
+        }
+        languages.sort();
+        languages.dedup();
+        languages
+    }
+
+    /// The languages whose dictionaries are currently loaded.
+    pub fn loaded(&self) -> Vec<Language> {
+        let mut languages : Vec<Language> = self.lock().keys().cloned().collect();
+        languages.sort();
+        languages
+    }
+
+    /// Drop the registry's copy of the dictionary for the given language, which
+    /// will be loaded anew if requested again. Copies already handed out remain
+    /// valid.
+    pub fn evict(&self, lang : Language) -> Option<Arc<D>> {
+        self.lock().remove(&lang)
+    }
+
+    fn load(&self, lang : Language) -> load::Result<D> {
+        for source in &self.sources {
+            let loaded = match *source {
+                Source::Embedded => D::from_embedded(lang),
+                Source::Dir(ref dir) => D::from_path(lang, dir.join(D::file_name(lang)))
+            };
+            match loaded {
+                Err(Error::NotEmbedded(_)) => continue,
+                Err(Error::IO(ref e)) if e.kind() == io::ErrorKind::NotFound => continue,
+                result => return result
+            }
+        }
+        Err(Error::Resource)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Language, Arc<D>>> {
+        relock(&self.cache)
+    }
+}
+
+/// Lock a mutex of the registry. Its contents are left consistent by every
+/// operation, even one that panics.
+fn relock<T>(mutex : &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+
+// Asynchronous loading
+
+#[cfg(feature = "async")]
+impl<D> Registry<D> where D : Load {
+    /// The dictionary for the given language, fetching its serialized bytes
+    /// with `fetch` unless it was loaded already.
+    ///
+    /// Should another request for the same language be under way, the fetch
+    /// is skipped and the dictionary awaited instead. Fetches which fail, or
+    /// are dropped before completion, are not shared: the requests awaiting
+    /// them proceed to fetch the dictionary themselves.
+    ///
+    /// The sources of the registry are not consulted, since reading them
+    /// would block.
+    pub fn fetch<F, Fut>(&self, lang : Language, fetch : F) -> Fetch<'_, D, F, Fut>
+    where F : FnOnce(Language) -> Fut
+        , Fut : Future<Output = io::Result<Vec<u8>>>
+    {
+        Fetch { registry : self, lang, fetch : Some(fetch), state : State::Start }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<D> Registry<D> {
+    /// Publish the outcome of a fetch to the requests awaiting it.
+    fn settle(&self, lang : Language, pending : &Arc<Pending<D>>, outcome : Option<Arc<D>>) {
+        if let Some(ref dict) = outcome {
+            relock(&self.cache).insert(lang, dict.clone());
+        }
+        {
+            let mut in_flight = relock(&self.pending);
+            if in_flight.get(&lang).is_some_and(|p| Arc::ptr_eq(p, pending)) {
+                in_flight.remove(&lang);
+            }
+        }
+        *relock(&pending.status) = match outcome {
+            Some(dict) => Status::Loaded(dict),
+            None => Status::Failed
+        };
+        let wakers : Vec<Waker> = relock(&pending.wakers).drain(..).collect();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// A fetch under way, awaited by concurrent requests.
+#[cfg(feature = "async")]
+#[derive(Debug)]
+struct Pending<D> {
+    status : Mutex<Status<D>>,
+    wakers : Mutex<Vec<Waker>>
+}
+
+#[cfg(feature = "async")]
+#[derive(Debug)]
+enum Status<D> {
+    Loading,
+    Loaded(Arc<D>),
+    Failed
+}
+
+#[cfg(feature = "async")]
+enum State<D, Fut> {
+    Start,
+    Waiting(Arc<Pending<D>>),
+    Driving(Pin<Box<Fut>>, Arc<Pending<D>>),
+    Done
+}
+
+/// A future resolving to a dictionary of the registry, as returned by
+/// `Registry::fetch`.
+#[cfg(feature = "async")]
+pub struct Fetch<'r, D : 'r, F, Fut> {
+    registry : &'r Registry<D>,
+    lang : Language,
+    fetch : Option<F>,
+    state : State<D, Fut>
+}
+
+// The inner future is pinned in its own allocation, and nothing else is
+// structurally pinned.
+#[cfg(feature = "async")]
+impl<'r, D, F, Fut> Unpin for Fetch<'r, D, F, Fut> {}
+
+#[cfg(feature = "async")]
+impl<'r, D, F, Fut> Future for Fetch<'r, D, F, Fut>
+where D : Load
+    , F : FnOnce(Language) -> Fut
+    , Fut : Future<Output = io::Result<Vec<u8>>>
+{
+    type Output = load::Result<Arc<D>>;
+
+    fn poll(self : Pin<&mut Self>, cx : &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let (registry, lang) = (this.registry, this.lang);
+        loop {
+            match this.state {
+                State::Start => {
+                    // The cache is checked while holding the list of pending
+                    // fetches, which are only removed once their dictionary
+                    // is cached.
+                    let mut in_flight = relock(&registry.pending);
+                    if let Some(dict) = registry.lock().get(&lang) {
+                        this.state = State::Done;
+                        return Poll::Ready(Ok(dict.clone()))
+                    }
+                    this.state = match in_flight.get(&lang) {
+                        Some(pending) => State::Waiting(pending.clone()),
+                        None => {
+                            let pending = Arc::new(Pending {
+                                status : Mutex::new(Status::Loading),
+                                wakers : Mutex::new(vec![])
+                            });
+                            in_flight.insert(lang, pending.clone());
+                            let fetch = this.fetch.take().expect("`Fetch` polled after completion");
+                            State::Driving(Box::pin(fetch(lang)), pending)
+                        }
+                    };
+                },
+                State::Waiting(ref pending) => {
+                    // Wakers are registered before the status is read, lest
+                    // the fetch settle in between.
+                    {
+                        let mut wakers = relock(&pending.wakers);
+                        if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                            wakers.push(cx.waker().clone());
+                        }
+                    }
+                    let status = match *relock(&pending.status) {
+                        Status::Loading => return Poll::Pending,
+                        Status::Loaded(ref dict) => Some(dict.clone()),
+                        Status::Failed => None
+                    };
+                    match status {
+                        Some(dict) => {
+                            this.state = State::Done;
+                            return Poll::Ready(Ok(dict))
+                        },
+                        None => this.state = State::Start
+                    }
+                },
+                State::Driving(ref mut fetch, ref pending) => {
+                    let bytes = match fetch.as_mut().poll(cx) {
+                        Poll::Pending => return Poll::Pending,
+                        Poll::Ready(bytes) => bytes
+                    };
+                    let loaded = bytes.map_err(Error::from)
+                        .and_then(|bytes| D::from_bytes(lang, &bytes))
+                        .map(Arc::new);
+                    registry.settle(lang, pending, loaded.as_ref().ok().cloned());
+                    this.state = State::Done;
+                    return Poll::Ready(loaded)
+                },
+                State::Done => panic!("`Fetch` polled after completion")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<'r, D, F, Fut> Drop for Fetch<'r, D, F, Fut> {
+    fn drop(&mut self) {
+        // A fetch abandoned midway must release the requests awaiting it.
+        if let State::Driving(_, ref pending) = self.state {
+            self.registry.settle(self.lang, pending, None);
+        }
+    }
+}
@@ -34,10 +34,40 @@ word boundaries, we find:
 By convention, even values inhibit hyphenation, whereas odd values mark
 valid breaks. Thus, having matched these patterns, the dictionary will
 offer "fir·kin" as a valid hyphenation.
+
+Patterns are matched against the UTF-8 bytes of the word, one transition of
+the automaton per byte, and the values are likewise indexed by byte; chars
+are never decoded, and only breaks at char boundaries are retained.
+
+## Shared stems
+
+Inflected and derived words share their stem, whose patterns are matched
+anew whenever any of them is scored. A [`Stems`] memo keeps the matches found
+within the stems of recently scored words, along with the walks of the
+automaton still under way at their end, so that "hyphenated" resumes from
+"hyphenate" rather than from scratch; the score is the same either way.
+Only patterns held in the double-array backend are memoized; the others are
+scored from scratch.
+
+```ignore
+use kl_hyphenate::score::{Score, Stems};
+
+let stems = Stems::new(10_000);
+let hyphenate = en_us.score_with("hyphenate", &stems);
+let hyphenated = en_us.score_with("hyphenated", &stems);
+```
+
+[`Stems`]: struct.Stems.html
 */
 
+use std::iter;
+use std::sync::{Mutex, MutexGuard};
+
+use atlatl::fst::{FST, Output, Terminal};
 use kl_hyphenate_commons::dictionary::*;
 use kl_hyphenate_commons::dictionary::extended::*;
+use kl_hyphenate_commons::storage::{Automaton, Storage};
+use cache::Lru;
 
 
 /// Methods to evaluate each index in a string as an opportunity for hyphenation.
@@ -54,6 +84,11 @@ pub trait Score<'d> {
     /// an integer value, where an odd value marks the index as a valid break.
     fn score(&'d self, word : &str) -> Vec<Self::Value>;
 
+    /// Generate a word's hyphenation score as by `score`, resuming from the
+    /// matches memoized for its longest known stem, and memoizing those of
+    /// its own.
+    fn score_with(&'d self, word : &str, stems : &Stems) -> Vec<Self::Value>;
+
     /// Whether the given value denotes a valid break.
     fn denotes_opportunity(value : Self::Value) -> bool;
 }
@@ -68,23 +103,43 @@ impl<'d> Score<'d> for Standard {
         let hyphenable_length = word.len();
         let mut values : Vec<u8> = vec![0; hyphenable_length.saturating_sub(1)];
 
-        let fst = &self.patterns.automaton;
-        let tallies = &self.patterns.tallies;
+        let automaton = &self.patterns.automaton;
         for i in 0 .. match_str.len() - 1 {
             let substring = &match_str.as_bytes()[i ..];
-            for (_, tally_id) in fst.reap(substring) {
-                let tally = &tallies[tally_id as usize];
-                for &Locus { index, value } in tally {
-                    let k = i + index as usize;
-                    if k > 1 && k <= hyphenable_length && value > values[k - 2] {
-                        values[k - 2] = value;
-                    }
-                }
-            }
+            automaton.reap(substring, true, |tally_id| self.tally(&mut values, i, tally_id));
         }
         values
     }
 
+    fn score_with(&'d self, word : &str, stems : &Stems) -> Vec<u8> {
+        let fst = match self.patterns.automaton {
+            Automaton::DoubleArray(ref fst) => fst,
+            _ => return self.score(word)
+        };
+        let mut values : Vec<u8> = vec![0; word.len().saturating_sub(1)];
+        for (i, tally_id) in stems.matches(fst, word, true) {
+            self.tally(&mut values, i, tally_id);
+        }
+        values
+    }
+}
+
+trait Tally<'d> : Score<'d> {
+    /// Apply the tally matched at index `i` of the word enclosed in dots.
+    fn tally(&'d self, values : &mut [Self::Value], i : usize, tally_id : u16);
+}
+
+impl<'d> Tally<'d> for Standard {
+    #[inline]
+    fn tally(&'d self, values : &mut [u8], i : usize, tally_id : u16) {
+        let hyphenable_length = values.len() + 1;
+        for &Locus { index, value } in &self.patterns.tallies[tally_id as usize] {
+            let k = i + index as usize;
+            if k > 1 && k <= hyphenable_length && value > values[k - 2] {
+                values[k - 2] = value;
+            }
+        }
+    }
 }
 
 impl<'d> Score<'d> for Extended {
@@ -94,38 +149,200 @@ impl<'d> Score<'d> for Extended {
 
     fn score(&'d self, word : &str) -> Vec<Self::Value> {
         let match_str = [".", word, "."].concat();
-        let hyphenable_length = word.len();
-        let mut values : Vec<u8> = vec![0; hyphenable_length.saturating_sub(1)];
-        let mut regions : Vec<Option<&Subregion>> = vec![None; values.len()];
+        let mut values : Vec<Self::Value> = vec![(0, None); word.len().saturating_sub(1)];
 
-        let fst = &self.patterns.automaton;
-        let tallies = &self.patterns.tallies;
+        let automaton = &self.patterns.automaton;
         for i in 0 .. match_str.len() - 1 {
             let substring = &match_str.as_bytes()[i ..];
-            for (_, tally_id) in fst.reap_past_root(substring) {
-                let tally = &tallies[tally_id as usize];
-                // NOTE: By convention, competing standard and non-standard patterns
-                // may not assign equal values to the same location.
-                for &(Locus { index, value }, ref r) in tally.subregion.iter() {
-                    let k = i + index as usize;
-                    if k > 1 && k <= hyphenable_length && value > values[k - 2] {
-                        values[k - 2] = value;
-                        regions[k - 2] = Some(r);
-                    }
-                }
-                // The order of these two traversals matters, because—
-                for &Locus { index, value } in tally.standard.iter() {
-                    let k = i + index as usize;
-                    // —if a subregion was previously assigned to this location,
-                    // then `w == values[k - 2]`, and it will not be replaced.
-                    if k > 1 && k <= hyphenable_length && value > values[k - 2] {
-                        values[k - 2] = value;
-                        regions[k - 2] = None;
-                    }
+            automaton.reap(substring, false, |tally_id| self.tally(&mut values, i, tally_id));
+        }
+        values
+    }
+
+    fn score_with(&'d self, word : &str, stems : &Stems) -> Vec<Self::Value> {
+        let fst = match self.patterns.automaton {
+            Automaton::DoubleArray(ref fst) => fst,
+            _ => return self.score(word)
+        };
+        let mut values : Vec<Self::Value> = vec![(0, None); word.len().saturating_sub(1)];
+        for (i, tally_id) in stems.matches(fst, word, false) {
+            self.tally(&mut values, i, tally_id);
+        }
+        values
+    }
+}
+
+impl<'d> Tally<'d> for Extended {
+    #[inline]
+    fn tally(&'d self, values : &mut [Self::Value], i : usize, tally_id : u16) {
+        let hyphenable_length = values.len() + 1;
+        let tally = &self.patterns.tallies[tally_id as usize];
+        // NOTE: By convention, competing standard and non-standard patterns
+        // may not assign equal values to the same location.
+        for &(Locus { index, value }, ref r) in tally.subregion.iter() {
+            let k = i + index as usize;
+            if k > 1 && k <= hyphenable_length && value > values[k - 2].0 {
+                values[k - 2] = (value, Some(r));
+            }
+        }
+        // The order of these two traversals matters, because—
+        for &Locus { index, value } in tally.standard.iter() {
+            let k = i + index as usize;
+            // —if a subregion was previously assigned to this location,
+            // then `w == values[k - 2]`, and it will not be replaced.
+            if k > 1 && k <= hyphenable_length && value > values[k - 2].0 {
+                values[k - 2] = (value, None);
+            }
+        }
+    }
+}
+
+
+/// The least number of `char`s in a memoized stem.
+const MIN_STEM : usize = 4;
+
+/// The greatest number of `char`s in a memoized stem, beyond which stems are
+/// too rare to be worth memoizing, and too costly for long words.
+const MAX_STEM : usize = 32;
+
+/// A memo of the pattern matches found within the stems of recently scored
+/// words, shared by the words which begin with them.
+///
+/// A memo should only ever be used with one dictionary, whose patterns its
+/// matches are.
+#[derive(Debug)]
+pub struct Stems {
+    prefixes : Mutex<Lru<Traversal>>
+}
+
+impl Stems {
+    /// Memoize the matches within up to `capacity` stems, each of which
+    /// counts between four and 32 `char`s.
+    pub fn new(capacity : usize) -> Self {
+        Stems { prefixes : Mutex::new(Lru::new(capacity)) }
+    }
+
+    /// Forget every stem.
+    pub fn clear(&self) { self.lock().clear() }
+
+    // The memo is left consistent by every operation, even one that panics.
+    fn lock(&self) -> MutexGuard<'_, Lru<Traversal>> {
+        self.prefixes.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The index and tally of every pattern matched within the word enclosed
+    /// in dots, in the order in which `FST::reap` yields them, or
+    /// `FST::reap_past_root` unless `rooted`.
+    pub(crate) fn matches<A>(&self, automaton : &A, word : &str, rooted : bool) -> Vec<(usize, u16)>
+    where A : Walk {
+        let match_str = [".", word, "."].concat();
+        // The ends of the stems of the word within `match_str`, the longest
+        // being the word itself.
+        let stems : Vec<usize> = word.char_indices().map(|(i, _)| i)
+            .chain(iter::once(word.len()))
+            .take(MAX_STEM + 1)
+            .skip(MIN_STEM)
+            .map(|i| i + 1)
+            .collect();
+
+        let (mut traversal, start) = {
+            let mut prefixes = self.lock();
+            stems.iter().rev()
+                .find_map(|&end| prefixes.get(&match_str[.. end]).map(|t| (t, end)))
+                .unwrap_or_default()
+        };
+        let mut memoized = vec![];
+        traversal.advance(automaton, match_str.as_bytes(), start, rooted, |end, traversal| {
+            if stems.binary_search(&end).is_ok() {
+                memoized.push((end, traversal.clone()));
+            }
+        });
+        if !memoized.is_empty() {
+            let mut prefixes = self.lock();
+            for (end, traversal) in memoized {
+                prefixes.insert(&match_str[.. end], traversal);
+            }
+        }
+
+        let mut matches = traversal.matches;
+        matches.sort_unstable();
+        matches.into_iter().map(|(i, _, tally_id)| (i, tally_id)).collect()
+    }
+}
+
+/// The patterns matched within the leading bytes of a word enclosed in dots.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Traversal {
+    /// The start, end and tally of every match completed.
+    matches : Vec<(usize, usize, u16)>,
+    /// The start, state and output of every walk of the automaton under way.
+    walks : Vec<(usize, u32, u16)>
+}
+
+impl Traversal {
+    /// Read the text from byte `from` onwards, starting a walk of the
+    /// automaton at every byte but the last, and calling `visit` after each
+    /// byte with the number of bytes read so far.
+    fn advance<A, F>(&mut self, automaton : &A, text : &[u8], from : usize, rooted : bool, mut visit : F)
+    where A : Walk, F : FnMut(usize, &Traversal) {
+        let root = if rooted { automaton.root() } else { None };
+        for (p, &label) in text.iter().enumerate().skip(from) {
+            if p + 1 < text.len() {
+                if let Some(tally_id) = root {
+                    self.matches.push((p, p, tally_id));
                 }
+                self.walks.push((p, 0, 0));
             }
+            let matches = &mut self.matches;
+            self.walks.retain_mut(|walk| match automaton.step(walk.1, walk.2, label) {
+                Some((state, output, found)) => {
+                    if let Some(tally_id) = found {
+                        matches.push((walk.0, p + 1, tally_id));
+                    }
+                    *walk = (walk.0, state, output);
+                    true
+                },
+                None => false
+            });
+            visit(p + 1, self);
         }
+    }
+}
+
+/// An automaton of patterns, walked one byte at a time.
+pub(crate) trait Walk {
+    /// The tally of the empty pattern, if any.
+    fn root(&self) -> Option<u16>;
+
+    /// Follow the transition on `label` from `state`, reached with the given
+    /// output, to a new state and output, along with the tally of the
+    /// pattern ending there, if any.
+    fn step(&self, state : u32, output : u16, label : u8) -> Option<(u32, u16, Option<u16>)>;
+}
 
-        values.into_iter().zip(regions).collect()
+impl Walk for FST<u32, u16> {
+    fn root(&self) -> Option<u16> {
+        match self.da.stipe[0].terminal {
+            Terminal::Not => None,
+            Terminal::Empty => Some(0),
+            Terminal::Inner => Some(self.state_output[&0])
+        }
+    }
+
+    #[inline]
+    fn step(&self, state : u32, output : u16, label : u8) -> Option<(u32, u16, Option<u16>)> {
+        let e = state as usize + (1 + label as usize);
+        match self.da.stipe.get(e) {
+            Some(stipe) if stipe.check == label => {
+                let (state, output) = (self.da.next[e], output.mappend(self.da.output[e]));
+                let found = match stipe.terminal {
+                    Terminal::Not => None,
+                    Terminal::Empty => Some(output),
+                    Terminal::Inner => Some(output.mappend(self.state_output[&state]))
+                };
+                Some((state, output, found))
+            },
+            _ => None
+        }
     }
 }
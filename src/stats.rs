@@ -0,0 +1,102 @@
+/*! Statistics of hyphenation over a corpus
+
+Which exceptions to add, and which minima to use, are best decided from the
+way a dictionary hyphenates a representative corpus. [`Statistics`] are
+gathered from text segmented into words as by [`Text`], and tally the words
+by their number of breaks, along with the words left without any break
+although they were long enough to have one.
+
+```ignore
+use kl_hyphenate::stats::Statistics;
+
+let corpus = fs::read_to_string("corpus.txt") ?;
+let stats = Statistics::gather(&en_us, corpus.lines());
+println!("{:.1}% of words unbroken", stats.unbroken_percentage());
+for (word, count) in stats.most_frequent_unhyphenatable(20) {
+    println!("{}\t{}", count, word);
+}
+```
+
+Words are tallied lowercase, as dictionaries hyphenate them regardless of
+case.
+
+[`Statistics`]: struct.Statistics.html
+[`Text`]: ../text/trait.Text.html
+*/
+
+use std::collections::HashMap;
+
+use hyphenator::Hyphenator;
+use text::{Segmenter, WordBounds};
+
+
+/// Aggregate statistics of the hyphenation of a corpus.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    /// The number of words in the corpus.
+    pub words : usize,
+    /// The number of words by their number of breaks: `histogram[n]` words
+    /// have exactly `n` breaks.
+    pub histogram : Vec<usize>,
+    /// The number of words without any break.
+    pub unbroken : usize,
+    /// The words without any break, although longer than the minima of the
+    /// dictionary, with their number of occurrences.
+    pub unhyphenatable : HashMap<String, usize>
+}
+
+impl Statistics {
+    /// Gather the statistics of the given corpus, as successive runs of text
+    /// such as lines, segmented into words as by `Text::hyphenate_text`.
+    pub fn gather<'h, H, I, S>(dict : &'h H, corpus : I) -> Self
+    where H : Hyphenator<'h>, I : IntoIterator<Item = S>, S : AsRef<str> {
+        Statistics::gather_with(dict, corpus, &WordBounds)
+    }
+
+    /// Gather the statistics of the given corpus, its words as identified by
+    /// the segmenter.
+    pub fn gather_with<'h, H, I, S, T>(dict : &'h H, corpus : I, segmenter : &T) -> Self
+    where H : Hyphenator<'h>, I : IntoIterator<Item = S>, S : AsRef<str>, T : Segmenter {
+        let (left, right) = dict.unbreakable_chars();
+        let mut stats = Statistics::default();
+        for text in corpus {
+            for (_, word) in segmenter.words(text.as_ref()) {
+                let breaks = dict.hyphenate(word).breaks.len();
+                stats.words += 1;
+                if stats.histogram.len() <= breaks {
+                    stats.histogram.resize(breaks + 1, 0);
+                }
+                stats.histogram[breaks] += 1;
+                if breaks == 0 {
+                    stats.unbroken += 1;
+                    if word.chars().count() > left + right {
+                        *stats.unhyphenatable.entry(word.to_lowercase()).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+        stats
+    }
+
+    /// The percentage of words without any break, or 0 if there are none.
+    pub fn unbroken_percentage(&self) -> f64 {
+        if self.words == 0 { 0.0 } else { 100.0 * self.unbroken as f64 / self.words as f64 }
+    }
+
+    /// The mean number of breaks per word, or 0 if there are no words.
+    pub fn mean_breaks(&self) -> f64 {
+        let breaks : usize = self.histogram.iter().enumerate().map(|(n, &count)| n * count).sum();
+        if self.words == 0 { 0.0 } else { breaks as f64 / self.words as f64 }
+    }
+
+    /// The `n` most frequent words without any break, although longer than
+    /// the minima of the dictionary, from the most frequent down; words as
+    /// frequent as one another are in alphabetical order.
+    pub fn most_frequent_unhyphenatable(&self, n : usize) -> Vec<(&str, usize)> {
+        let mut words : Vec<(&str, usize)> = self.unhyphenatable.iter()
+            .map(|(word, &count)| (word.as_str(), count)).collect();
+        words.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        words.truncate(n);
+        words
+    }
+}
@@ -0,0 +1,535 @@
+/*! Hyphenation of text runs
+
+Dictionaries operate on individual words. The [`Text`] trait extends them
+to whole runs of text, which are segmented into words according to the
+Unicode word boundary rules ([UAX #29]) before each word is hyphenated in
+turn.
+
+```ignore
+use kl_hyphenate::text::Text;
+
+let hyphenated = en_us.hyphenate_text("I know noble accents");
+let collected : String = hyphenated.into_iter().collect();
+assert_eq!(collected, "I know no-ble ac-cents");
+```
+
+Opportunities found in text are byte indices into the whole text, rather
+than into the individual words.
+
+
+## URLs and email addresses
+
+Pattern hyphenation is meaningless for URLs, email addresses, and similar
+identifiers, and its results are unreadable at best ("exam-ple.com/path").
+Whitespace-delimited tokens shaped like either are thus left unhyphenated.
+Such tokens can be identified with [`is_url`] and [`is_email`], and broken
+separately by a [`UrlBreaker`].
+
+
+## Segmentation
+
+The default segmentation, performed by [`WordBounds`], is unsuitable for
+scripts written without spaces between words: Thai, Lao, Khmer, Myanmar,
+Chinese and Japanese. Runs of these scripts are left whole, and hyphenated
+as though they were a single word. A different [`Segmenter`] may be provided instead; the
+[`Lexicon`] segmenter divides such scripts into words by matching them
+against a list of known words.
+
+```ignore
+use kl_hyphenate::text::Lexicon;
+
+let lexicon = Lexicon::new(thai_words);
+let hyphenated = th.hyphenate_text_with(thai_text, &lexicon);
+```
+
+
+## Multilingual text
+
+Text in several languages, annotated with the byte range covered by each,
+can be hyphenated by a [`Multilingual`] hyphenator. Dictionaries are loaded
+from a [`Registry`] as needed, the first time their language is encountered.
+
+```ignore
+use kl_hyphenate::registry::{Registry, Source};
+use kl_hyphenate::text::Multilingual;
+
+let registry = Registry::new().source(Source::Dir("dictionaries".into()));
+let hyphenator = Multilingual::new(&registry);
+let text = "Ein Zitat: anfractuous";
+let spans = vec![(0 .. 10, German1996), (11 .. 22, EnglishUS)];
+let hyphenated = hyphenator.hyphenate_spans(text, &spans);
+```
+
+
+## Streaming
+
+Documents of any length can be hyphenated from a reader into a writer by a
+[`Stream`], which holds no more than a few kilobytes of text at a time, or
+the longest whitespace-delimited token should it exceed them.
+
+```ignore
+use kl_hyphenate::text::Stream;
+
+let input = File::open("corpus.txt") ?;
+let output = File::create("corpus.hyphenated.txt") ?;
+Stream::new(&en_us).mark_with("\u{00ad}").copy(input, output) ?;
+```
+
+
+[UAX #29]: https://www.unicode.org/reports/tr29/
+[`Text`]: trait.Text.html
+[`Stream`]: struct.Stream.html
+[`Multilingual`]: struct.Multilingual.html
+[`Registry`]: ../registry/struct.Registry.html
+[`WordBounds`]: struct.WordBounds.html
+[`Segmenter`]: trait.Segmenter.html
+[`Lexicon`]: struct.Lexicon.html
+[`is_url`]: fn.is_url.html
+[`is_email`]: fn.is_email.html
+[`UrlBreaker`]: ../url/struct.UrlBreaker.html
+*/
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::ops::Range;
+use std::str;
+use std::sync::Arc;
+use unicode_segmentation::UnicodeSegmentation;
+
+use kl_hyphenate_commons::Language;
+use kl_hyphenate_commons::dictionary::{Standard, Extended};
+use hyphenator::{Hyphenator, Offset, Word};
+use cjk::is_cjk;
+use registry::Registry;
+#[cfg(feature = "detection")]
+use detect::Detection;
+
+
+/// Hyphenation of every word within a text run.
+pub trait Text<'h> {
+    /// Plain representation of a break within the text.
+    type Opportunity;
+
+    /// Hyphenate the words of the given text, computing appropriate breaks and
+    /// preparing it for iteration.
+    ///
+    /// Breaks are byte indices into `text`. Tokens shaped like URLs or email
+    /// addresses are not hyphenated.
+    fn hyphenate_text<'t>(&'h self, text : &'t str) -> Word<'t, Self::Opportunity> {
+        self.hyphenate_text_with(text, &WordBounds)
+    }
+
+    /// Hyphenate the words of the given text, as identified by the segmenter.
+    fn hyphenate_text_with<'t, S>(&'h self, text : &'t str, segmenter : &S)
+        -> Word<'t, Self::Opportunity>
+    where S : Segmenter;
+}
+
+impl<'h, H> Text<'h> for H
+where H : Hyphenator<'h>
+    , H::Opportunity : Offset
+{
+    type Opportunity = H::Opportunity;
+
+    fn hyphenate_text_with<'t, S>(&'h self, text : &'t str, segmenter : &S)
+        -> Word<'t, Self::Opportunity>
+    where S : Segmenter
+    {
+        let mut breaks = vec![];
+        for (start, word) in segmenter.words(text) {
+            let found = self.hyphenate(word).breaks;
+            breaks.extend(found.into_iter().map(|o| o.offset(start)));
+        }
+
+        Word { text, breaks }
+    }
+}
+
+
+/// A hyphenator for text spanning several languages.
+///
+/// The dictionary for each language is retrieved from the `registry`, which
+/// loads it the first time the language is encountered and keeps it for
+/// subsequent use. Should no dictionary be found, text in that language is left
+/// unhyphenated, and the registry is consulted anew the next time around.
+pub struct Multilingual<'r> {
+    registry : &'r Registry<Standard>,
+    #[cfg(feature = "detection")]
+    detection : Detection
+}
+
+impl<'r> Multilingual<'r> {
+    /// Build a multilingual hyphenator which retrieves dictionaries from the
+    /// given registry.
+    pub fn new(registry : &'r Registry<Standard>) -> Self {
+        Multilingual {
+            registry,
+            #[cfg(feature = "detection")]
+            detection : Detection::default()
+        }
+    }
+
+    /// Set the parameters with which the language of text is detected.
+    #[cfg(feature = "detection")]
+    pub fn detect_with(&mut self, detection : Detection) {
+        self.detection = detection;
+    }
+
+    /// Hyphenate the words of the given text with the dictionary for its
+    /// detected language. Breaks are byte indices into `text`.
+    ///
+    /// Should no language be detected, nor a fallback be set, the text is left
+    /// unhyphenated.
+    #[cfg(feature = "detection")]
+    pub fn hyphenate_text_detecting<'t>(&self, text : &'t str) -> Word<'t, usize> {
+        match self.detection.detect(text) {
+            Some(lang) => self.hyphenate_spans(text, &[(0 .. text.len(), lang)]),
+            None => Word { text, breaks : vec![] }
+        }
+    }
+
+    /// The dictionary for the given language, loading it if necessary.
+    pub fn dictionary(&self, lang : Language) -> Option<Arc<Standard>> {
+        self.registry.get(lang).ok()
+    }
+
+    /// Hyphenate the words in each span of text with the dictionary for its
+    /// language. Breaks are byte indices into `text`.
+    ///
+    /// Spans are given as byte ranges into `text`, and should not overlap.
+    /// Text outside of all spans is left unhyphenated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a span does not lie on `char` boundaries within `text`.
+    pub fn hyphenate_spans<'t>(&self, text : &'t str, spans : &[(Range<usize>, Language)])
+        -> Word<'t, usize>
+    {
+        let mut breaks = vec![];
+        for &(ref range, lang) in spans {
+            if let Some(dict) = self.dictionary(lang) {
+                let found = dict.hyphenate_text(&text[range.clone()]).breaks;
+                breaks.extend(found.into_iter().map(|i| i + range.start));
+            }
+        }
+        breaks.sort_unstable();
+        breaks.dedup();
+
+        Word { text, breaks }
+    }
+}
+
+impl<'r> fmt::Debug for Multilingual<'r> {
+    fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Multilingual").field("loaded", &self.registry.loaded()).finish()
+    }
+}
+
+
+/// Hyphenation of text streamed from a reader into a writer, in bounded memory.
+///
+/// Text is read in chunks which end on ASCII whitespace, so that no word is
+/// split across chunks. Each chunk is hyphenated and written out with breaks
+/// marked, by default, with a hyphen.
+#[derive(Clone, Debug)]
+pub struct Stream<'h, H : 'h, S = WordBounds> {
+    dictionary : &'h H,
+    segmenter : S,
+    mark : String
+}
+
+/// The number of bytes requested from the reader at once.
+const CHUNK_SIZE : usize = 8 * 1024;
+
+impl<'h, H> Stream<'h, H> {
+    /// Prepare to hyphenate streamed text with the given dictionary.
+    pub fn new(dictionary : &'h H) -> Self {
+        Stream { dictionary, segmenter : WordBounds, mark : "-".to_owned() }
+    }
+}
+
+impl<'h, H, S> Stream<'h, H, S> {
+    /// Set the mark that will be inserted before word breaks.
+    pub fn mark_with(mut self, mark : &str) -> Self {
+        self.mark = mark.to_owned();
+        self
+    }
+
+    /// Identify words with the given segmenter.
+    pub fn segment_with<T : Segmenter>(self, segmenter : T) -> Stream<'h, H, T> {
+        Stream { dictionary : self.dictionary, segmenter, mark : self.mark }
+    }
+
+    /// Read text to exhaustion, passing it to `hyphenate` in chunks that end
+    /// on whitespace.
+    fn stream<R, W, F>(&self, mut reader : R, mut writer : W, mut hyphenate : F) -> io::Result<()>
+    where R : Read
+        , W : Write
+        , F : FnMut(&str, &mut W) -> io::Result<()>
+    {
+        let mut pending : Vec<u8> = Vec::with_capacity(CHUNK_SIZE);
+        let mut chunk = vec![0; CHUNK_SIZE];
+        loop {
+            let n = match reader.read(&mut chunk) {
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e)
+            };
+            let scanned = pending.len();
+            pending.extend_from_slice(&chunk[.. n]);
+
+            // ASCII whitespace never occurs within a multibyte sequence, thus
+            // the text up to it is complete, both in words and in UTF-8. The
+            // bytes kept from previous reads follow the last whitespace, such
+            // that only those just read need be searched.
+            let end = if n == 0 { pending.len() } else {
+                pending[scanned ..].iter().rposition(u8::is_ascii_whitespace).map_or(0, |i| scanned + i + 1)
+            };
+            if end > 0 {
+                let text = str::from_utf8(&pending[.. end])
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)) ?;
+                hyphenate(text, &mut writer) ?;
+                pending.drain(.. end);
+            }
+            if n == 0 { break }
+        }
+        writer.flush()
+    }
+}
+
+macro_rules! impl_stream {
+    ($dict:ty) => {
+        impl<'h, S> Stream<'h, $dict, S> where S : Segmenter {
+            /// Read text to exhaustion, and write it out hyphenated.
+            ///
+            /// The text must be valid UTF-8; otherwise, an error of kind
+            /// `InvalidData` is returned.
+            pub fn copy<R, W>(&self, reader : R, writer : W) -> io::Result<()>
+            where R : Read, W : Write {
+                self.stream(reader, writer, |text, writer| {
+                    let mut segments = self.dictionary.hyphenate_text_with(text, &self.segmenter)
+                                           .into_iter();
+                    segments.mark_with(&self.mark);
+                    for segment in segments {
+                        writer.write_all(segment.as_bytes()) ?;
+                    }
+                    Ok(())
+                })
+            }
+        }
+    }
+}
+
+impl_stream! { Standard }
+impl_stream! { Extended }
+
+
+/// A strategy to identify the words within a text.
+pub trait Segmenter {
+    /// The words of the text that are eligible for hyphenation, paired with
+    /// their byte offset into the text.
+    fn words<'t>(&self, text : &'t str) -> Vec<(usize, &'t str)>;
+}
+
+/// The default segmenter, which follows the Unicode word boundary rules
+/// ([UAX #29]) and leaves out tokens shaped like URLs or email addresses.
+///
+/// Runs of scripts written without spaces between words are not segmented
+/// further, and yielded whole.
+///
+/// [UAX #29]: https://www.unicode.org/reports/tr29/
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WordBounds;
+
+impl Segmenter for WordBounds {
+    fn words<'t>(&self, text : &'t str) -> Vec<(usize, &'t str)> {
+        let mut words = vec![];
+        for token in text.split_whitespace() {
+            if is_url(token) || is_email(token) { continue }
+
+            // `split_whitespace` only yields subslices of the text itself.
+            let start = token.as_ptr() as usize - text.as_ptr() as usize;
+            for (i, run) in script_runs(token) {
+                if run.starts_with(unspaced) {
+                    words.push((start + i, run));
+                } else {
+                    let found = run.split_word_bound_indices()
+                        .filter(|&(_, w)| w.chars().any(char::is_alphabetic))
+                        .map(|(j, w)| (start + i + j, w));
+                    words.extend(found);
+                }
+            }
+        }
+        words
+    }
+}
+
+/// A dictionary-based segmenter for scripts written without spaces between
+/// words, such as Thai, Lao, Khmer, Myanmar, Chinese and Japanese.
+///
+/// Runs of such scripts are segmented by maximal matching against a lexicon
+/// of known words: at each position, the longest known word is taken, and
+/// any characters which begin no known word are gathered into a segment of
+/// their own. All other text is segmented as by [`WordBounds`].
+///
+/// [`WordBounds`]: struct.WordBounds.html
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lexicon {
+    words : HashSet<String>,
+    /// The length of the longest known word, in `char`s.
+    longest : usize
+}
+
+impl Lexicon {
+    /// Build a lexicon from a list of known words.
+    pub fn new<I, S>(words : I) -> Self
+    where I : IntoIterator<Item = S>
+        , S : Into<String>
+    {
+        let mut lexicon = Lexicon::default();
+        for word in words { lexicon.insert(word); }
+        lexicon
+    }
+
+    /// Add a known word to the lexicon, returning whether it was absent.
+    pub fn insert<S : Into<String>>(&mut self, word : S) -> bool {
+        let word = word.into();
+        self.longest = self.longest.max(word.chars().count());
+        self.words.insert(word)
+    }
+
+    /// The byte length of the longest known word at the start of the string.
+    fn longest_match(&self, s : &str) -> Option<usize> {
+        s.char_indices().skip(1).map(|(i, _)| i).chain(Some(s.len()))
+            .take(self.longest)
+            .filter(|&end| self.words.contains(&s[.. end]))
+            .last()
+    }
+
+    /// Divide a run of unspaced script into known and unknown words.
+    fn segment<'t>(&self, run : &'t str) -> Vec<(usize, &'t str)> {
+        let mut words = vec![];
+        let mut unknown = None;
+        let mut i = 0;
+        while i < run.len() {
+            match self.longest_match(&run[i ..]) {
+                Some(n) => {
+                    if let Some(start) = unknown.take() {
+                        words.push((start, &run[start .. i]));
+                    }
+                    words.push((i, &run[i .. i + n]));
+                    i += n;
+                },
+                None => {
+                    unknown.get_or_insert(i);
+                    i += run[i ..].chars().next().map_or(1, char::len_utf8);
+                }
+            }
+        }
+        if let Some(start) = unknown {
+            words.push((start, &run[start ..]));
+        }
+        words
+    }
+}
+
+impl Segmenter for Lexicon {
+    fn words<'t>(&self, text : &'t str) -> Vec<(usize, &'t str)> {
+        let mut words = vec![];
+        for (start, run) in script_runs(text) {
+            let found = if run.starts_with(unspaced) {
+                self.segment(run)
+            } else { WordBounds.words(run) };
+            words.extend(found.into_iter().map(|(i, w)| (start + i, w)));
+        }
+        words
+    }
+}
+
+/// Whether the character belongs to a script written without spaces between
+/// words.
+fn unspaced(c : char) -> bool {
+    match c {
+        '\u{0E00}' ..= '\u{0E7F}'     // Thai
+      | '\u{0E80}' ..= '\u{0EFF}'     // Lao
+      | '\u{1000}' ..= '\u{109F}'     // Myanmar
+      | '\u{A9E0}' ..= '\u{A9FF}'     // Myanmar Extended-B
+      | '\u{AA60}' ..= '\u{AA7F}'     // Myanmar Extended-A
+      | '\u{1780}' ..= '\u{17FF}'     // Khmer
+      | '\u{19E0}' ..= '\u{19FF}'     // Khmer Symbols
+          => true,
+        _ => is_cjk(c)
+    }
+}
+
+/// Divide the text into maximal runs of unspaced and spaced script, paired with
+/// their byte offset into the text.
+fn script_runs(text : &str) -> Vec<(usize, &str)> {
+    let mut runs = vec![];
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        match chars.peek() {
+            Some(&(i, next)) if unspaced(c) != unspaced(next) => {
+                runs.push((start, &text[start .. i]));
+                start = i;
+            },
+            None => runs.push((start, &text[start ..])),
+            _ => ()
+        }
+    }
+    runs
+}
+
+/// Strip the punctuation that commonly encloses or follows a token in running
+/// text, such as brackets and commas.
+fn trim_enclosing(token : &str) -> &str {
+    token.trim_start_matches(|c| "([{<\"'«‘“".contains(c))
+         .trim_end_matches(|c| ")]}>\"'»’”.,;:!?".contains(c))
+}
+
+/// Whether the token is shaped like a URL: one with a scheme, one beginning
+/// with `www.`, or a domain followed by a path, such as `example.com/path`.
+///
+/// A domain alone is not taken for a URL, since words run together across a
+/// full stop, file names and the like are shaped as domains too. Nor is one
+/// with an uppercase top-level domain, as in `accomplished.Thereafter/`.
+pub fn is_url(token : &str) -> bool {
+    let token = trim_enclosing(token);
+    let lowercase = token.to_lowercase();
+    if lowercase.contains("://") || lowercase.starts_with("www.") {
+        return true
+    }
+
+    match token.find(['/', '?', '#']) {
+        Some(i) => {
+            let host = &token[.. i];
+            is_domain(host) && host.rsplit('.').next().is_some_and(|tld| tld.chars().all(char::is_lowercase))
+        },
+        None => false
+    }
+}
+
+/// Whether the token is shaped like an email address.
+pub fn is_email(token : &str) -> bool {
+    let token = trim_enclosing(token);
+    let token = token.strip_prefix("mailto:").unwrap_or(token);
+    match token.find('@') {
+        Some(i) if i > 0 => is_domain(&token[i + 1 ..]),
+        _ => false
+    }
+}
+
+/// Whether the string is a plausible domain name: two or more dot-separated
+/// labels, ending with an alphabetic top-level domain.
+fn is_domain(s : &str) -> bool {
+    let labels : Vec<_> = s.split('.').collect();
+    let well_formed = labels.iter().all(|l| {
+        !l.is_empty() && l.chars().all(|c| c.is_alphanumeric() || c == '-')
+    });
+    let tld = labels[labels.len() - 1];
+
+    labels.len() > 1 && well_formed
+        && tld.chars().count() > 1 && tld.chars().all(char::is_alphabetic)
+}
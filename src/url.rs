@@ -0,0 +1,106 @@
+/*! Breaking URLs
+
+URLs are not words, and cannot be hyphenated by a dictionary. They are
+however the most frequent cause of overfull lines, and common style guides
+agree on where they may be broken: after the slashes, periods, hyphens,
+question marks and ampersands that delimit their components.
+
+The [`UrlBreaker`] finds such opportunities, and implements [`Hyphenator`]
+so that it may be used interchangeably with a dictionary.
+
+```ignore
+use kl_hyphenate::url::UrlBreaker;
+
+let urls = UrlBreaker::default();
+let broken = urls.hyphenate("https://example.com/a-path?q=1&r=2");
+let mut segments = broken.iter();
+segments.mark_with("");
+let collected : Vec<_> = segments.collect();
+assert_eq!(collected, vec!["https://", "example.", "com/", "a-", "path?", "q=1&", "r=2"]);
+```
+
+Since hyphens inserted at the end of a line could be mistaken for part of the
+URL, breaks should be marked with an empty string when iterating.
+
+[`UrlBreaker`]: struct.UrlBreaker.html
+[`Hyphenator`]: ../hyphenator/trait.Hyphenator.html
+*/
+
+use std::collections::HashMap;
+
+use hyphenator::{self, Hyphenator, Word};
+
+
+/// The characters after which a URL may be broken.
+const DELIMITERS : &[char] = &['/', '.', '-', '?', '&'];
+
+/// A non-dictionary hyphenator for URLs, which yields opportunities after
+/// the delimiters of their components.
+///
+/// A run of consecutive delimiters, such as the `//` following a scheme, is
+/// never broken internally.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UrlBreaker {
+    /// Known breaks for specific URLs, which take priority over delimiters.
+    pub exceptions : HashMap<String, Vec<usize>>,
+    /// The minimum number of `char`s from the start and end of a URL where
+    /// breaks may not occur.
+    pub minima : (usize, usize)
+}
+
+impl Default for UrlBreaker {
+    fn default() -> Self {
+        UrlBreaker { exceptions : HashMap::new(), minima : (1, 1) }
+    }
+}
+
+impl<'h> Hyphenator<'h> for UrlBreaker {
+    type Opportunity = usize;
+    type Exact = usize;
+
+    /// Find the breaks within a URL.
+    ///
+    /// Unlike dictionary hyphenation, this method is case-sensitive, and does
+    /// not grant priority to soft hyphens.
+    fn hyphenate<'t>(&'h self, url : &'t str) -> Word<'t, usize> {
+        Word { text : url, breaks : self.opportunities(url) }
+    }
+
+    fn opportunities_within(&'h self, url : &str, (l, r) : (usize, usize)) -> Vec<usize> {
+        let chars : Vec<_> = url.char_indices().collect();
+        chars.windows(2)
+            .filter(|pair| {
+                let (fore, aft) = (pair[0].1, pair[1].1);
+                DELIMITERS.contains(&fore) && !DELIMITERS.contains(&aft)
+            }).map(|pair| pair[1].0)
+            .filter(|&i| i >= l && i <= r)
+            .collect()
+    }
+
+    #[inline]
+    fn exact_within(&'h self, url : &str, (l, r) : (usize, usize)) -> Option<Vec<usize>> {
+        self.exceptions.get(url).map(|v| v.iter().filter(|&i| *i >= l && *i <= r).cloned().collect())
+    }
+
+    #[inline]
+    fn add_exact(&mut self, url : String, ops : Vec<usize>) -> Option<Vec<usize>> {
+        self.exceptions.insert(url, ops)
+    }
+
+    /// Specify the breaks of a URL marked with hyphens (`-`) or equal signs
+    /// (`=`). URLs are matched as given, and thus left unfolded; those which
+    /// hold either character should be specified with `add_exact` instead.
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<usize>> {
+        let (url, ops) = hyphenator::unmark_with(marked, str::to_owned);
+        self.add_exact(url, ops)
+    }
+
+    #[inline]
+    fn remove_exact(&mut self, url : &str) -> Option<Vec<usize>> {
+        self.exceptions.remove(url)
+    }
+
+    #[inline] fn clear_exceptions(&mut self) { self.exceptions.clear() }
+
+    #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.minima }
+}
@@ -0,0 +1,93 @@
+/*! Hyphenating controlled vocabularies
+
+Some domains, such as part catalogues or legal texts, draw their words from a
+controlled vocabulary, which must be hyphenated exactly as specified or not
+at all; patterns would only find breaks where none are wanted. A
+[`Vocabulary`] holds exact hyphenations alone, and implements [`Hyphenator`]
+so that it may be used interchangeably with a dictionary.
+
+```ignore
+use kl_hyphenate::vocabulary::Vocabulary;
+
+let parts = Vocabulary::from_marked(["Ti-tan-ox-id", "Flansch-dich-tung"]);
+assert_eq!(parts.hyphenate("Titanoxid").breaks, vec![2, 5, 7]);
+assert!(parts.hyphenate("Stahlrohr").breaks.is_empty());
+```
+
+Like dictionaries, the vocabulary is case-insensitive, and grants priority
+to soft hyphens.
+
+[`Vocabulary`]: struct.Vocabulary.html
+[`Hyphenator`]: ../hyphenator/trait.Hyphenator.html
+*/
+
+use kl_hyphenate_commons::dictionary::Exceptions;
+use hyphenator::{hyphenate_prepared, unmark_for, Hyphenator, Word};
+
+
+/// A non-dictionary hyphenator which only breaks the words it knows, at their
+/// specified breaks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vocabulary {
+    /// The words of the vocabulary and their breaks.
+    pub exceptions : Exceptions,
+    /// The minimum number of `char`s from the start and end of a word where
+    /// breaks may not occur.
+    pub minima : (usize, usize)
+}
+
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Vocabulary { exceptions : Exceptions::default(), minima : (1, 1) }
+    }
+}
+
+impl Vocabulary {
+    /// Build a vocabulary of words marked with hyphens (`-`) or equal signs
+    /// (`=`) at their breaks, as by `Hyphenator::add_exact_marked`.
+    pub fn from_marked<I, S>(words : I) -> Self
+    where I : IntoIterator<Item = S>, S : AsRef<str> {
+        let mut vocabulary = Vocabulary::default();
+        for word in words {
+            vocabulary.add_exact_marked(word.as_ref());
+        }
+        vocabulary
+    }
+}
+
+impl<'h> Hyphenator<'h> for Vocabulary {
+    type Opportunity = usize;
+    type Exact = usize;
+
+    fn hyphenate<'t>(&'h self, word : &'t str) -> Word<'t, usize> {
+        hyphenate_prepared(self, word, |i| i, |i, prepared| prepared.realign(i))
+    }
+
+    /// Words outside the vocabulary have no opportunities.
+    #[inline]
+    fn opportunities_within(&'h self, _ : &str, _ : (usize, usize)) -> Vec<usize> { vec![] }
+
+    #[inline]
+    fn exact_within(&'h self, w : &str, (l, r) : (usize, usize)) -> Option<Vec<usize>> {
+        self.exceptions.0.get(w).map(|v| v.iter().filter(|&i| *i >= l && *i <= r).cloned().collect())
+    }
+
+    #[inline]
+    fn add_exact(&mut self, w : String, ops : Vec<usize>) -> Option<Vec<usize>> {
+        self.exceptions.0.insert(w, ops)
+    }
+
+    fn add_exact_marked(&mut self, marked : &str) -> Option<Vec<usize>> {
+        let (w, ops) = unmark_for(marked, self);
+        self.add_exact(w, ops)
+    }
+
+    #[inline]
+    fn remove_exact(&mut self, w : &str) -> Option<Vec<usize>> {
+        self.exceptions.0.remove(w)
+    }
+
+    #[inline] fn clear_exceptions(&mut self) { self.exceptions.0.clear() }
+
+    #[inline] fn unbreakable_chars(&self) -> (usize, usize) { self.minima }
+}
@@ -0,0 +1,45 @@
+/*! Integration with `textwrap`
+
+Hyphenation dictionaries can serve as [word splitters] for the `textwrap`
+crate, which will then break overlong words at the opportunities found by
+the dictionary.
+
+```ignore
+use kl_hyphenate::wrap::Splitter;
+use textwrap::Options;
+
+let en_us = Standard::from_path(Language::EnglishUS, path_to_dict) ?;
+let options = Options::new(18).word_splitter(Splitter(en_us));
+
+let lines = textwrap::wrap("Hyphenation is accomplished by the dictionary", &options);
+```
+
+Splitters are not aware of subregions: opportunities found by [`Extended`]
+dictionaries are used as plain breaks, leaving the word unaltered.
+
+
+[word splitters]: https://docs.rs/textwrap/0.14/textwrap/word_splitters/trait.WordSplitter.html
+[`Extended`]: ../extended/struct.Extended.html
+*/
+
+use textwrap::word_splitters::WordSplitter;
+
+use kl_hyphenate_commons::dictionary::{Standard, Extended};
+use hyphenator::Hyphenator;
+
+
+/// A hyphenation dictionary wrapped for use as a `textwrap` word splitter.
+#[derive(Clone, Debug)]
+pub struct Splitter<H>(pub H);
+
+impl WordSplitter for Splitter<Standard> {
+    fn split_points(&self, word : &str) -> Vec<usize> {
+        self.0.hyphenate(word).breaks
+    }
+}
+
+impl WordSplitter for Splitter<Extended> {
+    fn split_points(&self, word : &str) -> Vec<usize> {
+        self.0.hyphenate(word).breaks.into_iter().map(|(i, _)| i).collect()
+    }
+}
@@ -1,31 +1,52 @@
 #[macro_use] extern crate lazy_static;
 extern crate quickcheck;
 extern crate unicode_segmentation;
+#[cfg(feature = "python")]
+extern crate pyo3;
 
+use std::env;
+use std::fs;
 use std::fs::File;
 use std::io::{BufReader};
-use std::path::{Path};
+use std::path::{Path, PathBuf};
 use quickcheck::{quickcheck, TestResult};
 
 extern crate kl_hyphenate;
 extern crate kl_hyphenate_commons;
 use kl_hyphenate::*;
 use kl_hyphenate::extended::*;
+use kl_hyphenate::load::{Error, Format};
 use kl_hyphenate::Language::*;
 
 
+// The dictionaries built by the build script, when it builds them, rather than
+// those of the source tree.
+#[cfg(feature = "build_dictionaries")]
+const DICTIONARIES : &str = kl_hyphenate::load::BUILT_DICTIONARIES;
+#[cfg(not(feature = "build_dictionaries"))]
+const DICTIONARIES : &str = "dictionaries";
+
+fn dictionary(filename : &str) -> PathBuf {
+    Path::new(DICTIONARIES).join(filename)
+}
+
 fn fiat_std(lang : Language) -> Standard {
     let filename = format!("{}.standard.bincode", lang.code());
-    let file = File::open(Path::new("dictionaries").join(filename)).unwrap();
+    let file = File::open(dictionary(&filename)).unwrap();
     Standard::from_reader(lang, &mut BufReader::new(file)).unwrap()
 }
 
 fn fiat_ext(lang : Language) -> Extended {
     let filename = format!("{}.extended.bincode", lang.code());
-    let file = File::open(Path::new("dictionaries").join(filename)).unwrap();
+    let file = File::open(dictionary(&filename)).unwrap();
     Extended::from_reader(lang, &mut BufReader::new(file)).unwrap()
 }
 
+/// The breaks of a word, with the substitution by which each respells it.
+fn respellings(dict : &Extended, word : &str) -> Vec<(usize, Option<String>)> {
+    dict.hyphenate(word).breaks.into_iter().map(|(i, subr)| (i, subr.map(|s| s.substitution().to_owned()))).collect()
+}
+
 lazy_static! {
     static ref EN_US : Standard = fiat_std(EnglishUS);
     static ref HU : Extended = fiat_ext(Hungarian);
@@ -44,6 +65,64 @@ fn collected_equals_original() {
     quickcheck(property as fn(String) -> bool);
 }
 
+#[test]
+fn ascii_case_insensitivity() {
+    fn property(word : String) -> TestResult {
+        if !word.is_ascii() { return TestResult::discard() }
+        let lowercase = word.to_lowercase();
+        TestResult::from_bool(EN_US.hyphenate(&word).breaks == EN_US.hyphenate(&lowercase).breaks)
+    }
+
+    quickcheck(property as fn(String) -> TestResult);
+    assert_eq!(EN_US.hyphenate("HYPHENATION").breaks, EN_US.hyphenate("hyphenation").breaks);
+}
+
+#[test]
+fn latin1_case_insensitivity() {
+    lazy_static! {
+        static ref FR : Standard = fiat_std(French);
+    }
+
+    fn property(word : String) -> bool {
+        let latin1 : String = word.chars().filter_map(|c| std::char::from_u32(c as u32 % 256)).collect();
+        let lowercase = latin1.to_lowercase();
+        FR.hyphenate(&latin1).breaks == FR.hyphenate(&lowercase).breaks
+    }
+
+    quickcheck(property as fn(String) -> bool);
+    let words = [("ÉLÉPHANTESQUEMENT", "éléphantesquement"), ("PÂTISSIÈRE×ŒUVRE", "pâtissière×œuvre")];
+    for &(upper, lower) in words.iter() {
+        assert_eq!(FR.hyphenate(upper).breaks, FR.hyphenate(&upper.to_lowercase()).breaks);
+        assert_eq!(FR.hyphenate(upper).breaks, FR.hyphenate(lower).breaks);
+    }
+}
+
+#[test]
+fn hyphenating_into_buffers() {
+    fn property(word : String) -> bool {
+        let mut breaks = vec![7];
+        EN_US.hyphenate_into(&word, &mut breaks);
+        breaks == EN_US.hyphenate(&word).breaks
+    }
+
+    quickcheck(property as fn(String) -> bool);
+
+    let mut breaks = Vec::with_capacity(16);
+    for word in &["CoAssociative", "hy\u{ad}phenation", "İLGİNÇ", "a"] {
+        EN_US.hyphenate_into(word, &mut breaks);
+        assert_eq!(breaks, EN_US.hyphenate(word).breaks);
+        TR.hyphenate_into(word, &mut breaks);
+        assert_eq!(breaks, TR.hyphenate(word).breaks);
+    }
+    assert!(breaks.capacity() >= 16);
+
+    let mut breaks = vec![];
+    for word in &["Asszonnyal", "asz\u{ad}szony", "Mennyi"] {
+        HU.hyphenate_into(word, &mut breaks);
+        assert_eq!(breaks, HU.hyphenate(word).breaks);
+    }
+}
+
 #[test]
 fn opportunities_within_bounds() {
     fn property(s : String) -> TestResult {
@@ -126,7 +205,7 @@ fn basics_extended() {
 #[test]
 fn special_casing() {
     let w0 = "İbrahim";
-    let v0 : Vec<_> = TR.hyphenate(&w0).into_iter().segments().collect();
+    let v0 : Vec<_> = TR.hyphenate(w0).into_iter().segments().collect();
     assert_eq!(v0, vec!["İb", "ra", "him"]);
 
     let w1 = "İLGİNÇ";
@@ -146,6 +225,868 @@ fn special_casing() {
     assert_eq!(v3, vec!["MU", "CİLAGİ", "NOUS"]);
 }
 
+#[cfg(feature = "textwrap")]
+#[test]
+fn textwrap_splitter() {
+    extern crate textwrap;
+    use kl_hyphenate::wrap::Splitter;
+
+    let options = textwrap::Options::new(10).word_splitter(Splitter(EN_US.clone()));
+    let lines = textwrap::wrap("anfractuous hyphenation", &options);
+    assert_eq!(lines, vec!["anfractu-", "ous hy-", "phenation"]);
+}
+
+#[test]
+fn url_breaks() {
+    use kl_hyphenate::url::UrlBreaker;
+
+    let urls = UrlBreaker::default();
+    let url = "https://example.com/a-path?q=1&r=2";
+    let mut segments = urls.hyphenate(url).into_iter();
+    segments.mark_with("");
+    let collected : Vec<_> = segments.collect();
+    assert_eq!(collected, vec!["https://", "example.", "com/", "a-", "path?", "q=1&", "r=2"]);
+
+    assert!(urls.hyphenate("").breaks.is_empty());
+    assert_eq!(urls.hyphenate("example.com.").breaks, vec![8]);
+}
+
+#[test]
+fn cjk_breaks() {
+    use kl_hyphenate::cjk::CjkBreaker;
+
+    let text = "漢字の「かな」、ちょっと";
+    let mut segments = CjkBreaker.hyphenate(text).into_iter();
+    segments.mark_with("");
+    let collected : Vec<_> = segments.collect();
+    assert_eq!(collected, vec!["漢", "字", "の", "「か", "な」、", "ちょっ", "と"]);
+
+    assert!(CjkBreaker.hyphenate("Latin text").breaks.is_empty());
+}
+
+#[test]
+fn soft_hyphen_stripping() {
+    use kl_hyphenate::hyphenator::strip_soft_hyphens;
+
+    let original = "ri\u{00ad}bo\u{00ad}nu\u{00ad}cle\u{00ad}ase";
+    let stripped = strip_soft_hyphens(original);
+    assert_eq!(stripped.text, "ribonuclease");
+
+    let breaks : Vec<_> = EN_US.hyphenate(&stripped.text).breaks.into_iter()
+        .map(|i| stripped.realign(i)).collect();
+    assert_eq!(breaks, vec![2, 10, 15]);
+    let prefixes : Vec<_> = breaks.iter().map(|&i| &original[.. i]).collect();
+    assert_eq!(prefixes, vec!["ri", "ri\u{00ad}bo\u{00ad}nu", "ri\u{00ad}bo\u{00ad}nu\u{00ad}cle"]);
+
+    assert_eq!(strip_soft_hyphens("ribonuclease").realign(4), 4);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_hyphenation() {
+    use kl_hyphenate::parallel::ParHyphenate;
+
+    let words : Vec<&str> = vec!["anfractuous", "hyphenation", "bevies", "sulphur"];
+    let sequential : Vec<_> = words.iter().map(|w| EN_US.hyphenate(w)).collect();
+    assert_eq!(EN_US.par_hyphenate(&words), sequential);
+
+    let words : Vec<&str> = words.iter().cycle().cloned().take(1000).collect();
+    let sequential : Vec<_> = words.iter().map(|w| EN_US.hyphenate(w)).collect();
+    assert_eq!(EN_US.par_hyphenate_batch(&words), sequential);
+}
+
+#[test]
+fn batches() {
+    let words = ["anfractuous", "HYPHENATION", "ri\u{00ad}bo\u{00ad}nu", "", "sulphur"];
+    let sequential : Vec<_> = words.iter().map(|w| EN_US.hyphenate(w)).collect();
+    assert_eq!(EN_US.hyphenate_batch(&words), sequential);
+    let sequential : Vec<_> = words.iter().map(|w| HU.hyphenate(w)).collect();
+    assert_eq!(HU.hyphenate_batch(&words), sequential);
+}
+
+#[test]
+fn language_tags() {
+    let tags = vec![
+        ("en", Some(EnglishUS)), ("en-GB", Some(EnglishGB)), ("en_AU", Some(EnglishGB)),
+        ("de", Some(German1996)), ("de-1901", Some(German1901)), ("de-CH", Some(GermanSwiss)),
+        ("sr-Latn", Some(SerbocroatianLatin)), ("sr-Cyrl-RS", Some(SerbianCyrillic)),
+        ("pt-BR", Some(Portuguese)), ("el-polyton", Some(GreekPoly)), ("el-GR", Some(GreekMono)),
+        ("la-x-classic", Some(LatinClassic)), ("zh-Latn-pinyin", Some(Chinese)),
+        ("zh-Hans", None), ("nb-NO", Some(NorwegianBokmal)), ("fr-u-co-phonebk", Some(French)),
+        ("tlh", None), ("", None)
+    ];
+    for (tag, expected) in tags {
+        assert_eq!(Language::from_tag(tag), expected, "{}", tag);
+    }
+}
+
+#[test]
+fn language_metadata() {
+    use kl_hyphenate::{Script, Direction};
+
+    assert_eq!(GreekPoly.script(), Some(Script::Greek));
+    assert_eq!(Sanskrit.script().map(|s| s.code()), Some("Deva"));
+    assert_eq!(Russian.direction(), Some(Direction::LeftToRight));
+    assert_eq!((German1901.iso_639_1(), German1901.iso_639_3()), (Some("de"), Some("deu")));
+    assert_eq!((Friulan.iso_639_1(), Friulan.iso_639_3()), (None, Some("fur")));
+    assert_eq!(NorwegianBokmal.english_name(), "Norwegian Bokmål");
+    assert_eq!(French.native_name(), "Français");
+}
+
+#[test]
+fn language_codes_round_trip() {
+    for &lang in &[EnglishUS, German1901, Chinese, LatinClassic, Ethiopic] {
+        assert_eq!(lang.to_string().parse(), Ok(lang));
+    }
+    assert_eq!("DE-1996".parse(), Ok(German1996));
+    assert!("German1996".parse::<Language>().is_err());
+}
+
+#[test]
+fn other_languages() {
+    let klingon = Language::Other("tlh");
+    assert_eq!(Language::other(&String::from("tlh")), klingon);
+    assert_eq!((klingon.code(), klingon.iso_639_3(), klingon.script()), ("tlh", Some("tlh"), None));
+
+    let mut dict = fiat_std(EnglishUS);
+    dict.language = klingon;
+    let buffer = Format::Bincode.serialize(&dict).unwrap();
+    let loaded = Standard::from_reader(klingon, &mut &buffer[..]).unwrap();
+    assert_eq!(loaded.language, klingon);
+    assert!(Standard::from_reader(Language::other("x-klingon"), &mut &buffer[..]).is_err());
+}
+
+#[test]
+fn minima_overrides() {
+    let en_us = fiat_std(EnglishUS);
+    assert!(!en_us.overrides_minima());
+
+    let en_us = en_us.with_minima((3, 3));
+    assert!(en_us.overrides_minima());
+    assert_eq!(en_us.hyphenate("hyphenation").breaks, vec![6, 7]);
+
+    let buffer = Format::Bincode.serialize(&en_us).unwrap();
+    let loaded = Standard::from_reader(EnglishUS, &mut &buffer[..]).unwrap();
+    assert_eq!(loaded.unbreakable_chars(), (3, 3));
+}
+
+#[test]
+fn accept_language_negotiation() {
+    use kl_hyphenate::negotiate::negotiate;
+
+    let available = [EnglishGB, EnglishUS, French, GermanSwiss];
+    assert_eq!(negotiate("fr-CH, fr;q=0.9, en;q=0.8", &available), Some(French));
+    assert_eq!(negotiate("de;q=0.5, en-AU;q=0.8", &available), Some(EnglishGB));
+    assert_eq!(negotiate("de-DE", &available), Some(GermanSwiss));
+    assert_eq!(negotiate("it, *;q=0.1, en-GB;q=0, en-US;q=0", &available), Some(French));
+    assert_eq!(negotiate("it, es;q=0.5", &available), None);
+    assert_eq!(negotiate("fr;q=2, en-US", &available), Some(EnglishUS));
+}
+
+#[test]
+fn registry_caching() {
+    use std::sync::Arc;
+    use kl_hyphenate::registry::{Registry, Source};
+
+    let registry : Registry<Extended> = Registry::new()
+        .source(Source::Embedded)
+        .source(Source::Dir("nonexistent".into()))
+        .source(Source::Dir(DICTIONARIES.into()));
+    assert_eq!(registry.available(), vec![Catalan, Dutch, German1901, Hungarian, NorwegianBokmal, NorwegianNynorsk, Swedish]);
+
+    let hu = registry.get(Hungarian).unwrap();
+    assert!(Arc::ptr_eq(&hu, &registry.get(Hungarian).unwrap()));
+    assert_eq!(registry.loaded(), vec![Hungarian]);
+    assert!(registry.get(EnglishUS).is_err());
+
+    registry.evict(Hungarian);
+    assert!(registry.loaded().is_empty());
+}
+
+#[test]
+fn loading_from_bytes() {
+    let bytes = std::fs::read(dictionary("hu.extended.bincode")).unwrap();
+    let hu = Extended::from_bytes(Hungarian, &bytes).unwrap();
+    assert_eq!(&hu, &*HU);
+    assert!(Extended::from_bytes(Catalan, &bytes).is_err());
+    assert!(Extended::from_bytes(Hungarian, &bytes[.. 64]).is_err());
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn loading_from_mapped_files() {
+    let hu = Extended::from_mapped(Hungarian, dictionary("hu.extended.bincode")).unwrap();
+    assert_eq!(&hu, &*HU);
+
+    use kl_hyphenate::packed::{self, Mapped};
+    let path = env::temp_dir().join("kl-hyphenate-mapped.tr.packed");
+    fs::write(&path, packed::pack(&TR)).unwrap();
+    let mapped = Mapped::from_path(Turkish, &path).unwrap();
+    let tr = mapped.packed();
+    for word in ["hükümet", "İSTANBUL", "işçilerimizin"].iter() {
+        assert_eq!(tr.hyphenate(word), TR.hyphenate(word));
+    }
+    assert!(Mapped::from_path(EnglishUS, &path).is_err());
+    match Standard::from_mapped(Turkish, &path) {
+        Err(Error::Malformed(_)) => (),
+        other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+    }
+    drop(mapped);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn packed_dictionaries() {
+    use kl_hyphenate::packed::{self, Packed};
+
+    let bytes = packed::pack(&TR);
+    let tr = Packed::from_bytes(Turkish, &bytes).unwrap();
+    let words = ["hükümet", "İSTANBUL", "ka\u{00ad}rar", "ol", "işçilerimizin", "x"];
+    for word in words.iter() {
+        assert_eq!(tr.hyphenate(word), TR.hyphenate(word));
+    }
+    fn property(word : String) -> bool {
+        let bytes = packed::pack(&EN_US);
+        let en_us = Packed::from_bytes(EnglishUS, &bytes).unwrap();
+        en_us.hyphenate(&word) == EN_US.hyphenate(&word)
+    }
+    quickcheck(property as fn(String) -> bool);
+
+    assert!(Packed::from_bytes(EnglishUS, &bytes).is_err());
+    assert!(Packed::from_bytes(Turkish, &bytes[.. bytes.len() / 2]).is_err());
+    let mut stale = bytes.clone();
+    stale[4 .. 8].copy_from_slice(&(packed::VERSION + 1).to_le_bytes());
+    match Packed::from_bytes(Turkish, &stale) {
+        Err(Error::VersionMismatch { found : Some(found), .. }) => assert_eq!(found, packed::VERSION + 1),
+        other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+    }
+    match kl_hyphenate::disk::OnDisk::any_open(::std::io::Cursor::new(&stale)) {
+        Err(Error::VersionMismatch { .. }) => (),
+        other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+    }
+
+    let bytes = packed::pack(&EN_US);
+    let (mut packed, mut en_us) = (Packed::from_bytes(EnglishUS, &bytes).unwrap(), EN_US.clone());
+    for (word, _) in EN_US.exceptions.iter() {
+        assert_eq!(packed.hyphenate(word), EN_US.hyphenate(word));
+    }
+    assert_eq!(packed.add_exact_marked("co-associative"), en_us.add_exact_marked("co-associative"));
+    assert_eq!(packed.hyphenate("coassociative"), en_us.hyphenate("coassociative"));
+    assert_eq!(packed.remove_exact("coassociative"), en_us.remove_exact("coassociative"));
+    assert_eq!(packed.hyphenate("coassociative"), en_us.hyphenate("coassociative"));
+    assert_eq!(packed.remove_exact("hyphenation"), en_us.remove_exact("hyphenation"));
+    packed.clear_exceptions();
+    en_us.clear_exceptions();
+    assert_eq!(packed.hyphenate("bevies"), en_us.hyphenate("bevies"));
+}
+
+#[cfg(any(feature = "postcard", feature = "cbor"))]
+#[test]
+fn alternative_formats() {
+    let formats = [
+        #[cfg(feature = "postcard")] Format::Postcard,
+        #[cfg(feature = "cbor")] Format::Cbor
+    ];
+    for &format in formats.iter() {
+        let bytes = format.serialize(&*EN_US).unwrap();
+        assert_eq!(Format::detect(&bytes), Some(format));
+        let en_us = Standard::from_bytes(EnglishUS, &bytes).unwrap();
+        assert_eq!(&en_us, &*EN_US);
+        assert!(Standard::from_bytes(Turkish, &bytes).is_err());
+    }
+    let mut bytes = Format::Bincode.serialize(&*TR).unwrap();
+    assert_eq!(Format::detect(&bytes), Some(Format::Bincode));
+    bytes[8] = 0xff;
+    match Format::Bincode.deserialize::<Standard>(&bytes) {
+        Err(Error::Malformed(_)) => (),
+        other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+    }
+}
+
+#[test]
+fn dictionary_archives() {
+    use kl_hyphenate::archive::{self, Archive};
+
+    let bytes = archive::archive_dir(DICTIONARIES).unwrap();
+    let archive = Archive::from_bytes(&bytes).unwrap();
+    assert!(archive.languages::<Standard>().contains(&Turkish));
+    assert_eq!(archive.languages::<Extended>(), vec![Catalan, German1901, Hungarian, NorwegianBokmal, Dutch, NorwegianNynorsk, Swedish]);
+    let tr : Standard = archive.load(Turkish).unwrap();
+    assert_eq!(&tr, &*TR);
+    assert!(archive.load::<Extended>(Turkish).is_err());
+
+    assert!(Archive::from_bytes(&bytes[.. bytes.len() - 1]).is_err());
+    let empty = archive::archive(vec![]);
+    assert_eq!(Archive::from_bytes(&empty).unwrap().names().count(), 0);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_conversion() {
+    use kl_hyphenate::json::Json;
+
+    let json = EN_US.to_json();
+    assert!(json.contains(r#""language": "en-us""#));
+    let en_us = Standard::from_json(&json).unwrap();
+    assert_eq!(en_us.to_json(), json);
+    for word in ["hyphenation", "anfractuous", "associate", "project"].iter() {
+        assert_eq!(en_us.hyphenate(word).breaks, EN_US.hyphenate(word).breaks);
+    }
+
+    let hu = fiat_ext(Hungarian);
+    let json = hu.to_json();
+    let loaded = Extended::from_json(&json).unwrap();
+    assert_eq!(loaded.to_json(), json);
+    assert_eq!(loaded.hyphenate("asszonnyal").breaks, hu.hyphenate("asszonnyal").breaks);
+    assert!(Standard::from_json("{}").is_err());
+
+    // Patterns refer to their tally by a `u16`, which numbers every tally of
+    // a single locus, and the empty tally no more.
+    let mut patterns : Vec<String> = (0 ..= u16::MAX as usize).map(|i| format!(
+        r#""p{}": [{{ "index": {}, "value": {} }}]"#, i, i / 256, i % 256)).collect();
+    patterns.push(r#""p": []"#.to_owned());
+    let json = format!(r#"{{ "language": "en-us", "minima": [2, 3], "patterns": {{ {} }}, "exceptions": {{}} }}"#,
+                       patterns.join(", "));
+    match Standard::from_json(&json) {
+        Err(Error::Malformed(_)) => (),
+        other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+    }
+}
+
+#[test]
+fn tex_export() {
+    use kl_hyphenate::export::Export;
+    use kl_hyphenate_commons::dictionary::{Exceptions, Patterns};
+    use kl_hyphenate_commons::dictionary::extended as ext;
+    use kl_hyphenate_commons::parse::Parse;
+
+    let same = |s : &str| s.to_owned();
+    let patterns = EN_US.tex_patterns();
+    assert!(patterns.contains(&"hy3ph".to_owned()));
+    let entries = EN_US.patterns.entries();
+    assert_eq!(patterns.len(), entries.len());
+    for (pattern, (letters, tally)) in patterns.iter().zip(entries) {
+        assert_eq!(Patterns::pair(pattern, same), (letters, tally.clone()));
+    }
+    let exceptions = EN_US.tex_exceptions();
+    assert!(exceptions.contains(&"co-asso-cia-tive".to_owned()));
+    for exception in exceptions {
+        let (word, breaks) = Exceptions::pair(&exception, same);
+        assert_eq!(EN_US.exceptions.0.get(&word), Some(&breaks));
+    }
+    assert!(EN_US.to_tex().starts_with("\\patterns{\n"));
+
+    let hu = fiat_ext(Hungarian);
+    let patterns = hu.tex_patterns();
+    assert!(patterns.contains(&"as5szon2y/sz=,2,1".to_owned()));
+    for (pattern, (letters, tally)) in patterns.iter().zip(hu.patterns.entries()) {
+        assert_eq!(&ext::Patterns::pair(pattern, same), &(letters, tally.clone()));
+    }
+}
+
+#[test]
+fn tex_parsing() {
+    use kl_hyphenate::export::Export;
+
+    let tex = EN_US.to_tex();
+    let en_us = Standard::from_tex(EnglishUS, &mut tex.as_bytes()).unwrap();
+    assert_eq!(en_us.patterns.entries(), EN_US.patterns.entries());
+    // TeX splits multiword exceptions into words of their own, which are
+    // lowercased as they are read.
+    for (word, breaks) in EN_US.exceptions.0.iter().filter(|&(word, _)| !word.contains(' ')) {
+        assert_eq!(en_us.exceptions.0.get(&word.to_lowercase()), Some(breaks));
+    }
+    for word in ["hyphenation", "anfractuous", "coassociative", "project"].iter() {
+        assert_eq!(en_us.hyphenate(word).breaks, EN_US.hyphenate(word).breaks);
+    }
+
+    let tex = r"
+        % Only the first group is the patterns.
+        \message{hy3ph}
+        \patterns{ hy3ph % he2n
+          he2n}\hyphenation{ta-ble Ba-na-na}
+    ";
+    let custom = Standard::from_tex(EnglishUS, &mut tex.as_bytes()).unwrap();
+    assert_eq!(custom.tex_patterns(), vec!["he2n", "hy3ph"]);
+    assert_eq!(custom.tex_exceptions(), vec!["ba-na-na", "ta-ble"]);
+    assert_eq!(custom.hyphenate("Banana").breaks, vec![2]);
+    assert!(Standard::from_tex(EnglishUS, &mut r"\patterns{ hy3ph".as_bytes()).is_err());
+}
+
+#[test]
+fn building_from_patterns() {
+    use kl_hyphenate_commons::dictionary::Locus;
+
+    let dict = Standard::from_pattern_strings(EnglishUS, &["hy3ph", "he2n", "hy3ph"]).unwrap();
+    assert_eq!(dict.patterns.entries().len(), 2);
+    assert_eq!(dict.hyphenate("hyphen").breaks, vec![2]);
+
+    let pairs = vec![("hyph".to_owned(), vec![Locus { index : 2, value : 3 }])];
+    let from_pairs = Standard::from_pairs(EnglishUS, pairs).unwrap();
+    assert_eq!(from_pairs.hyphenate("hyphen").breaks, vec![2]);
+}
+
+#[test]
+fn libhyphen_dictionaries() {
+    use kl_hyphenate::export::Export;
+    use kl_hyphenate_commons::parse::Dic;
+
+    let dic = b"ISO8859-1\n% A comment\nLEFTHYPHENMIN 1\nRIGHTHYPHENMIN 2\n\
+                COMPOUNDLEFTHYPHENMIN 3\nhy3ph\nt\xe91\nc1c/c=,1,1\nNEXTLEVEL\n1-1\n";
+    let parsed = Dic::parse(dic).unwrap();
+    assert_eq!(parsed.encoding, "ISO8859-1");
+    assert_eq!((parsed.minima, parsed.compound_minima), ((Some(1), Some(2)), (Some(3), None)));
+    assert_eq!(parsed.patterns, vec!["hy3ph", "t\u{e9}1", "c1c/c=,1,1"]);
+    assert_eq!(parsed.next_level, vec!["1-1"]);
+
+    let dict = Standard::from_dic(French, &mut &dic[..]).unwrap();
+    assert_eq!(dict.minima, (1, 2));
+    assert_eq!(dict.tex_patterns(), vec!["c1c", "hy3ph", "t\u{e9}1"]);
+    assert_eq!(dict.hyphenate("t\u{e9}t\u{e9}").breaks, vec![3]);
+    assert!(Standard::from_dic(French, &mut &b"KOI8-R\n"[..]).is_err());
+}
+
+#[test]
+fn libhyphen_nonstandard_patterns() {
+    let patterns = std::fs::read_to_string("patterns/hyph-hu.ext.txt").unwrap();
+    let dic = format!("UTF-8\nLEFTHYPHENMIN 2\n{}", patterns);
+    let hu = Extended::from_dic(Hungarian, &mut dic.as_bytes()).unwrap();
+    for word in ["asszonnyal", "esszé", "kulccsal", "hosszú"].iter() {
+        let segments : Vec<_> = hu.hyphenate(word).into_iter().segments().collect();
+        let expected : Vec<_> = HU.hyphenate(word).into_iter().segments().collect();
+        assert_eq!(segments, expected);
+    }
+
+    let dic = "UTF-8\nschif1fahrt/ff=f,5,2\n";
+    let de = Extended::from_dic(German1901, &mut dic.as_bytes()).unwrap();
+    let segments : Vec<_> = de.hyphenate("schiffahrt").into_iter().segments().collect();
+    assert_eq!(segments, vec!["schiff", "fahrt"]);
+    assert!(Extended::from_dic(German1901, &mut &b"UTF-8\nschif1fahrt/ff=f,9,9\n"[..]).is_err());
+}
+
+#[test]
+fn tex_exception_blocks() {
+    let class = r"
+        \hyphenation{% Common in journal classes
+          as-so-ciate as-so-ciates
+          Dijk-stra}
+    ";
+    let mut en_us = EN_US.clone();
+    assert_eq!(en_us.add_tex_exceptions(class), Ok(3));
+    assert_eq!(en_us.hyphenate("associate").breaks, vec![2, 4]);
+    assert_eq!(en_us.hyphenate("Dijkstra").breaks, vec![4]);
+
+    let mut hu = HU.clone();
+    assert_eq!(hu.add_tex_exceptions(r"\hyphenation{asz-szony}"), Ok(1));
+    let segments : Vec<_> = hu.hyphenate("asszony").into_iter().segments().collect();
+    assert_eq!(segments, vec!["asz", "szony"]);
+    assert!(en_us.add_tex_exceptions(r"\hyphenation{ta-ble").is_err());
+}
+
+#[test]
+fn marked_exceptions() {
+    let mut en_us = EN_US.clone();
+    assert_eq!(en_us.add_exact_marked("Ta-ble"), None);
+    assert_eq!(en_us.add_exact_marked("ta=ble-"), Some(vec![2]));
+    assert_eq!(en_us.hyphenate("TABLE").breaks, vec![2]);
+
+    let mut hu = HU.clone();
+    hu.add_exact_marked("őr-ült");
+    assert_eq!(hu.hyphenate("őrült").breaks, vec![(3, None)]);
+}
+
+#[test]
+fn exception_imports() {
+    let list = b"ta-ble\r\n\nas-so-ciate\nYingyong Shuxue\n\xffoops\nDijk-stra";
+    let mut en_us = EN_US.clone();
+    let import = en_us.add_exceptions_from(&list[..]).unwrap();
+    assert_eq!(import.added, 3);
+    assert_eq!(import.errors, vec![(4, "the line holds more than one word"),
+                                   (5, "the line is not valid UTF-8")]);
+    assert_eq!(en_us.hyphenate("table").breaks, vec![2]);
+    assert_eq!(en_us.hyphenate("Dijkstra").breaks, vec![4]);
+}
+
+#[test]
+fn exception_removal() {
+    let mut en_us = EN_US.clone();
+    en_us.add_exact_marked("ta-ble");
+    assert_eq!(en_us.remove_exact("table"), Some(vec![2]));
+    assert_eq!(en_us.remove_exact("table"), None);
+    assert_eq!(en_us.hyphenate("table").breaks, EN_US.hyphenate("table").breaks);
+
+    assert_eq!(en_us.hyphenate("coassociative").breaks, vec![2, 6, 9]);
+    en_us.clear_exceptions();
+    assert!(en_us.exceptions.is_empty());
+    assert_ne!(en_us.hyphenate("coassociative").breaks, vec![2, 6, 9]);
+}
+
+#[test]
+fn exception_iteration() {
+    let mut en_us = EN_US.clone();
+    en_us.clear_exceptions();
+    en_us.add_exact_marked("ta-ble");
+    en_us.add_exact_marked("as-so-ciate");
+    let mut exceptions : Vec<_> = en_us.exceptions.iter().collect();
+    exceptions.sort();
+    assert_eq!(exceptions, vec![("associate", &[2, 4][..]), ("table", &[2][..])]);
+    assert_eq!(EN_US.exceptions.iter().count(), EN_US.exceptions.len());
+}
+
+#[test]
+fn saving() {
+    let mut en_us = EN_US.clone().with_minima((3, 3));
+    en_us.add_exact_marked("ta-ble");
+    let path = env::temp_dir().join("kl-hyphenate-saving.standard.bincode");
+    en_us.save(&path).unwrap();
+    let saved = Standard::from_path(EnglishUS, &path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(saved, en_us);
+
+    let mut bytes = vec![];
+    HU.write_to(&mut bytes).unwrap();
+    assert_eq!(Extended::from_bytes(Hungarian, &bytes).unwrap(), *HU);
+}
+
+#[test]
+fn exception_overlays() {
+    use kl_hyphenate::overlay::Overlay;
+    use std::sync::Arc;
+
+    let base = Arc::new(EN_US.clone());
+    let mut tenant = Overlay::new(base.clone());
+    tenant.add_exact_marked("ta-ble");
+    assert_eq!(tenant.hyphenate("Table").breaks, vec![2]);
+    assert_eq!(tenant.hyphenate("coassociative").breaks, vec![2, 6, 9]);
+    assert_eq!(tenant.remove_exact("coassociative"), Some(vec![2, 6, 9]));
+    assert_eq!(tenant.remove_exact("coassociative"), None);
+    assert!(tenant.suppresses("coassociative"));
+    assert_ne!(tenant.hyphenate("coassociative").breaks, vec![2, 6, 9]);
+    assert_eq!(base.hyphenate("coassociative").breaks, vec![2, 6, 9]);
+    assert_ne!(base.hyphenate("table").breaks, vec![2]);
+
+    let mut hu = Overlay::new(&*HU);
+    hu.add_exact_marked("ass-zony");
+    assert_eq!(hu.hyphenate("asszony").breaks, vec![(3, None)]);
+    hu.clear_exceptions();
+    assert_eq!(hu.hyphenate("asszony").breaks, HU.hyphenate("asszony").breaks);
+}
+
+#[test]
+fn programmatic_subregions() {
+    let subr = Subregion::new(2, 1, "szsz", 2);
+    assert_eq!((subr.left(), subr.right(), subr.substitution(), subr.breakpoint()), (2, 1, "szsz", 2));
+    assert_eq!(subr.span(3), Some(1 .. 4));
+
+    let mut hu = HU.clone();
+    hu.add_exact("asszony".to_owned(), vec![(3, Some(subr))]);
+    let segments : Vec<_> = hu.hyphenate("asszony").into_iter().segments().collect();
+    assert_eq!(segments, vec!["asz", "szony"]);
+}
+
+#[test]
+fn extended_spellings() {
+    let spellings : Vec<_> = HU.hyphenate("asszonnyal").spellings().into_iter()
+        .map(|(fore, aft)| (fore.into_owned(), aft.into_owned())).collect();
+    assert_eq!(spellings, vec![("asz".to_owned(), "szonnyal".to_owned()),
+                               ("asszony".to_owned(), "nyal".to_owned())]);
+}
+
+#[test]
+fn applied_breaks() {
+    let word = EN_US.hyphenate("coassociative");
+    assert_eq!(word.apply(word.breaks[1]), ("coasso-".to_owned(), "ciative".to_owned()));
+    assert_eq!(word.apply_marked(2, "\u{2010}"), ("co\u{2010}".to_owned(), "associative".to_owned()));
+
+    let word = HU.hyphenate("asszonnyal");
+    assert_eq!(word.apply(word.breaks[0]), ("asz-".to_owned(), "szonnyal".to_owned()));
+    assert_eq!(word.apply((1, None)), ("a-".to_owned(), "sszonnyal".to_owned()));
+}
+
+#[test]
+fn discretionaries() {
+    use kl_hyphenate::discretionary::Discretionary;
+
+    let word = EN_US.hyphenate("coassociative");
+    assert_eq!(word.discretionaries()[0], Discretionary {
+        span : 2 .. 2, pre : "-".to_owned(), post : String::new(), no_break : String::new()
+    });
+
+    let word = HU.hyphenate("asszonnyal");
+    let discretionaries = word.discretionaries_marked("=");
+    assert_eq!(discretionaries[0], Discretionary {
+        span : 1 .. 2, pre : "sz=".to_owned(), post : String::new(), no_break : "s".to_owned()
+    });
+    assert_eq!(discretionaries[1], Discretionary {
+        span : 5 .. 8, pre : "ny=".to_owned(), post : "ny".to_owned(), no_break : "nny".to_owned()
+    });
+}
+
+#[test]
+fn extended_levels() {
+    use kl_hyphenate::hyphenator::Levels;
+
+    let leveled = HU.hyphenate_leveled("asszonnyal");
+    let breaks : Vec<_> = leveled.breaks.iter().map(|&(op, _)| op).collect();
+    assert_eq!(breaks, HU.hyphenate("asszonnyal").breaks);
+    assert!(leveled.breaks.iter().all(|&(_, level)| level.is_some_and(|v| v % 2 == 1)));
+
+    let mut hu = HU.clone();
+    hu.add_exact_marked("ass-zony");
+    assert_eq!(hu.hyphenate_leveled("asszony").breaks, vec![((3, None), None)]);
+    assert_eq!(HU.hyphenate_leveled("asz\u{00ad}szony").breaks, vec![((3, None), None)]);
+}
+
+#[test]
+fn unified_opportunities() {
+    use kl_hyphenate::hyphenator::Levels;
+    use kl_hyphenate::opportunity::{Opportunity, Unified};
+
+    fn indices<'h, H : Unified<'h>>(dict : &'h H, word : &str) -> Vec<usize> {
+        dict.hyphenate_unified(word).breaks.iter().map(|op| op.index).collect()
+    }
+
+    assert_eq!(indices(&*EN_US, "hyphenation"), EN_US.hyphenate("hyphenation").breaks);
+    let standard = EN_US.hyphenate_unified("hyphenation");
+    assert!(standard.breaks.iter().all(|op| op.substitution.is_none()));
+    let levels : Vec<_> = EN_US.hyphenate_leveled("hyphenation").breaks.into_iter().map(|(_, l)| l).collect();
+    assert_eq!(standard.breaks.iter().map(|op| op.level).collect::<Vec<_>>(), levels);
+
+    let extended = HU.hyphenate_unified("asszonnyal");
+    assert_eq!(extended.breaks[1], Opportunity {
+        index : 6, level : extended.breaks[1].level, substitution : HU.hyphenate("asszonnyal").breaks[1].1
+    });
+    assert!(extended.breaks[1].level.is_some());
+    assert_eq!(extended.discretionaries(), HU.hyphenate("asszonnyal").discretionaries());
+}
+
+#[test]
+fn runtime_extended_dictionaries() {
+    use kl_hyphenate_commons::dictionary::extended as ext;
+
+    let exceptions = ext::Exceptions::from_strings(&["Schiffahrt schiff-fahrt", "ba-cken"]);
+    let dict = Extended::from_pattern_strings(German1901, &["c1k/k=k,1,2", "1fa"]).unwrap()
+        .with_exceptions(exceptions);
+    let zucker = dict.hyphenate("Zucker");
+    assert_eq!(zucker.apply(zucker.breaks[0]), ("Zuk-".to_owned(), "ker".to_owned()));
+    assert_eq!(dict.hyphenate("backen").breaks, vec![(2, None)]);
+    assert_eq!(respellings(&dict, "schiffahrt"), vec![(6, Some("f".to_owned()))]);
+
+    assert!(Extended::from_pattern_strings(German1901, &["c1k/k=k,9,2"]).is_err());
+    let pairs = vec![kl_hyphenate_commons::parse::extended_pair("c1k/k=k,1,2").unwrap()];
+    assert_eq!(Extended::from_pairs(German1901, pairs).unwrap().hyphenate("Zucker").breaks,
+               dict.hyphenate("zucker").breaks);
+}
+
+#[test]
+fn turkish_case_folding() {
+    let tr = fiat_std(Turkish);
+    assert_eq!(tr.hyphenate("kırmızı").breaks, vec![4, 7]);
+    assert_eq!(tr.hyphenate("KIRMIZI").breaks, vec![3, 5]);
+    let segments : Vec<_> = tr.hyphenate("İSTANBUL").into_iter().segments().collect();
+    assert_eq!(segments, tr.hyphenate("istanbul").into_iter().segments()
+        .map(|s| s.to_uppercase().replace('I', "İ")).collect::<Vec<_>>());
+
+    let mut tr = tr;
+    tr.add_exact_marked("KI-RIM");
+    assert_eq!(tr.exceptions.0.get("kırım"), Some(&vec![3]));
+    assert_eq!(tr.hyphenate("KIRIM").breaks, vec![2]);
+}
+
+#[test]
+fn full_case_folding() {
+    use kl_hyphenate::CaseFolding;
+
+    let lowercase = Standard::from_pattern_strings(German1996, &["a1s", "s1s", "r1a"]).unwrap();
+    assert_eq!(lowercase.hyphenate("STRAẞE").breaks, vec![3]);
+    let full = lowercase.with_case_folding(CaseFolding::Full);
+    assert_eq!(full.hyphenate("strasse").breaks, vec![3, 4, 5]);
+    assert_eq!(full.hyphenate("STRAẞE").breaks, vec![3, 4]);
+    let segments : Vec<_> = full.hyphenate("Straße").into_iter().segments().collect();
+    assert_eq!(segments, vec!["Str", "a", "ße"]);
+
+    let bytes = kl_hyphenate::packed::pack(&full);
+    let mut on_disk = kl_hyphenate::disk::OnDisk::open(German1996, ::std::io::Cursor::new(&bytes)).unwrap();
+    assert_eq!(on_disk.case_folding, CaseFolding::Lowercase);
+    on_disk.case_folding = CaseFolding::Full;
+    assert_eq!(on_disk.hyphenate("STRAẞE").breaks, vec![3, 4]);
+}
+
+#[test]
+fn vocabularies() {
+    use kl_hyphenate::vocabulary::Vocabulary;
+
+    let parts = Vocabulary::from_marked(["Ti-tan-ox-id", "Flansch-dich-tung"]);
+    assert_eq!(parts.hyphenate("TITANOXID").breaks, vec![2, 5, 7]);
+    assert_eq!(parts.hyphenate("Flanschdichtung").breaks, vec![7, 11]);
+    assert!(parts.hyphenate("Stahlrohr").breaks.is_empty());
+    assert_eq!(parts.hyphenate("Ti\u{ad}tanoxid").breaks, vec![2]);
+    assert_eq!(parts.hyphenate("(Titanoxid),").breaks, vec![3, 6, 8]);
+}
+
+#[test]
+fn merging() {
+    use kl_hyphenate_commons::dictionary::Locus;
+
+    let house = Standard::from_pattern_strings(EnglishUS, &["hy5ph", "4table"]).unwrap();
+    let mut house = house.with_minima((3, 3));
+    house.add_exact_marked("co-as-so-ci-a-tive");
+    let merged = EN_US.merge(&house).unwrap();
+    assert_eq!(merged.minima, EN_US.minima);
+    assert_eq!(merged.patterns.entries().len(), EN_US.patterns.entries().len() + 1);
+    assert!(merged.patterns.entries().contains(&("hyph".to_owned(), &vec![Locus { index : 2, value : 5 }])));
+    assert_eq!(merged.hyphenate("coassociative").breaks, vec![2, 4, 6, 8, 9]);
+    assert_eq!(merged.exceptions.len(), EN_US.exceptions.len());
+
+    let merged = HU.merge(&Extended::from_dic(Hungarian, &mut &b"UTF-8\nxq1qx/q=q,2,2"[..]).unwrap()).unwrap();
+    assert_eq!(merged.patterns.entries().len(), HU.patterns.entries().len() + 1);
+}
+
+#[test]
+fn concurrent_exceptions() {
+    use kl_hyphenate::overlay::Concurrent;
+    use std::sync::Arc;
+    use std::thread;
+
+    let shared = Arc::new(Concurrent::new(Arc::new(EN_US.clone())));
+    let writers : Vec<_> = ["ta-ble", "as-so-ciate"].iter().map(|&marked| {
+        let shared = shared.clone();
+        thread::spawn(move || shared.insert_marked(marked))
+    }).collect();
+    for writer in writers {
+        assert_eq!(writer.join().unwrap(), None);
+    }
+    assert_eq!(shared.hyphenate("Table").breaks, vec![2]);
+    assert_eq!(shared.hyphenate("associate").breaks, vec![2, 4]);
+    assert_eq!(shared.hyphenate("coassociative").breaks, vec![2, 6, 9]);
+    assert_eq!(shared.remove("table"), Some(vec![2]));
+    assert_eq!(shared.exceptions().len(), 1);
+}
+
+#[test]
+fn shared_clones() {
+    use std::sync::Arc;
+
+    let mut en_us = EN_US.clone();
+    assert!(Arc::ptr_eq(&en_us.patterns, &EN_US.patterns));
+    assert!(Arc::ptr_eq(&en_us.exceptions, &EN_US.exceptions));
+    en_us.add_exact_marked("ta-ble");
+    assert!(!Arc::ptr_eq(&en_us.exceptions, &EN_US.exceptions));
+    assert_eq!(EN_US.exceptions.0.get("table"), None);
+}
+
+#[test]
+fn caching() {
+    use kl_hyphenate::cache::{Cached, Stats};
+
+    let mut en_us = Cached::new(EN_US.clone(), 2);
+    for word in &["anfractuous", "table", "anfractuous", "Table", "table"] {
+        assert_eq!(en_us.hyphenate(word), EN_US.hyphenate(word));
+    }
+    assert_eq!(en_us.stats(), Stats { hits : 1, misses : 4, len : 2 });
+    assert_eq!(en_us.hyphenate("Table").breaks, EN_US.hyphenate("Table").breaks);
+    assert_eq!(en_us.stats().hits, 2);
+
+    en_us.add_exact_marked("ta-ble");
+    assert_eq!(en_us.stats().len, 0);
+    assert_eq!(en_us.hyphenate("table").breaks, vec![2]);
+
+    let en_us = Cached::new(EN_US.clone(), 2);
+    let list = "# English\nthe 23135851162\n\n3463405 hyphenation\nanfractuous\t12\ntable 10\n";
+    assert_eq!(en_us.warm_from_list(list.as_bytes()).unwrap(), 2);
+    en_us.hyphenate("hyphenation");
+    en_us.hyphenate("the");
+    en_us.hyphenate("anfractuous");
+    assert_eq!(en_us.stats(), Stats { hits : 2, misses : 1, len : 2 });
+}
+
+#[test]
+fn format_versions() {
+    let mut bytes = Format::Bincode.serialize(&*TR).unwrap();
+    assert_eq!(&bytes[.. 4], kl_hyphenate::load::MAGIC);
+
+    let mut corrupted = bytes.clone();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 1;
+    for loaded in [Standard::from_bytes(Turkish, &corrupted),
+                   Standard::from_reader(Turkish, &mut &corrupted[..])] {
+        match loaded {
+            Err(Error::ChecksumMismatch { .. }) => (),
+            other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+        }
+    }
+    assert!(Standard::from_trusted_bytes(Turkish, &bytes).is_ok());
+
+    bytes[4 .. 8].copy_from_slice(&0u32.to_le_bytes());
+    match Standard::from_bytes(Turkish, &bytes) {
+        Err(Error::VersionMismatch { found : Some(0), .. }) => (),
+        other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+    }
+    match Standard::from_reader(Turkish, &mut &bytes[8 ..]) {
+        Err(Error::VersionMismatch { found : None, .. }) => (),
+        other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+    }
+}
+
+#[test]
+fn loading_from_trait_objects() {
+    let file = File::open(dictionary("tr.standard.bincode")).unwrap();
+    let mut reader : Box<dyn std::io::Read> = Box::new(BufReader::new(file));
+    let tr = Standard::from_reader(Turkish, &mut *reader).unwrap();
+    assert_eq!(&tr, &*TR);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn registry_fetching() {
+    use std::future::Future;
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Waker};
+    use kl_hyphenate::registry::Registry;
+
+    // Yields once before reading the dictionary.
+    struct Deferred(bool);
+    impl Future for Deferred {
+        type Output = io::Result<Vec<u8>>;
+        fn poll(mut self : Pin<&mut Self>, cx : &mut Context) -> Poll<Self::Output> {
+            if self.0 { return Poll::Ready(std::fs::read(dictionary("tr.standard.bincode"))) }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    let registry : Registry<Standard> = Registry::new();
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut first = registry.fetch(Turkish, |_| Deferred(false));
+    let mut second = registry.fetch(Turkish, |_| -> Deferred { panic!("fetched twice") });
+
+    assert!(Pin::new(&mut first).poll(&mut cx).is_pending());
+    assert!(Pin::new(&mut second).poll(&mut cx).is_pending());
+    let tr = match Pin::new(&mut first).poll(&mut cx) {
+        Poll::Ready(tr) => tr.unwrap(),
+        Poll::Pending => panic!("fetch did not complete")
+    };
+    match Pin::new(&mut second).poll(&mut cx) {
+        Poll::Ready(shared) => assert!(Arc::ptr_eq(&tr, &shared.unwrap())),
+        Poll::Pending => panic!("fetch was not shared")
+    }
+    assert_eq!(registry.loaded(), vec![Turkish]);
+
+    // Abandoned fetches release their waiters, which fetch in their stead.
+    let mut abandoned = registry.fetch(EnglishUS, |_| Deferred(false));
+    let mut waiting = registry.fetch(EnglishUS, |_| std::future::ready(Ok(vec![])));
+    assert!(Pin::new(&mut abandoned).poll(&mut cx).is_pending());
+    assert!(Pin::new(&mut waiting).poll(&mut cx).is_pending());
+    drop(abandoned);
+    match Pin::new(&mut waiting).poll(&mut cx) {
+        Poll::Ready(result) => assert!(result.is_err()),
+        Poll::Pending => panic!("waiter was not released")
+    }
+}
+
 #[test]
 fn language_mismatch_on_load() {
     let file = File::open("./dictionaries/mul-ethi.standard.bincode").unwrap();
@@ -153,6 +1094,29 @@ fn language_mismatch_on_load() {
     assert!(Standard::from_reader(EnglishUS, &mut reader).is_err());
 }
 
+#[cfg(feature = "embed_all")]
+#[test]
+fn embedded_dictionaries() {
+    assert!(Standard::embedded().contains(&EnglishUS));
+    assert_eq!(Extended::embedded(), vec![Catalan, German1901, Hungarian, NorwegianBokmal, Dutch, NorwegianNynorsk, Swedish]);
+
+    let en_us = Standard::from_embedded(EnglishUS).unwrap();
+    assert_eq!(en_us.hyphenate("hyphenation").breaks, EN_US.hyphenate("hyphenation").breaks);
+    match Extended::from_embedded(EnglishUS) {
+        Err(Error::NotEmbedded(EnglishUS)) => (),
+        other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+    }
+}
+
+#[cfg(feature = "statics")]
+#[test]
+fn static_dictionaries() {
+    let en_us = kl_hyphenate::dictionaries::english_us();
+    assert!(std::ptr::eq(en_us, kl_hyphenate::dictionaries::english_us()));
+    assert_eq!(en_us.hyphenate("hyphenation").breaks, vec![2, 6, 7]);
+    assert_eq!(kl_hyphenate::dictionaries::hungarian_extended().language, Hungarian);
+}
+
 #[test]
 fn text() {
     use unicode_segmentation::UnicodeSegmentation;
@@ -174,3 +1138,762 @@ fn text() {
     assert_eq!(seg1, expect1);
 
 }
+
+#[cfg(feature = "text")]
+#[test]
+fn text_mode() {
+    use kl_hyphenate::text::{is_url, Text};
+
+    let hyphenate_text = |text : &str| -> String {
+        EN_US.hyphenate_text(text).into_iter().collect()
+    };
+
+    let t0 = "I know noble accents / And lucid, inescapable rhythms; […]";
+    let expect0 = "I know no-ble ac-cents / And lu-cid, in-escapable rhythms; […]";
+    assert_eq!(hyphenate_text(t0), expect0);
+
+    let t1 = "See example.com/anfractuous or <anfractuous@example.com>, anfractuous.";
+    let expect1 = "See example.com/anfractuous or <anfractuous@example.com>, an-frac-tu-ous.";
+    assert_eq!(hyphenate_text(t1), expect1);
+
+    for url in ["https://example.com", "(www.example.com)", "example.com/anfractuous"].iter() {
+        assert!(is_url(url), "{}", url);
+    }
+    for word in ["accomplished.Thereafter", "report.pdf", "Node.js", "example.com"].iter() {
+        assert!(!is_url(word), "{}", word);
+    }
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn text_stream() {
+    use std::io::{self, Read};
+    use kl_hyphenate::text::{Stream, Text};
+
+    // A reader which yields at most three bytes at a time, cutting through
+    // words and multibyte sequences alike.
+    struct Trickle<'a>(&'a [u8]);
+    impl<'a> Read for Trickle<'a> {
+        fn read(&mut self, buf : &mut [u8]) -> io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(3);
+            buf[.. n].copy_from_slice(&self.0[.. n]);
+            self.0 = &self.0[n ..];
+            Ok(n)
+        }
+    }
+
+    let text = "Anfractuous rhythms, inescapable — and\nsulphur unconsumed ";
+    let mut output = vec![];
+    Stream::new(&*EN_US).copy(Trickle(text.as_bytes()), &mut output).unwrap();
+    let expected : String = EN_US.hyphenate_text(text).into_iter().collect();
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+    let mut output = vec![];
+    let invalid = Stream::new(&*EN_US).copy(&b"sulphur \xff"[..], &mut output);
+    assert_eq!(invalid.unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[cfg(feature = "detection")]
+#[test]
+fn language_detection() {
+    use kl_hyphenate::detect::Detection;
+    use kl_hyphenate::registry::{Registry, Source};
+    use kl_hyphenate::text::Multilingual;
+
+    let french = "Les sanglots longs des violons de l’automne blessent mon cœur d’une langueur monotone";
+    assert_eq!(Detection::default().detect(french), Some(French));
+
+    let registry = Registry::new().source(Source::Dir(DICTIONARIES.into()));
+    let mut hyphenator = Multilingual::new(&registry);
+    let detected = hyphenator.hyphenate_text_detecting(french);
+    let known = hyphenator.hyphenate_spans(french, &[(0 .. french.len(), French)]);
+    assert!(!detected.breaks.is_empty());
+    assert_eq!(detected, known);
+
+    let undetectable = "anfractuous";
+    let fallback = Detection { threshold : 1.1, fallback : Some(EnglishUS) };
+    assert_eq!(fallback.detect(undetectable), Some(EnglishUS));
+    hyphenator.detect_with(fallback);
+    let collected : String = hyphenator.hyphenate_text_detecting(undetectable).into_iter().collect();
+    assert_eq!(collected, "an-frac-tu-ous");
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn lexicon_segmentation() {
+    use kl_hyphenate::text::{Lexicon, Segmenter, WordBounds};
+
+    let lexicon = Lexicon::new(vec!["ภาษา", "ไทย", "ภา"]);
+    let words = lexicon.words("ภาษาไทยง่าย and English");
+    assert_eq!(words, vec![(0, "ภาษา"), (12, "ไทย"), (21, "ง่าย"), (34, "and"), (38, "English")]);
+
+    let lexicon = Lexicon::new(vec!["ພາສາ", "ລາວ", "ភាសា", "ខ្មែរ"]);
+    let words = lexicon.words("ພາສາລາວ, ភាសាខ្មែរ");
+    assert_eq!(words, vec![(0, "ພາສາ"), (12, "ລາວ"), (23, "ភាសា"), (35, "ខ្មែរ")]);
+
+    // Without a lexicon, runs of unspaced script are kept whole.
+    let words = WordBounds.words("ภาษาไทย (ဗမာစာ)");
+    assert_eq!(words, vec![(0, "ภาษาไทย"), (23, "ဗမာစာ")]);
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn multilingual_spans() {
+    use kl_hyphenate::registry::{Registry, Source};
+    use kl_hyphenate::text::Multilingual;
+
+    let registry = Registry::new().source(Source::Dir(DICTIONARIES.into()));
+    let hyphenator = Multilingual::new(&registry);
+    let text = "Je dis : anfractuous, anfractuous";
+    let spans = vec![(0 .. 8, French), (9 .. 21, EnglishUS)];
+    let collected : String = hyphenator.hyphenate_spans(text, &spans).into_iter().collect();
+    assert_eq!(collected, "Je dis : an-frac-tu-ous, anfractuous");
+
+    let nowhere = Registry::new().source(Source::Dir("/nonexistent".into()));
+    let failing = Multilingual::new(&nowhere);
+    assert!(failing.dictionary(EnglishUS).is_none());
+    assert!(failing.hyphenate_spans(text, &spans).breaks.is_empty());
+}
+
+#[test]
+fn stem_memoization() {
+    use kl_hyphenate::cache::Stemmed;
+    use kl_hyphenate::packed::{self, Packed};
+    use kl_hyphenate::score::{Score, Stems};
+
+    let words = ["hyphenate", "hyphenated", "hyphenating", "hyphen", "Hyphenation", "hy\u{00ad}phe\u{00ad}nates"];
+    let en_us = Stemmed::new(EN_US.clone(), 16);
+    for word in words.iter() {
+        assert_eq!(en_us.hyphenate(word), EN_US.hyphenate(word));
+    }
+    let words = ["asszonyság", "asszonyságok", "asszonyságoknak", "asszony", "pályázatot", "pályázatokat"];
+    let hu = Stemmed::new(HU.clone(), 16);
+    for word in words.iter() {
+        assert_eq!(hu.hyphenate(word), HU.hyphenate(word));
+    }
+
+    let bytes = packed::pack(&TR);
+    let tr = Packed::from_bytes(Turkish, &bytes).unwrap();
+    let stems = Stems::new(4);
+    for word in ["işçi", "işçiler", "işçilerimiz", "işçilerimizin", "hükümet", "hükümetin"].iter() {
+        assert_eq!(tr.score_with(word, &stems), TR.score(word));
+    }
+
+    fn property(words : Vec<String>) -> bool {
+        let stems = Stems::new(8);
+        words.iter().all(|word| {
+            let stem = word.chars().take(5).collect::<String>();
+            EN_US.score_with(word, &stems) == EN_US.score(word)
+                && EN_US.score_with(&stem, &stems) == EN_US.score(&stem)
+        })
+    }
+
+    quickcheck(property as fn(Vec<String>) -> bool);
+}
+
+#[test]
+fn length_caps() {
+    use kl_hyphenate_commons::dictionary::MAX_LENGTH;
+
+    let long = "hyphenation".repeat(100);
+    assert!(long.len() > MAX_LENGTH);
+    assert!(EN_US.hyphenate(&long).breaks.is_empty());
+    assert!(!EN_US.hyphenate(&long[.. 990]).breaks.is_empty());
+
+    let en_us = EN_US.clone().with_max_length(8);
+    assert!(en_us.hyphenate("hyphenation").breaks.is_empty());
+    assert_eq!(en_us.hyphenate("hy\u{00ad}phen\u{00ad}ation").breaks, vec![2, 8]);
+    assert_eq!(en_us.hyphenate("anfract").breaks, EN_US.hyphenate("anfract").breaks);
+    let mut buffer = vec![0];
+    en_us.hyphenate_into("hyphenation", &mut buffer);
+    assert!(buffer.is_empty());
+
+    let turkish = "İ".repeat(50_000);
+    let tr = TR.clone().with_max_length(usize::MAX);
+    assert!(tr.hyphenate(&turkish).breaks.iter().all(|&i| turkish.is_char_boundary(i)));
+}
+
+#[test]
+fn bytewise_boundaries() {
+    use kl_hyphenate::vocabulary::Vocabulary;
+
+    fn property(word : String, l_min : u8, r_min : u8) -> bool {
+        let (l_min, r_min) = (l_min as usize % 4, r_min as usize % 4 + 1);
+        let vocabulary = Vocabulary { minima : (l_min, r_min), .. Vocabulary::default() };
+        let expected = if word.chars().count() >= l_min + r_min {
+            word.char_indices().nth(l_min).map(|(l, _)| l)
+                .map(|l| (l, word.char_indices().rev().nth(r_min - 1).unwrap().0))
+        } else { None };
+        vocabulary.boundaries(&word) == expected
+    }
+
+    quickcheck(property as fn(String, u8, u8) -> bool);
+    assert_eq!(EN_US.boundaries("hyphenation"), Some((2, 8)));
+    assert_eq!(EN_US.boundaries("çàèéù"), Some((4, 4)));
+    assert_eq!(EN_US.boundaries("hy"), None);
+}
+
+#[test]
+fn storage_backends() {
+    use std::sync::Arc;
+    use kl_hyphenate::score::{Score, Stems};
+    use kl_hyphenate_commons::storage::{Backend, Storage};
+
+    assert_eq!(EN_US.patterns.automaton.backend(), Backend::DoubleArray);
+    let patterns = EN_US.patterns.with_backend(Backend::HashTable).unwrap();
+    assert_eq!(patterns.automaton.backend(), Backend::HashTable);
+    assert_eq!(patterns.entries(), EN_US.patterns.entries());
+    let en_us = Standard { patterns : Arc::new(patterns), .. EN_US.clone() };
+    let stems = Stems::new(4);
+    for word in ["hyphenation", "anfractuous", "firkin", "associate", "a"].iter() {
+        assert_eq!(en_us.score(word), EN_US.score(word));
+        assert_eq!(en_us.score_with(word, &stems), EN_US.score(word));
+    }
+
+    let patterns = HU.patterns.with_backend(Backend::HashTable).unwrap();
+    let hu = Extended { patterns : Arc::new(patterns), .. HU.clone() };
+    for word in ["asszonnyal", "asszonyságoknak", "pályázatot", "karrier"].iter() {
+        assert_eq!(hu.hyphenate(word), HU.hyphenate(word));
+    }
+    assert_eq!(hu.patterns.with_backend(Backend::DoubleArray).unwrap(), *HU.patterns);
+}
+
+#[test]
+fn on_disk_dictionaries() {
+    use std::io::Cursor;
+    use kl_hyphenate::disk::OnDisk;
+    use kl_hyphenate::packed;
+
+    let path = env::temp_dir().join("kl-hyphenate-on-disk.tr.packed");
+    fs::write(&path, packed::pack(&TR)).unwrap();
+    let tr = OnDisk::from_path(Turkish, &path).unwrap();
+    for word in ["hükümet", "İSTANBUL", "ka\u{00ad}rar", "ol", "işçilerimizin", "x"].iter() {
+        assert_eq!(tr.hyphenate(word), TR.hyphenate(word));
+    }
+    assert!(OnDisk::from_path(EnglishUS, &path).is_err());
+    fs::remove_file(&path).unwrap();
+
+    let bytes = packed::pack(&EN_US);
+    assert!(OnDisk::any_open(Cursor::new(&bytes[.. bytes.len() - 1])).is_err());
+    let mut corrupt = bytes.clone();
+    corrupt[8 .. 12].copy_from_slice(&u32::MAX.to_le_bytes());
+    match OnDisk::any_open(Cursor::new(&corrupt)) {
+        Err(Error::Malformed(_)) => (),
+        other => panic!("unexpected result: {:?}", other.map(|dict| dict.language))
+    }
+    let (mut on_disk, mut en_us) = (OnDisk::open(EnglishUS, Cursor::new(&bytes)).unwrap(), EN_US.clone());
+    for word in ["hyphenation", "anfractuous", "firkin", "table"].iter().cloned()
+        .chain(EN_US.exceptions.iter().map(|(word, _)| word)) {
+        assert_eq!(on_disk.hyphenate(word), EN_US.hyphenate(word));
+    }
+    assert_eq!(on_disk.add_exact_marked("co-associative"), en_us.add_exact_marked("co-associative"));
+    assert_eq!(on_disk.hyphenate("coassociative"), en_us.hyphenate("coassociative"));
+    assert_eq!(on_disk.remove_exact("hyphenation"), en_us.remove_exact("hyphenation"));
+    assert_eq!(on_disk.hyphenate("hyphenation"), en_us.hyphenate("hyphenation"));
+    assert_eq!(on_disk.into_inner().into_inner(), &bytes);
+}
+
+#[test]
+fn dictionary_diffs() {
+    use kl_hyphenate::diff::{diff, Difference};
+
+    let strict = EN_US.clone().with_minima((4, 4));
+    let words = ["hyphenation", "is", "accents", "hyphenation"];
+    let differences = diff(&*EN_US, &strict, &words);
+    assert_eq!(differences.len(), 3);
+    assert_eq!(differences[0], Difference { word : "hyphenation".to_owned(), before : vec![2, 6, 7], after : vec![6, 7] });
+    assert_eq!(differences[1].marked("-"), ("ac-cents".to_owned(), "accents".to_owned()));
+    assert!(diff(&*EN_US, &*EN_US, &words).is_empty());
+}
+
+#[test]
+fn dictionary_evaluation() {
+    use kl_hyphenate::evaluate::{evaluate, Evaluation};
+
+    let gold = ["hyphen-a-tion", "ac-cents", "", "no-ble", "an-fract-u-ous"];
+    let evaluation = evaluate(&*EN_US, gold);
+    assert_eq!(evaluation, Evaluation {
+        words : 4, correct : 2, found : 6, spurious : 2, missed : 1,
+        mistaken : vec!["hyphen-a-tion".to_owned(), "an-fract-u-ous".to_owned()]
+    });
+    assert_eq!(evaluation.precision(), 0.75);
+    assert_eq!(evaluation.accuracy(), 0.5);
+    assert_eq!(evaluate(&*EN_US, Vec::<String>::new()).recall(), 1.0);
+    // Words are folded by the dictionary, as the language folds them.
+    let tr = Standard::from_pattern_strings(Turkish, &["ı1r"]).unwrap().with_minima((1, 1));
+    assert_eq!(evaluate(&tr, ["KI-RIM"]).correct, 1);
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn corpus_statistics() {
+    use kl_hyphenate::stats::Statistics;
+
+    let corpus = ["I know noble accents, and rhythms too.", "Anfractuous rhythms; Rhythms!", ""];
+    let stats = Statistics::gather(&*EN_US, corpus);
+    assert_eq!((stats.words, stats.unbroken), (10, 7));
+    assert_eq!(stats.histogram, vec![7, 2, 0, 1]);
+    assert_eq!(stats.unbroken_percentage(), 70.0);
+    assert_eq!(stats.mean_breaks(), 0.5);
+    assert_eq!(stats.most_frequent_unhyphenatable(5), vec![("rhythms", 3)]);
+}
+
+#[test]
+fn pattern_introspection() {
+    use kl_hyphenate_commons::dictionary::Locus;
+
+    let letters = |patterns : Vec<(String, &Vec<Locus>)>| patterns.into_iter().map(|(p, _)| p).collect::<Vec<_>>();
+    assert_eq!(letters(EN_US.patterns.with_prefix(".as")), vec![".asc", ".asp", ".ass", ".aster"]);
+    assert_eq!(EN_US.patterns.entries().len(), fs::read_to_string("patterns/hyph-en-us.pat.txt").unwrap().lines().count());
+
+    let matches = EN_US.patterns.matches("asso");
+    let found : Vec<_> = matches.iter().map(|(i, p, _)| (*i, p.as_str())).collect();
+    assert_eq!(found, vec![(0, ".ass"), (2, "ss"), (3, "so")]);
+    assert_eq!(matches[0].2, &vec![Locus { index : 3, value : 1 }]);
+    assert_eq!(HU.patterns.matches("asso").len(), HU.patterns.entries().iter()
+        .filter(|(p, _)| ".asso.".contains(p.as_str())).count());
+}
+
+#[test]
+fn dictionary_provenance() {
+    use kl_hyphenate_commons::provenance::{date, Provenance};
+
+    let release = Some("tex-hyphen@49706f9cfa97f6ead26b473ec10d23d5a651318a".to_owned());
+    assert_eq!(EN_US.provenance.release, release);
+    assert_eq!(EN_US.provenance.sources, vec!["hyph-en-us.pat.txt", "hyph-en-us.hyp.txt"]);
+    assert_eq!(HU.provenance.license.as_deref(), Some("MPL-1.1"));
+    assert!(EN_US.provenance.built.iter().all(|date| date.len() == 10));
+    assert_eq!((date(0), date(951_782_400)), ("1970-01-01".to_owned(), "2000-02-29".to_owned()));
+
+    let merged = EN_US.merge(&TR).unwrap().provenance;
+    assert_eq!(merged.release, release);
+    assert_eq!(merged.sources.len(), 3);
+    let licensed = |license : &str| Provenance { license : Some(license.to_owned()), .. Provenance::default() };
+    assert_eq!(licensed("MIT").merge(&licensed("MPL-1.1")).license.as_deref(), Some("(MIT) AND (MPL-1.1)"));
+}
+
+#[test]
+fn reproducible_builds() {
+    let build = |patterns : &[&str], exceptions : &[&str]| {
+        let mut dict = Standard::from_pattern_strings(EnglishUS, patterns).unwrap();
+        for &marked in exceptions { dict.add_exact_marked(marked); }
+        Format::Bincode.serialize(&dict).unwrap()
+    };
+    let patterns = ["hy3ph", "he2n", "hena4", "hen5at", "1na", "n2at", "1tio", "2io", "o2n"];
+    let exceptions = ["ta-ble", "pro-ject", "as-so-ciate", "re-cord"];
+    let (mut shuffled, mut reordered) = (patterns.to_vec(), exceptions.to_vec());
+    shuffled.reverse();
+    reordered.rotate_left(2);
+    assert_eq!(build(&patterns, &exceptions), build(&shuffled, &reordered));
+
+    let built = fs::read(dictionary("en-us.standard.bincode")).unwrap();
+    assert_eq!(Format::Bincode.serialize(&*EN_US).unwrap(), built);
+}
+
+#[test]
+fn ligatures() {
+    let segments = |word : &str| -> Vec<String> { EN_US.hyphenate(word).into_iter().segments().map(String::from).collect() };
+    assert_eq!(segments("\u{FB01}nancial"), segments("financial").iter().map(|s| s.replacen("fi", "\u{FB01}", 1)).collect::<Vec<_>>());
+    assert_eq!(EN_US.hyphenate("e\u{FB00}ective").breaks, EN_US.hyphenate("effective").breaks[1 ..].iter().map(|&i| i + 1).collect::<Vec<_>>());
+    assert_eq!(segments("e\u{FB00}ective").concat(), "e\u{FB00}ective");
+}
+
+#[test]
+fn typographic_apostrophes() {
+    let elision = Standard::from_pattern_strings(French, &["'1", "1mm"]).unwrap().with_minima((1, 1));
+    assert_eq!(elision.hyphenate("l'homme").breaks, vec![2, 4]);
+    assert_eq!(elision.hyphenate("L\u{2019}HOMME").breaks, vec![4, 6]);
+    let segments : Vec<_> = elision.hyphenate("l\u{2019}homme").into_iter().segments().collect();
+    assert_eq!(segments, vec!["l\u{2019}", "ho", "mme"]);
+
+    let mut elision = elision;
+    elision.add_exact_marked("qu\u{2019}-il");
+    assert_eq!(elision.hyphenate("qu'il").breaks, vec![3]);
+}
+
+#[test]
+fn zero_width_joiners() {
+    let hi = fiat_std(Hindi);
+    let word = "विश्वविद्यालय";
+    let breaks = hi.hyphenate(word).breaks;
+    for joiner in &["\u{200C}", "\u{200D}"] {
+        let joined = word.replacen("\u{94d}", &["\u{94d}", joiner].concat(), 1);
+        let shifted : Vec<_> = breaks.iter().map(|&i| if i > 9 { i + 3 } else { i }).collect();
+        assert_eq!(hi.hyphenate(&joined).breaks, shifted);
+
+        let leading = [&word[.. 6], joiner, &word[6 ..]].concat();
+        let segments : Vec<_> = hi.hyphenate(&leading).into_iter().segments().collect();
+        assert_eq!(segments[0], ["वि", joiner].concat());
+    }
+}
+
+#[test]
+fn joiners_within_subregions() {
+    for &word in &["as\u{200D}szonnyal", "asszon\u{200D}nyal", "\u{200C}asszonnyal"] {
+        let hyphenated = HU.hyphenate(word);
+        let spellings = hyphenated.spellings();
+        let discretionaries = hyphenated.discretionaries();
+        assert_eq!(spellings.len(), discretionaries.len());
+        for (&(i, subr), d) in hyphenated.breaks.iter().zip(&discretionaries) {
+            if let Some(subr) = subr {
+                assert_eq!(&word[d.span.clone()].len(), &(subr.left() + subr.right()), "{}", i);
+            }
+        }
+        let mut breaks = Vec::new();
+        HU.hyphenate_into(word, &mut breaks);
+        assert_eq!(breaks, hyphenated.breaks);
+    }
+    assert_eq!(respellings(&HU, "as\u{200D}szonnyal"), vec![(9, Some("nyny".to_owned()))]);
+    assert_eq!(respellings(&HU, "\u{200C}asszonnyal"), vec![(5, Some("sz".to_owned())), (9, Some("nyny".to_owned()))]);
+
+    let subr = Subregion::new(3, 0, "x", 1);
+    assert_eq!(subr.span(2), None);
+    assert_eq!(subr.split("ab", 2), None);
+    assert_eq!(Subregion::new(1, 1, "xx", 1).split("a\u{e9}", 1), None);
+}
+
+/// Whether every break of the word falls between its grapheme clusters.
+#[cfg(feature = "graphemes")]
+fn between_clusters<'h, H : Hyphenator<'h, Opportunity = usize>>(dict : &'h H, word : &str) -> bool {
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let starts : Vec<_> = word.grapheme_indices(true).map(|(i, _)| i).collect();
+    dict.hyphenate(word).breaks.iter().all(|i| starts.contains(i))
+}
+
+#[cfg(feature = "graphemes")]
+#[test]
+fn grapheme_clusters() {
+    let anywhere = Standard::from_pattern_strings(EnglishUS, &["a1"]).unwrap().with_minima((1, 1));
+    assert_eq!(anywhere.hyphenate("banana").breaks, vec![2, 4]);
+    assert_eq!(anywhere.hyphenate("ba\u{332}nana").breaks, vec![6]);
+    assert_eq!(anywhere.hyphenate("ba\u{332}\u{ad}nana").breaks, vec![4]);
+
+    let indic = [
+        (Hindi, "विश्वविद्यालय प्रतिनिधित्व अंतर्राष्ट्रीय स्वतन्त्रता क्षत्रिय"),
+        (Bengali, "বিশ্ববিদ্যালয় স্বাধীনতা আন্তর্জাতিক প্রতিষ্ঠান"),
+        (Tamil, "பல்கலைக்கழகம் தமிழ்நாடு கட்டுப்பாடு"),
+        (Malayalam, "സർവ്വകലാശാല ഇന്ത്യൻ അന്താരാഷ്ട്ര"),
+        (Telugu, "విశ్వవిద్యాలయం స్వాతంత్ర్యం")
+    ];
+    for &(lang, corpus) in &indic {
+        let dict = fiat_std(lang);
+        assert!(corpus.split(' ').all(|word| between_clusters(&dict, word)), "{:?}", lang);
+    }
+
+    // Vietnamese stacks tone marks over vowels which may carry their own.
+    let vietnamese = "nghie\u{302}\u{323}p tie\u{302}\u{301}ng tru\u{31b}o\u{31b}\u{300}ng qua\u{309}n nguye\u{302}\u{303}n";
+    let syllables = Standard::from_pattern_strings(EnglishUS, &["e1", "o1", "a1", "u1", "y1", "1n", "1g"]).unwrap()
+        .with_minima((1, 1)).with_normalization(Some(NormalizationForm::Nfd));
+    for word in vietnamese.split(' ') {
+        assert!(!syllables.hyphenate(word).breaks.is_empty());
+        assert!(between_clusters(&syllables, word), "{}", word);
+        assert!(between_clusters(&syllables, &NormalizationForm::Nfc.apply(word)), "{}", word);
+    }
+}
+
+#[test]
+fn punctuated_tokens() {
+    let breaks = EN_US.hyphenate("hyphenation").breaks;
+    assert_eq!(EN_US.hyphenate("(hyphenation),").breaks, breaks.iter().map(|&i| i + 1).collect::<Vec<_>>());
+    assert_eq!(EN_US.hyphenate("hyphenation.").breaks, breaks);
+    let segments : Vec<_> = EN_US.hyphenate("\u{201c}hyphenation!\u{201d}").into_iter().segments().collect();
+    assert_eq!(segments, vec!["\u{201c}hy", "phen", "a", "tion!\u{201d}"]);
+    assert!(EN_US.hyphenate("--").breaks.is_empty());
+    assert!(EN_US.hyphenate("(an)").breaks.is_empty());
+
+    let spellings : Vec<_> = HU.hyphenate("\u{bb}asszonnyal\u{ab}").into_iter().segments().collect();
+    assert_eq!(spellings, vec!["\u{bb}asz", "szony", "nyal\u{ab}"]);
+}
+
+#[test]
+fn preprocessed_words() {
+    use kl_hyphenate::preprocess::{self, Preprocessed, Shift};
+
+    let long_s = |word : &str| preprocess::map_chars(word, |c, mapped| match c {
+        '\u{17f}' => mapped.push('s'),
+        c => mapped.push(c)
+    });
+    let modern = Preprocessed::new(fiat_std(EnglishUS), long_s);
+    let segments : Vec<_> = modern.hyphenate("poſſeſſion").into_iter().segments().collect();
+    assert_eq!(segments, vec!["poſ", "ſeſ", "ſion"]);
+    assert_eq!(EN_US.hyphenate("possession").into_iter().segments().collect::<Vec<_>>(), vec!["pos", "ses", "sion"]);
+
+    let hu = Preprocessed::new(fiat_ext(Hungarian), long_s);
+    assert_eq!(respellings(hu.inner(), "asszonnyal").iter().map(|b| b.0).collect::<Vec<_>>(), vec![2, 6]);
+    let breaks : Vec<_> = hu.hyphenate("aſſzonnyal").breaks.into_iter().map(|(i, subr)| (i, subr.map(|s| s.substitution()))).collect();
+    // The subregion of the first break spans the long `ſ`s, which its
+    // substitution cannot stand for.
+    assert_eq!(breaks, vec![(8, Some("nyny"))]);
+
+    // OCR output confusing `m` with `rn`, whose breaks fall before the `r`.
+    let ocr = Preprocessed::new(fiat_std(EnglishUS), |word : &str| {
+        let shifts = word.match_indices("rn").enumerate()
+            .map(|(k, (i, _))| Shift::new(i - k, -(k as isize + 1))).collect();
+        (word.replace("rn", "m"), shifts, vec![])
+    });
+    assert_eq!(ocr.hyphenate("cornputer").breaks, EN_US.hyphenate("computer").breaks.iter()
+        .map(|&i| if i > 2 { i + 1 } else { i }).collect::<Vec<_>>());
+
+    // Breaks within the chars of an expansion have no place in the original.
+    let ks = Preprocessed::new(Standard::from_pattern_strings(EnglishUS, &["k1s"]).unwrap().with_minima((1, 1)),
+        |word : &str| preprocess::map_chars(word, |c, mapped| match c {
+            'x' => mapped.push_str("ks"),
+            c => mapped.push(c)
+        }));
+    assert_eq!(ks.inner().hyphenate("taksi").breaks, vec![3]);
+    assert!(ks.hyphenate("taxi").breaks.is_empty());
+}
+
+#[test]
+fn decomposed_words() {
+    let fr = fiat_std(French);
+    for &(composed, decomposed) in &[("cafétéria", "cafe\u{301}te\u{301}ria"), ("Réélection", "Re\u{301}e\u{301}lection")] {
+        let segments : Vec<_> = fr.hyphenate(decomposed).into_iter().segments().collect();
+        let recomposed : Vec<String> = segments.iter().map(|s| NormalizationForm::Nfc.apply(s)).collect();
+        assert_eq!(recomposed, fr.hyphenate(composed).into_iter().segments().collect::<Vec<_>>());
+        assert!(segments.len() > 1);
+    }
+}
+
+#[test]
+fn runtime_normalization() {
+    let composed = Standard::from_pattern_strings(French, &["\u{e9}1t"]).unwrap().with_minima((1, 1));
+    let decomposed = "Re\u{301}te";
+    assert_eq!(composed.hyphenate(decomposed).breaks, vec![4]);
+    let decomposed_patterns = Standard::from_pattern_strings(French, &["e\u{301}1t"]).unwrap().with_minima((1, 1));
+    assert!(decomposed_patterns.hyphenate(decomposed).breaks.is_empty());
+    let nfd = decomposed_patterns.with_normalization(Some(NormalizationForm::Nfd));
+    assert_eq!(nfd.hyphenate("R\u{e9}te").breaks, vec![3]);
+    let bytes = kl_hyphenate::packed::pack(&nfd);
+    let packed = kl_hyphenate::packed::Packed::from_bytes(French, &bytes).unwrap();
+    assert_eq!(packed.normalization, Some(NormalizationForm::Nfd));
+    assert_eq!(packed.hyphenate("R\u{e9}te").breaks, vec![3]);
+
+    let mut nfc = composed.with_normalization(Some(NormalizationForm::Nfc));
+    let segments : Vec<_> = nfc.hyphenate(decomposed).into_iter().segments().collect();
+    assert_eq!(segments, vec!["Re\u{301}", "te"]);
+    assert_eq!(nfc.hyphenate("R\u{e9}te").breaks, vec![3]);
+
+    nfc.add_exact_marked("te\u{301}-te\u{301}");
+    assert_eq!(nfc.hyphenate("te\u{301}te\u{301}").breaks, vec![4]);
+    assert_eq!("nfkd".parse(), Ok(NormalizationForm::Nfkd));
+
+    let reloaded = Standard::from_bytes(French, &Format::Bincode.serialize(&nfc).unwrap()).unwrap();
+    assert_eq!(reloaded.normalization, Some(NormalizationForm::Nfc));
+    assert_eq!(reloaded.hyphenate(decomposed).breaks, vec![4]);
+    assert_eq!(EN_US.normalization, None);
+}
+
+#[test]
+fn respelled_exceptions() {
+    use kl_hyphenate_commons::dictionary::extended as ext;
+    use kl_hyphenate_commons::parse::Parse;
+
+    let verbatim = |s : &str| s.to_owned();
+    assert_eq!(ext::Exceptions::pair("ta-ble", verbatim), ("table".to_owned(), vec![(2, None)]));
+    let (word, breaks) = ext::Exceptions::pair("schiffahrt schiff-fahrt", verbatim);
+    let mut hu = HU.clone();
+    hu.add_exact(word, breaks);
+    let segments : Vec<_> = hu.hyphenate("schiffahrt").into_iter().segments().collect();
+    assert_eq!(segments, vec!["schiff", "fahrt"]);
+
+    let ca = fiat_ext(Catalan);
+    assert!(!ca.exceptions.0.is_empty());
+    assert!(ca.provenance.sources.iter().any(|source| source == "hyph-ca.hyp.txt"));
+}
+
+#[test]
+fn traditional_german() {
+    let de = fiat_ext(German1901);
+    assert_eq!(respellings(&de, "Zucker"), vec![(3, Some("kk".to_owned()))]);
+    assert_eq!(respellings(&de, "Blickfeld"), vec![(5, None)]);
+    assert_eq!(respellings(&de, "Rückantwort"), vec![(5, None), (8, None)]);
+    assert_eq!(respellings(&de, "Druckabfall"), vec![(5, None), (7, None)]);
+    assert_eq!(respellings(&de, "Blickachse"), vec![(5, None), (8, None)]);
+    assert_eq!(respellings(&de, "Stückarbeit"), vec![(6, None), (8, None)]);
+    assert_eq!(respellings(&de, "Backofen"), vec![(4, None)]);
+    assert_eq!(respellings(&de, "Schiffahrt"), vec![(6, Some("f".to_owned()))]);
+    assert_eq!(respellings(&de, "Stillegung"), vec![(5, Some("l".to_owned())), (6, None)]);
+    assert_eq!(de.provenance.sources, vec!["hyph-de-1901.ext.txt", "hyph-de-1901.pat.txt", "hyph-de-1901.ext.hyp.txt"]);
+}
+
+#[test]
+fn dutch_respellings() {
+    let nl = fiat_ext(Dutch);
+    assert_eq!(respellings(&nl, "ideeën"), vec![(4, Some("ee".to_owned()))]);
+    assert_eq!(respellings(&nl, "coördinatie")[0], (2, Some("oo".to_owned())));
+    assert_eq!(respellings(&nl, "cafeetje"), vec![(5, Some("é".to_owned()))]);
+    assert_eq!(respellings(&nl, "kadootje"), vec![(2, None), (4, Some(String::new()))]);
+    assert_eq!(respellings(&nl, "vaatje"), vec![(4, None)]);
+}
+
+#[test]
+fn norwegian_respellings() {
+    let (nb, nn) = (fiat_ext(NorwegianBokmal), fiat_ext(NorwegianNynorsk));
+    assert_eq!(respellings(&nb, "busstopp"), vec![(4, Some("s".to_owned()))]);
+    assert_eq!(respellings(&nb, "fullastet"), vec![(4, Some("l".to_owned())), (6, None)]);
+    assert_eq!(respellings(&nn, "nattog"), vec![(4, Some("t".to_owned()))]);
+    assert_eq!(respellings(&nn, "bussen"), vec![(3, None)]);
+}
+
+#[test]
+fn swedish_respellings() {
+    let sv = fiat_ext(Swedish);
+    assert_eq!(respellings(&sv, "glasskål"), vec![(5, Some("s".to_owned()))]);
+    assert_eq!(respellings(&sv, "tillåta"), vec![(4, Some("l".to_owned())), (6, None)]);
+    assert!(sv.provenance.sources.iter().any(|source| source == "hyph-sv.ext.hyp.txt"));
+}
+
+#[test]
+fn pattern_generation() {
+    use kl_hyphenate::patgen::{Generator, Level};
+
+    let generator = Generator::new(vec![Level::new((2, 2), 1, 1, 1)]).with_minima((1, 1));
+    assert_eq!(generator.patterns(["ba-na"]), vec!["a1n", "ba1", "1na"]);
+
+    let words = fs::read_to_string("patterns/hyph-en-us.hyp.txt").unwrap();
+    let words : Vec<&str> = words.lines().map(str::trim).filter(|w| !w.is_empty()).collect();
+    let generator = Generator::new((4 .. 8).map(|longest| Level::new((1, longest), 1, 1, 1)).collect())
+        .with_minima((1, 1));
+    let generated = generator.standard(EnglishUS, &words).unwrap();
+    let correct = words.iter().filter(|marked| {
+        let word = marked.replace('-', "");
+        generated.hyphenate(&word).into_iter().segments().collect::<Vec<_>>().join("-") == **marked
+    }).count();
+    assert!(correct * 10 > words.len() * 9);
+}
+
+#[test]
+fn memory_footprints() {
+    let footprint = EN_US.memory_footprint();
+    assert!(footprint.automaton > 0 && footprint.tallies > 0 && footprint.exceptions > 0);
+    assert_eq!(footprint.total(), footprint.automaton + footprint.tallies + footprint.exceptions);
+
+    let mut en_us = EN_US.clone();
+    en_us.clear_exceptions();
+    assert_eq!(en_us.memory_footprint().automaton, footprint.automaton);
+    let cleared = en_us.memory_footprint().exceptions;
+    for i in 0 .. 1000 {
+        en_us.add_exact_marked(&format!("hy-phen-a-tion{}", i));
+    }
+    assert!(en_us.memory_footprint().exceptions > cleared + 1000 * "hyphenation".len());
+
+    let hu = HU.memory_footprint();
+    assert!(hu.total() > hu.automaton && hu.tallies > 0);
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn c_interface() {
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+    use kl_hyphenate::ffi::*;
+
+    let (en_us, path) = (CString::new("en-us").unwrap(), CString::new(dictionary("en-us.standard.bincode").to_str().unwrap()).unwrap());
+    let (word, marker) = (CString::new("anfractuous").unwrap(), CString::new("\u{00ad}").unwrap());
+    unsafe {
+        let dict = hyphenation_load(en_us.as_ptr(), path.as_ptr());
+        assert!(!dict.is_null());
+        let hyphenated = hyphenation_hyphenate(dict, word.as_ptr(), marker.as_ptr());
+        assert_eq!(CStr::from_ptr(hyphenated).to_str().unwrap(), "an\u{00ad}frac\u{00ad}tu\u{00ad}ous");
+        hyphenation_free_string(hyphenated);
+        let hyphenated = hyphenation_hyphenate(dict, word.as_ptr(), ptr::null());
+        assert_eq!(CStr::from_ptr(hyphenated).to_str().unwrap(), "an-frac-tu-ous");
+        hyphenation_free_string(hyphenated);
+        assert!(hyphenation_hyphenate(dict, ptr::null(), ptr::null()).is_null());
+
+        let text = "anfractuous cosmopolitan";
+        let word = hyphenation_hyphenate_word(dict, text.as_ptr() as *const _, 11);
+        assert_eq!((word.text, word.len), (text.as_ptr() as *const _, 11));
+        assert_eq!(std::slice::from_raw_parts(word.breaks.indices, word.breaks.len), &[2, 6, 8]);
+        hyphenation_free_word(word);
+        let breaks = hyphenation_breaks(dict, text.as_ptr() as *const _, 2);
+        assert!(!breaks.indices.is_null() && breaks.len == 0);
+        hyphenation_free_breaks(breaks);
+        assert!(hyphenation_breaks(dict, b"\xff".as_ptr() as *const _, 1).indices.is_null());
+        hyphenation_free(dict);
+
+        let tr = CString::new("tr").unwrap();
+        assert!(hyphenation_load(tr.as_ptr(), path.as_ptr()).is_null());
+        assert!(hyphenation_load(ptr::null(), path.as_ptr()).is_null());
+    }
+}
+
+#[cfg(feature = "python")]
+#[test]
+fn python_bindings() {
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+    use kl_hyphenate::python::Dictionary;
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let locals = PyDict::new(py);
+        locals.set_item("en_us", Py::new(py, Dictionary(EN_US.clone())).unwrap()).unwrap();
+        let check = |expression : &str, expected : &str| {
+            let found = py.eval(&std::ffi::CString::new(expression).unwrap(), None, Some(&locals)).unwrap();
+            assert_eq!(found.repr().unwrap().to_string(), expected);
+        };
+        check("en_us", "Dictionary(\"en-us\")");
+        check("en_us.hyphenate('anfractuous')", "'an-frac-tu-ous'");
+        check("en_us.segments('anfractuous')", "['an', 'frac', 'tu', 'ous']");
+        check("en_us.breaks('\u{e7}\u{e0}hyphenation')", "[4, 8]");
+        check("en_us.wrap('Hyphenation is accomplished by the dictionary', 18)",
+              "['Hyphenation is ac-', 'complished by the', 'dictionary']");
+    });
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn command_line() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    fn hyphenate(args : &[&str], input : &str) -> (Option<i32>, String) {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_hyphenate")).args(args)
+            .env("KL_HYPHENATE_DICTIONARIES", DICTIONARIES)
+            .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null())
+            .spawn().unwrap();
+        // The tool may exit upon a usage error before reading its input.
+        let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+        let output = child.wait_with_output().unwrap();
+        (output.status.code(), String::from_utf8(output.stdout).unwrap())
+    }
+
+    assert_eq!(hyphenate(&["-l", "en-us"], "I know noble accents\n"), (Some(0), "I know no-ble ac-cents\n".to_owned()));
+    assert_eq!(hyphenate(&["--language=en-US", "--words", "--marker", "\\-"], "anfractuous\n\nhyphenation\n"),
+               (Some(0), "an\\-frac\\-tu\\-ous\n\nhy\\-phen\\-a\\-tion\n".to_owned()));
+    assert_eq!(hyphenate(&["-l", "en-us", "-w", "-m", "shy", "--minima", "4,4"], "hyphenation\n"),
+               (Some(0), "hyphen\u{00ad}a\u{00ad}tion\n".to_owned()));
+    assert_eq!(hyphenate(&["-w"], "hyphenation\n").0, Some(2));
+    assert_eq!(hyphenate(&["-l", "en-us", "--minima", "4"], "").0, Some(2));
+    // Embedded dictionaries are found before those of any directory.
+    #[cfg(not(feature = "embed"))]
+    assert_eq!(hyphenate(&["-l", "tr", "-d", "nowhere"], "").0, Some(1));
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn compiling_dictionaries() {
+    use std::process::Command;
+
+    let dir = env::temp_dir().join("kl-hyphenate-compiling");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("tiny.pat.txt"), "1ba\n1na\n").unwrap();
+    fs::write(dir.join("tiny.hyp"), "ban-a-na\n").unwrap();
+    let compile = |args : &[&str]| Command::new(env!("CARGO_BIN_EXE_hyphenate")).arg("compile").args(args)
+        .current_dir(&dir).status().unwrap().code();
+
+    assert_eq!(compile(&["-l", "en-us", "--minima=1,1", "tiny.pat.txt", "-o", "tiny.bincode"]), Some(0));
+    let tiny = Standard::from_path(Language::EnglishUS, dir.join("tiny.bincode")).unwrap();
+    assert_eq!(tiny.hyphenate("banana").into_iter().segments().collect::<Vec<_>>(), vec!["ba", "na", "na"]);
+
+    assert_eq!(compile(&["-l", "en-us", "-e", "--minima", "1,1", "tiny.pat.txt", "tiny.hyp"]), Some(0));
+    let extended = Extended::from_path(Language::EnglishUS, dir.join(Extended::file_name(Language::EnglishUS))).unwrap();
+    assert_eq!(extended.hyphenate("banana").into_iter().segments().collect::<Vec<_>>(), vec!["ban", "a", "na"]);
+
+    assert_eq!(compile(&["-l", "en-us", "tiny.txt"]), Some(1));
+    assert_eq!(compile(&["tiny.pat.txt"]), Some(2));
+    fs::remove_dir_all(&dir).unwrap();
+}